@@ -34,18 +34,10 @@ pub struct Group;
 
 // callbacks need to be extern "C" to be callable from C
 extern "C" fn callback_group_create(
-    world: *mut sys::ecs_world_t,
-    group_id: u64,
+    _world: *mut sys::ecs_world_t,
+    _group_id: u64,
     _group_by_ctx: *mut c_void,
 ) -> *mut c_void {
-    let world_ref = unsafe { WorldRef::from_ptr(world) };
-    println!(
-        "Group created: {:?}",
-        world_ref.world().entity_from_id(group_id).name()
-    );
-
-    println!();
-
     let mut counter = GROUP_COUNTER.lock().unwrap();
     *counter += 1;
 
@@ -57,19 +49,13 @@ extern "C" fn callback_group_create(
 
 // callbacks need to be extern "C" to be callable from C
 extern "C" fn callback_group_delete(
-    world: *mut sys::ecs_world_t,
-    group_id: u64,
-    _ctx: *mut c_void,
+    _world: *mut sys::ecs_world_t,
+    _group_id: u64,
+    ctx: *mut c_void,
     _group_by_ctx: *mut c_void,
 ) {
-    let world_ref = unsafe { WorldRef::from_ptr(world) };
-    println!(
-        "Group deleted: {:?}",
-        world_ref.world().entity_from_id(group_id).name()
-    );
-
-    // if you have any data associated with the group, you need to free it
-    // or use the callback group_by_ctx where you pass a context to the callback
+    // free the data associated with the group
+    drop(unsafe { Box::from_raw(ctx as *mut GroupCtx) });
 }
 
 fn main() {
@@ -85,9 +71,9 @@ fn main() {
         .query::<(&Position,)>()
         .group_by::<Group>()
         // Callback invoked when a new group is created
-        .on_group_create(Some(callback_group_create))
+        .on_group_create_action(Some(callback_group_create))
         // Callback invoked when a group is deleted
-        .on_group_delete(Some(callback_group_delete))
+        .on_group_delete_action(Some(callback_group_delete))
         .build();
 
     // Create entities in 6 different tables with 3 group ids
@@ -138,7 +124,7 @@ fn main() {
 
     query.run_iter(|it, (pos,)| {
         let group = world.entity_from_id(it.group_id());
-        let ctx = unsafe { &*(query.group_context(group) as *mut GroupCtx) };
+        let ctx = unsafe { &*(query.group_context_ptr(group) as *mut GroupCtx) };
         println!(
             "Group: {:?} - Table: [{:?}] - Counter: {}",
             group.path().unwrap(),
@@ -153,14 +139,10 @@ fn main() {
         println!();
     });
 
-    // Deleting the query will call the on_group_deleted callback
+    // Deleting the query will call the on_group_delete callback
     query.destruct();
 
     // Output:
-    //  Group created: "Third"
-    //  Group created: "Second"
-    //  Group created: "First"
-    //
     //  Group: "::First" - Table: [Position, (Group,First)] - Counter: 3
     //   [Position { x: 3.0, y: 3.0 }]
     //
@@ -178,10 +160,6 @@ fn main() {
     //
     //  Group: "::Third" - Table: [Position, Tag, (Group,Third)] - Counter: 1
     //   [Position { x: 4.0, y: 4.0 }]
-    //
-    //  Group deleted: "Second"
-    //  Group deleted: "First"
-    //  Group deleted: "Third"
 }
 
 #[cfg(feature = "flecs_nightly_tests")]
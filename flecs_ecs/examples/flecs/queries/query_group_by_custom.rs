@@ -54,7 +54,7 @@ fn main() {
     // Grouped query
     let query = world
         .query::<&Position>()
-        .group_by_fn::<Group>(Some(callback_group_by_relationship))
+        .group_by_action::<Group>(Some(callback_group_by_relationship))
         .build();
 
     // Create entities in 6 different tables with 3 group ids
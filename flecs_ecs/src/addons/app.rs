@@ -1,16 +1,212 @@
 //! addon for running the main application loop.
 
-use std::ffi::c_void;
+use std::ffi::{c_int, c_void};
 
 use crate::core::*;
 use crate::sys;
 
+/// Drives the frames of an [`App`], for when the host owns the main loop, such as a windowing
+/// event loop or a test harness.
+///
+/// The frame action is called once by [`App::run()`], after the target fps, threads, REST and
+/// stats settings of the app are applied and the init callbacks ran. It runs frames by calling
+/// [`AppFrames::run_frame()`] until it returns `false`, or until the host stops calling it. The quit
+/// callbacks run after the frame action returns.
+///
+/// Frame actions are implemented for closures that take [`AppFrames`].
+///
+/// # Example
+///
+/// ```
+/// use flecs_ecs::addons::app::{AppFrames, FrameAction};
+/// use flecs_ecs::prelude::*;
+///
+/// #[derive(Component)]
+/// struct Position {
+///     x: f32,
+/// }
+///
+/// struct Harness {
+///     frames: i32,
+/// }
+///
+/// impl FrameAction for Harness {
+///     fn run(&mut self, frames: &mut AppFrames) {
+///         while frames.frame_count() < self.frames && frames.run_frame() {}
+///     }
+/// }
+///
+/// let world = World::new();
+/// let position = world.entity().set(Position { x: 0.0 });
+/// world.system::<&mut Position>().each(|p| p.x += 1.0);
+///
+/// world.app().set_frame_action(Harness { frames: 3 }).run();
+/// position.get::<&Position>(|p| assert_eq!(p.x, 3.0));
+/// ```
+pub trait FrameAction {
+    /// Runs the frames of the app.
+    ///
+    /// # Arguments
+    ///
+    /// * `frames` - Runs the frames, with the frame callbacks of the app.
+    fn run(&mut self, frames: &mut AppFrames);
+}
+
+impl<F> FrameAction for F
+where
+    F: FnMut(&mut AppFrames),
+{
+    fn run(&mut self, frames: &mut AppFrames) {
+        self(frames);
+    }
+}
+
+/// A callback that is called at the beginning or end of every frame of an [`App`].
+type FrameHook = Box<dyn FnMut(WorldRef, FTime)>;
+
+/// A callback that is called once when an [`App`] starts or quits.
+type AppHook = Box<dyn FnOnce(WorldRef)>;
+
+/// Runs the frames of an [`App`], passed to [`FrameAction::run()`].
+pub struct AppFrames<'a> {
+    world: WorldRef<'a>,
+    desc: *const sys::ecs_app_desc_t,
+    on_frame_begin: Vec<FrameHook>,
+    on_frame_end: Vec<FrameHook>,
+    frame_count: i32,
+    result: c_int,
+}
+
+impl<'a> AppFrames<'a> {
+    /// Runs a single frame, which calls the frame begin callbacks, progresses the world and
+    /// calls the frame end callbacks.
+    ///
+    /// The frame end callbacks are not called for the frame in which the app stops, after
+    /// [`World::quit()`] was called, and the frame isn't counted by
+    /// [`AppFrames::frame_count()`].
+    ///
+    /// # Returns
+    ///
+    /// Whether the app keeps running. This is `false` once [`World::quit()`] is called, or when
+    /// the number of frames set with [`App::set_frames()`] ran. Frames aren't run anymore after
+    /// this returned `false`.
+    ///
+    /// # See also
+    ///
+    /// * C API: `ecs_app_run_frame`
+    #[doc(alias = "ecs_app_run_frame")]
+    pub fn run_frame(&mut self) -> bool {
+        let frames = unsafe { (*self.desc).frames };
+        if self.result != 0 || (frames != 0 && self.frame_count >= frames) {
+            return false;
+        }
+
+        let world_ptr = self.world.world_ptr_mut();
+        let previous_delta_time = unsafe { (*sys::ecs_get_world_info(world_ptr)).delta_time };
+        for on_frame_begin in &mut self.on_frame_begin {
+            on_frame_begin(self.world, previous_delta_time);
+        }
+
+        crate::addons::pipeline::resolve_if_dirty(self.world);
+        self.result = unsafe { sys::ecs_app_run_frame(world_ptr, self.desc) };
+        if self.result != 0 {
+            return false;
+        }
+        self.frame_count += 1;
+
+        let delta_time = unsafe { (*sys::ecs_get_world_info(world_ptr)).delta_time };
+        for on_frame_end in &mut self.on_frame_end {
+            on_frame_end(self.world, delta_time);
+        }
+
+        frames == 0 || self.frame_count < frames
+    }
+
+    /// Returns the number of frames that ran.
+    pub fn frame_count(&self) -> i32 {
+        self.frame_count
+    }
+}
+
+impl<'a> WorldProvider<'a> for AppFrames<'a> {
+    #[inline(always)]
+    fn world(&self) -> WorldRef<'a> {
+        self.world
+    }
+}
+
+/// The Rust callbacks of an [`App`], stored in the world while the app runs.
+#[derive(Default)]
+pub(crate) struct AppHooks {
+    on_init: Vec<AppHook>,
+    on_frame_begin: Vec<FrameHook>,
+    on_frame_end: Vec<FrameHook>,
+    on_quit: Vec<AppHook>,
+    frame_action: Option<Box<dyn FrameAction>>,
+}
+
+impl AppHooks {
+    fn is_empty(&self) -> bool {
+        self.on_init.is_empty()
+            && self.on_frame_begin.is_empty()
+            && self.on_frame_end.is_empty()
+            && self.on_quit.is_empty()
+            && self.frame_action.is_none()
+    }
+}
+
+/// The run action of apps with Rust callbacks, which follows the default run action of flecs.
+///
+/// Worlds that run without callbacks, while the run action is installed, take the same path
+/// with empty hooks.
+unsafe extern "C" fn run_action(
+    world_ptr: *mut sys::ecs_world_t,
+    desc: *mut sys::ecs_app_desc_t,
+) -> c_int {
+    let world = WorldRef::from_ptr(world_ptr);
+    let mut hooks = world.world_ctx_mut().app_hooks.take().unwrap_or_default();
+
+    if let Some(init) = (*desc).init {
+        init(world_ptr);
+    }
+    for on_init in hooks.on_init.drain(..) {
+        on_init(world);
+    }
+
+    let mut frames = AppFrames {
+        world,
+        desc,
+        on_frame_begin: std::mem::take(&mut hooks.on_frame_begin),
+        on_frame_end: std::mem::take(&mut hooks.on_frame_end),
+        frame_count: 0,
+        result: 0,
+    };
+    match &mut hooks.frame_action {
+        Some(frame_action) => frame_action.run(&mut frames),
+        None => while frames.run_frame() {},
+    }
+
+    // ensure the quit flag is set, like the default run action
+    sys::ecs_quit(world_ptr);
+    for on_quit in hooks.on_quit.drain(..) {
+        on_quit(world);
+    }
+
+    // 1 is a normal exit, other values are error codes
+    if frames.result == 1 {
+        0
+    } else {
+        frames.result
+    }
+}
+
 /// Application interface.
 ///
 /// These are typically constructed via [`World::app()`]
 pub struct App<'a> {
     world: WorldRef<'a>,
     desc: sys::ecs_app_desc_t,
+    hooks: AppHooks,
 }
 
 impl<'a> App<'a> {
@@ -29,6 +225,7 @@ impl<'a> App<'a> {
         let mut obj = Self {
             world: world.world(),
             desc: sys::ecs_app_desc_t::default(),
+            hooks: AppHooks::default(),
         };
 
         let stats = unsafe { sys::ecs_get_world_info(obj.world.ptr_mut()) };
@@ -134,7 +331,6 @@ impl<'a> App<'a> {
         self
     }
 
-    // TODO change this to FnMut(&mut World) -> cint
     /// Set the application init action.
     ///
     /// # Arguments
@@ -143,6 +339,7 @@ impl<'a> App<'a> {
     ///
     /// # See also
     ///
+    /// * [`App::on_init()`]
     /// * C++ API: `app_builder::init`
     #[doc(alias = "app_builder::init")]
     pub fn init(&mut self, value: sys::ecs_app_init_action_t) -> &mut Self {
@@ -165,11 +362,127 @@ impl<'a> App<'a> {
         self
     }
 
+    /// Call `callback` when the application starts, before the first frame.
+    ///
+    /// Init callbacks run after the target fps, threads, REST and stats settings are applied,
+    /// and after the init action set with [`App::init()`], in the order in which they were
+    /// added.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - The callback, which is passed the world.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    /// }
+    ///
+    /// let world = World::new();
+    /// world
+    ///     .app()
+    ///     .set_frames(2)
+    ///     .on_init(|world| {
+    ///         world.entity_named("player").set(Position { x: 0.0 });
+    ///         world.system::<&mut Position>().each(|p| p.x += 1.0);
+    ///     })
+    ///     .run();
+    ///
+    /// world
+    ///     .lookup("player")
+    ///     .get::<&Position>(|p| assert_eq!(p.x, 2.0));
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`App::on_quit()`]
+    pub fn on_init(&mut self, callback: impl FnOnce(WorldRef) + 'static) -> &mut Self {
+        self.hooks.on_init.push(Box::new(callback));
+        self
+    }
+
+    /// Call `callback` at the start of every frame, before the world progresses.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - The callback, which is passed the world and the delta time of the
+    ///   previous frame.
+    ///
+    /// # See also
+    ///
+    /// * [`App::on_frame_end()`]
+    pub fn on_frame_begin(&mut self, callback: impl FnMut(WorldRef, FTime) + 'static) -> &mut Self {
+        self.hooks.on_frame_begin.push(Box::new(callback));
+        self
+    }
+
+    /// Call `callback` at the end of every frame, after the world progressed.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - The callback, which is passed the world and the delta time of the frame.
+    ///
+    /// # See also
+    ///
+    /// * [`App::on_frame_begin()`]
+    pub fn on_frame_end(&mut self, callback: impl FnMut(WorldRef, FTime) + 'static) -> &mut Self {
+        self.hooks.on_frame_end.push(Box::new(callback));
+        self
+    }
+
+    /// Call `callback` when the application quits, after the last frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - The callback, which is passed the world.
+    ///
+    /// # See also
+    ///
+    /// * [`App::on_init()`]
+    pub fn on_quit(&mut self, callback: impl FnOnce(WorldRef) + 'static) -> &mut Self {
+        self.hooks.on_quit.push(Box::new(callback));
+        self
+    }
+
+    /// Set the frame action, which runs the frames of the application instead of the default
+    /// loop.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_action` - The frame action.
+    ///
+    /// # See also
+    ///
+    /// * [`FrameAction`]
+    pub fn set_frame_action(&mut self, frame_action: impl FrameAction + 'static) -> &mut Self {
+        self.hooks.frame_action = Some(Box::new(frame_action));
+        self
+    }
+
     /// Run application. This will run the application with the parameters specified in desc.
     /// After the application quits ([`World::quit()`] is called) this will return.
     /// If a custom run action is set, it will be invoked by this operation.
     /// The default run action calls the frame action in a loop until it returns a non-zero value.
     ///
+    /// The callbacks and the frame action of the app are only called by the run action of
+    /// the Rust API. They are not called when a C module replaced the run action with
+    /// `ecs_app_set_run_action`.
+    ///
+    /// When the world is deterministic, the app doesn't set a target fps and progresses with the
+    /// fixed delta time, see [`World::set_deterministic()`]. While the pipeline is sequential,
+    /// the threads of the app are started when it stops being sequential, see
+    /// [`World::set_sequential()`].
+    ///
+    /// # Note
+    ///
+    /// Unlike the C++ API, this doesn't release the world when the application quits. The app
+    /// borrows the world and doesn't own a reference to it, so the world is freed when the
+    /// [`World`] that created it is dropped.
+    ///
     /// # Returns
     ///
     /// The exit code of the application.
@@ -180,17 +493,16 @@ impl<'a> App<'a> {
     #[doc(alias = "app_builder::run")]
     pub fn run(&mut self) -> i32 {
         let world_ptr = self.world.ptr_mut();
-        let result = unsafe { sys::ecs_app_run(world_ptr, &mut self.desc) };
-        unsafe {
-            if sys::ecs_should_quit(world_ptr) {
-                // Only free world if quit flag is set. This ensures that we won't
-                // try to cleanup the world if the app is used in an environment
-                // that takes over the main loop, like with emscripten.
-                if sys::flecs_poly_release_(world_ptr as *mut c_void) == 0 {
-                    sys::ecs_fini(world_ptr);
-                }
-            }
+        crate::addons::pipeline::apply_to_app(self.world, &mut self.desc);
+        crate::addons::pipeline::resolve_if_dirty(self.world);
+        let hooks = std::mem::take(&mut self.hooks);
+        if !hooks.is_empty() {
+            unsafe { sys::ecs_app_set_run_action(Some(run_action)) };
+            self.world.world_ctx_mut().app_hooks = Some(hooks);
         }
+        let result = unsafe { sys::ecs_app_run(world_ptr, &mut self.desc) };
+        // drop the callbacks when another run action ignored them
+        self.world.world_ctx_mut().app_hooks.take();
         result
     }
 }
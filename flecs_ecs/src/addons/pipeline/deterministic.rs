@@ -0,0 +1,227 @@
+//! Deterministic stepping, which progresses the world with a fixed delta time and a virtual
+//! clock instead of the time measured between frames.
+
+use crate::core::*;
+use crate::sys;
+
+/// The deterministic settings of a world.
+#[derive(Default)]
+pub(crate) struct Deterministic {
+    /// The delta time of every frame, or 0 when the world isn't deterministic.
+    delta_time: FTime,
+    /// The target fps that is set when the world stops being deterministic.
+    target_fps: FTime,
+    /// Whether the world measured the time between frames before it became deterministic.
+    measure_frame_time: bool,
+    /// Whether the pipeline runs on the main thread.
+    sequential: bool,
+    /// The worker threads that are started when the pipeline stops being sequential.
+    threads: i32,
+}
+
+/// Returns the delta time of the next frame, which is the fixed delta time when the world is
+/// deterministic and `delta_time` is 0.
+pub(crate) fn frame_delta_time(world: WorldRef, delta_time: FTime) -> FTime {
+    if delta_time == 0.0 {
+        world.world_ctx().deterministic.delta_time
+    } else {
+        delta_time
+    }
+}
+
+/// Sets the target fps of the world, or stores it until the world stops being deterministic.
+pub(crate) fn set_frame_rate(world: WorldRef, target_fps: FTime) {
+    let deterministic = &mut world.world_ctx_mut().deterministic;
+    if deterministic.delta_time != 0.0 {
+        deterministic.target_fps = target_fps;
+    } else {
+        unsafe { sys::ecs_set_target_fps(world.world_ptr_mut(), target_fps) };
+    }
+}
+
+/// Sets the worker threads of the world, or stores them until the pipeline stops being
+/// sequential.
+pub(crate) fn set_worker_threads(world: WorldRef, threads: i32) {
+    let deterministic = &mut world.world_ctx_mut().deterministic;
+    if deterministic.sequential {
+        deterministic.threads = threads;
+    } else {
+        unsafe { sys::ecs_set_threads(world.world_ptr_mut(), threads) };
+    }
+}
+
+/// Applies the deterministic settings of the world to an app, which doesn't set a target fps
+/// and progresses with the fixed delta time.
+#[cfg(feature = "flecs_app")]
+pub(crate) fn apply_to_app(world: WorldRef, desc: &mut sys::ecs_app_desc_t) {
+    let deterministic = &mut world.world_ctx_mut().deterministic;
+    if deterministic.delta_time != 0.0 {
+        if desc.target_fps != 0.0 {
+            deterministic.target_fps = desc.target_fps;
+            desc.target_fps = 0.0;
+        }
+        if desc.delta_time == 0.0 {
+            desc.delta_time = deterministic.delta_time;
+        }
+    }
+    if deterministic.sequential && desc.threads != 0 {
+        deterministic.threads = desc.threads;
+        desc.threads = 0;
+    }
+}
+
+impl World {
+    /// Progress the world with a fixed delta time, instead of the time measured between frames.
+    ///
+    /// Every frame run by [`World::progress()`], [`World::step_frames()`] or an
+    /// [`App`](crate::addons::app::App) advances the virtual clock of the world, returned by
+    /// [`World::time()`], by `delta_time` times the time scale. Systems, timers, rate filters
+    /// and [`TableIter::delta_time()`] all see the fixed delta time, so that frames give the
+    /// same results across runs and machines.
+    ///
+    /// The world doesn't sleep to reach a target fps while it is deterministic. The target fps
+    /// set with [`World::set_target_fps()`] is stored, and set when the world stops being
+    /// deterministic. The time between frames is measured again when the world stops being
+    /// deterministic, if it was measured before.
+    ///
+    /// # Arguments
+    ///
+    /// * `delta_time` - The delta time of every frame, or 0 to measure the time between frames
+    ///   again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    /// }
+    ///
+    /// let world = World::new();
+    /// world.set_deterministic(0.25);
+    ///
+    /// world
+    ///     .system::<&mut Position>()
+    ///     .each_iter(|it, _, p| p.x += it.delta_time());
+    ///
+    /// let e = world.entity().set(Position { x: 0.0 });
+    /// world.step_frames(4);
+    ///
+    /// e.get::<&Position>(|p| assert_eq!(p.x, 1.0));
+    /// assert_eq!(world.time(), 1.0);
+    /// assert_eq!(world.tick(), 4);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`World::get_deterministic()`]
+    /// * [`World::set_sequential()`]
+    /// * [`World::step_frames()`]
+    pub fn set_deterministic(&self, delta_time: FTime) {
+        let world_ptr = self.ptr_mut();
+        let deterministic = &mut self.world_ctx_mut().deterministic;
+        let was_deterministic = deterministic.delta_time != 0.0;
+        deterministic.delta_time = delta_time;
+
+        if delta_time != 0.0 && !was_deterministic {
+            deterministic.target_fps = self.info().target_fps;
+            deterministic.measure_frame_time =
+                unsafe { sys::ecs_rust_is_measuring_frame_time(world_ptr) };
+            if deterministic.target_fps != 0.0 {
+                unsafe { sys::ecs_set_target_fps(world_ptr, 0.0) };
+            }
+            // deterministic frames don't measure the time between frames
+            unsafe { sys::ecs_measure_frame_time(world_ptr, false) };
+        } else if delta_time == 0.0 && was_deterministic {
+            if deterministic.target_fps != 0.0 {
+                unsafe { sys::ecs_set_target_fps(world_ptr, deterministic.target_fps) };
+            }
+            unsafe { sys::ecs_measure_frame_time(world_ptr, deterministic.measure_frame_time) };
+        }
+    }
+
+    /// Returns the fixed delta time of the world, or `None` when the world measures the time
+    /// between frames.
+    ///
+    /// # See also
+    ///
+    /// * [`World::set_deterministic()`]
+    pub fn get_deterministic(&self) -> Option<FTime> {
+        let delta_time = self.world_ctx().deterministic.delta_time;
+        (delta_time != 0.0).then_some(delta_time)
+    }
+
+    /// Run the pipeline on the main thread, in a reproducible order.
+    ///
+    /// While the pipeline is sequential, multi-threaded systems run on the main thread, and
+    /// process their entities in the order of a single threaded run, no matter how many worker
+    /// threads are configured. The worker threads set with [`World::set_threads()`] or an
+    /// [`App`](crate::addons::app::App) are stopped, and started again when the pipeline stops
+    /// being sequential.
+    ///
+    /// # Arguments
+    ///
+    /// * `sequential` - Whether the pipeline runs on the main thread.
+    ///
+    /// # See also
+    ///
+    /// * [`World::set_deterministic()`]
+    /// * [`World::set_threads()`]
+    pub fn set_sequential(&self, sequential: bool) {
+        let world_ptr = self.ptr_mut();
+        let deterministic = &mut self.world_ctx_mut().deterministic;
+        if deterministic.sequential == sequential {
+            return;
+        }
+
+        if sequential {
+            let stage_count = unsafe { sys::ecs_get_stage_count(world_ptr) };
+            deterministic.threads = if stage_count > 1 { stage_count } else { 0 };
+            deterministic.sequential = true;
+            if stage_count > 1 {
+                unsafe { sys::ecs_set_threads(world_ptr, 0) };
+            }
+        } else {
+            deterministic.sequential = false;
+            if deterministic.threads != 0 {
+                unsafe { sys::ecs_set_threads(world_ptr, deterministic.threads) };
+            }
+        }
+    }
+
+    /// Returns whether the pipeline runs on the main thread.
+    ///
+    /// # See also
+    ///
+    /// * [`World::set_sequential()`]
+    pub fn is_sequential(&self) -> bool {
+        self.world_ctx().deterministic.sequential
+    }
+
+    /// Progress the world `frames` frames with the fixed delta time.
+    ///
+    /// # Arguments
+    ///
+    /// * `frames` - The number of frames to run.
+    ///
+    /// # Returns
+    ///
+    /// False if [`World::quit()`] has been called, in which case the remaining frames don't run.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the world isn't deterministic.
+    ///
+    /// # See also
+    ///
+    /// * [`World::set_deterministic()`]
+    /// * [`World::progress()`]
+    pub fn step_frames(&self, frames: u32) -> bool {
+        if self.get_deterministic().is_none() {
+            panic!("step_frames requires a deterministic world, see World::set_deterministic");
+        }
+        (0..frames).all(|_| self.progress())
+    }
+}
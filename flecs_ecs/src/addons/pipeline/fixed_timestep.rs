@@ -0,0 +1,203 @@
+//! Systems that run with a fixed timestep, any number of times per frame.
+
+use super::ordering::ordered_systems;
+use crate::core::*;
+use crate::sys;
+
+/// The phase of systems that run with a fixed timestep.
+///
+/// Systems created with `.kind::<FixedUpdate>()` are not run by the pipeline directly. Once
+/// [`World::set_fixed_timestep()`] is called, they run in the [`flecs::pipeline::PreUpdate`]
+/// phase as many times per frame as fixed steps fit in the time that passed, with the timestep
+/// as their delta time.
+///
+/// Within a frame the steps share the command queue of the frame, so entities and components
+/// added by one step are only visible to the next frame.
+///
+/// The phase is the tick source of its systems, like a [timer](crate::addons::timer), which
+/// ticks once for every fixed step. Systems that were given another tick source keep it.
+///
+/// # See also
+///
+/// * [`World::set_fixed_timestep()`]
+/// * [`FixedTime`]
+#[derive(flecs_ecs_derive::Component)]
+pub struct FixedUpdate;
+
+/// The state of the fixed timestep, available as singleton once
+/// [`World::set_fixed_timestep()`] was called.
+///
+/// Render systems can use `alpha` to interpolate between the two most recent fixed steps.
+#[derive(flecs_ecs_derive::Component, Debug, Clone, Copy, PartialEq)]
+pub struct FixedTime {
+    /// The duration of a fixed step.
+    pub timestep: FTime,
+    /// The maximum number of steps run in a frame. When a frame takes longer than this many
+    /// steps, the remaining time is dropped so slow frames don't cause ever more steps.
+    pub max_steps_per_frame: u32,
+    /// The time that passed since the last fixed step, always less than `timestep`.
+    pub accumulator: FTime,
+    /// The progress towards the next fixed step, from 0 to 1: `accumulator / timestep`.
+    pub alpha: FTime,
+    /// The number of fixed steps that ran in the current frame.
+    pub steps_this_frame: u32,
+}
+
+impl World {
+    /// Sets the timestep with which the systems of the [`FixedUpdate`] phase run.
+    ///
+    /// Every frame the delta time, including the time scale, is added to an accumulator. The
+    /// fixed systems then run once for every full timestep in the accumulator, up to
+    /// [`FixedTime::max_steps_per_frame`] times. The remaining fraction is exposed as
+    /// [`FixedTime::alpha`].
+    ///
+    /// The first call creates the [`FixedTime`] singleton, with at most 8 steps per frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestep` - The duration of a fixed step.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::addons::pipeline::{FixedTime, FixedUpdate};
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    /// }
+    ///
+    /// let world = World::new();
+    /// world.set_fixed_timestep(0.25);
+    ///
+    /// world
+    ///     .system::<&mut Position>()
+    ///     .kind::<FixedUpdate>()
+    ///     .each_iter(|it, _, p| p.x += it.delta_time());
+    ///
+    /// let e = world.entity().set(Position { x: 0.0 });
+    /// world.progress_time(0.625);
+    ///
+    /// e.get::<&Position>(|p| assert_eq!(p.x, 0.5));
+    /// world.get::<&FixedTime>(|time| {
+    ///     assert_eq!(time.steps_this_frame, 2);
+    ///     assert_eq!(time.alpha, 0.5);
+    /// });
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`World::set_max_fixed_steps()`]
+    pub fn set_fixed_timestep(&self, timestep: FTime) {
+        ecs_assert!(
+            timestep > 0.0,
+            FlecsErrorCode::InvalidParameter,
+            "fixed timestep must be positive"
+        );
+
+        if self.has::<FixedTime>() {
+            self.get::<&mut FixedTime>(|time| time.timestep = timestep);
+            return;
+        }
+
+        self.set(FixedTime {
+            timestep,
+            max_steps_per_frame: 8,
+            accumulator: 0.0,
+            alpha: 0.0,
+            steps_this_frame: 0,
+        });
+
+        EntityView::new_from(self, FixedUpdate::id(self)).set(flecs::system::TickSource {
+            tick: false,
+            time_elapsed: 0.0,
+        });
+
+        // the driver has no terms, so the fixed systems can read and write `FixedTime`
+        self.system::<()>()
+            .kind::<flecs::pipeline::PreUpdate>()
+            .run(|it| run_fixed_steps(it.world(), it.delta_time()));
+    }
+
+    /// Sets the maximum number of fixed steps that run in a frame.
+    ///
+    /// Requires [`World::set_fixed_timestep()`] to be called first.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_steps` - The maximum number of steps per frame.
+    pub fn set_max_fixed_steps(&self, max_steps: u32) {
+        self.get::<&mut FixedTime>(|time| time.max_steps_per_frame = max_steps);
+    }
+}
+
+/// Makes the [`FixedUpdate`] phase the tick source of a fixed system without a tick source.
+pub(crate) fn fixed_tick_source(world: WorldRef, desc: &mut sys::ecs_system_desc_t) {
+    if desc.tick_source != 0 {
+        return;
+    }
+    let fixed_update = FixedUpdate::id(world);
+    if unsafe { sys::ecs_has_id(world.world_ptr(), desc.entity, ecs_dependson(fixed_update)) } {
+        desc.tick_source = fixed_update;
+    }
+}
+
+/// Sets whether the tick source of the [`FixedUpdate`] phase ticks.
+fn tick_fixed_update(world: WorldRef, tick: bool, time_elapsed: FTime) {
+    EntityView::new_from(world, FixedUpdate::id(world)).get::<&mut flecs::system::TickSource>(
+        |source| {
+            source.tick = tick;
+            source.time_elapsed = time_elapsed;
+        },
+    );
+}
+
+/// Runs the systems of the [`FixedUpdate`] phase for every timestep that fits in the
+/// accumulator.
+///
+/// The state is copied out of the [`FixedTime`] singleton and written back before every step,
+/// so the fixed systems see the step that runs.
+fn run_fixed_steps(world: WorldRef, delta_time: FTime) {
+    let mut time = world.cloned::<&FixedTime>();
+    time.accumulator += delta_time;
+    time.steps_this_frame = 0;
+
+    let mut systems = Vec::new();
+    while time.accumulator >= time.timestep && time.steps_this_frame < time.max_steps_per_frame {
+        if time.steps_this_frame == 0 {
+            systems = fixed_systems(world);
+            tick_fixed_update(world, true, time.timestep);
+        }
+        time.accumulator -= time.timestep;
+        time.steps_this_frame += 1;
+        time.alpha = time.accumulator / time.timestep;
+        world.get::<&mut FixedTime>(|state| *state = time);
+
+        for &system in &systems {
+            unsafe {
+                sys::ecs_run(
+                    world.world_ptr_mut(),
+                    system,
+                    time.timestep,
+                    std::ptr::null_mut(),
+                );
+            }
+        }
+    }
+    if time.steps_this_frame != 0 {
+        tick_fixed_update(world, false, 0.0);
+    }
+
+    // drop the steps that didn't fit in the frame
+    if time.accumulator >= time.timestep {
+        time.accumulator %= time.timestep;
+    }
+    time.alpha = time.accumulator / time.timestep;
+    world.get::<&mut FixedTime>(|state| *state = time);
+}
+
+/// Returns the enabled systems of the [`FixedUpdate`] phase, in the order of the pipeline.
+fn fixed_systems(world: WorldRef) -> Vec<u64> {
+    ordered_systems(world, ecs_dependson(FixedUpdate::id(world)))
+}
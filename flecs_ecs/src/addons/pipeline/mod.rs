@@ -1,7 +1,24 @@
 //! Pipelines order and schedule systems for execution.
 
+mod deterministic;
+mod fixed_timestep;
+mod ordering;
 mod pipeline_builder;
+mod schedule;
+mod state;
+#[cfg(feature = "flecs_app")]
+pub(crate) use deterministic::apply_to_app;
+pub(crate) use deterministic::{
+    frame_delta_time, set_frame_rate, set_worker_threads, Deterministic,
+};
+pub(crate) use fixed_timestep::fixed_tick_source;
+pub use fixed_timestep::{FixedTime, FixedUpdate};
+pub use ordering::SystemOrderError;
+pub(crate) use ordering::{resolve_if_dirty, SystemOrdering};
 pub use pipeline_builder::*;
+pub use schedule::{PipelineSchedule, ScheduleOp, ScheduledSystem};
+pub(crate) use state::States;
+pub use state::{InState, OnEnter, OnExit};
 
 use std::ops::{Deref, DerefMut};
 
@@ -0,0 +1,563 @@
+//! Ordering constraints between systems: `before`, `after` and system sets.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::ffi::{c_void, CString};
+
+use crate::addons::system::SystemBuilder;
+use crate::core::*;
+use crate::sys;
+
+/// The position of a system in the order resolved from the ordering constraints.
+///
+/// The ordered pipeline sorts the systems of a phase by this component instead of by their
+/// entity id.
+#[derive(flecs_ecs_derive::Component, Clone, Copy)]
+pub(crate) struct SystemOrder(u32);
+
+/// The ordering constraints of a world, declared with [`SystemBuilder::before()`],
+/// [`SystemBuilder::after()`] and [`SystemBuilder::in_set()`].
+#[derive(Default)]
+pub(crate) struct SystemOrdering {
+    /// The ordered pipeline, or 0 before the first constraint is declared.
+    pipeline: u64,
+    /// The systems of the world, once the ordered pipeline is installed.
+    systems: Vec<u64>,
+    /// Pairs of systems or sets where the first runs before the second.
+    constraints: Vec<(u64, u64)>,
+    /// The sets of every system that was added to a set.
+    sets: HashMap<u64, Vec<u64>>,
+    /// The rank given to the next system that is created.
+    next_rank: u32,
+    /// Whether constraints were declared since the order was last resolved.
+    dirty: bool,
+}
+
+/// Error returned when the ordering constraints of the systems can't be satisfied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SystemOrderError {
+    /// The constraints form a cycle. Each system in `systems` must run before the next one,
+    /// and the last one before the first.
+    Cycle {
+        /// The systems of the cycle.
+        systems: Vec<Entity>,
+    },
+    /// A system must run before another system that is in an earlier phase.
+    PhaseConflict {
+        /// The system that must run first.
+        before: Entity,
+        /// The system that must run second, but is in an earlier phase.
+        after: Entity,
+    },
+}
+
+impl std::fmt::Display for SystemOrderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SystemOrderError::Cycle { systems } => {
+                write!(f, "system ordering constraints form a cycle: ")?;
+                for system in systems {
+                    write!(f, "{} -> ", system)?;
+                }
+                match systems.first() {
+                    Some(first) => write!(f, "{}", first),
+                    None => Ok(()),
+                }
+            }
+            SystemOrderError::PhaseConflict { before, after } => write!(
+                f,
+                "system {} must run before system {}, which is in an earlier phase",
+                before, after
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SystemOrderError {}
+
+impl<'a, T> SystemBuilder<'a, T>
+where
+    T: QueryTuple,
+{
+    /// Runs the system before `system`, or before all systems of a set.
+    ///
+    /// Both systems must be in the same phase, or the phase of this system must come first.
+    /// Systems without ordering constraints keep running in the order they were declared.
+    ///
+    /// The first ordering constraint replaces the builtin pipeline of the world with a pipeline
+    /// that orders systems by their constraints. Worlds that run a custom pipeline keep the
+    /// order of that pipeline.
+    ///
+    /// # Arguments
+    ///
+    /// * `system` - The system or set to run before.
+    ///
+    /// The order is resolved before the next frame. When the constraints of the systems form a
+    /// cycle, the error is logged and the systems keep their previous order, see
+    /// [`World::resolve_system_order()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    /// }
+    ///
+    /// let world = World::new();
+    /// world.entity().set(Position { x: 0.0 });
+    ///
+    /// let render = world
+    ///     .system_named::<&Position>("Render")
+    ///     .each(|p| assert_eq!(p.x, 1.0));
+    ///
+    /// world
+    ///     .system_named::<&mut Position>("Move")
+    ///     .before(render)
+    ///     .each(|p| p.x += 1.0);
+    ///
+    /// world.progress();
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`SystemBuilder::after()`]
+    /// * [`SystemBuilder::before_set()`]
+    pub fn before(&mut self, system: impl Into<Entity>) -> &mut Self {
+        let entity = self.desc.entity;
+        add_constraint(self.world(), entity, *system.into());
+        self
+    }
+
+    /// Runs the system after `system`, or after all systems of a set.
+    ///
+    /// # Arguments
+    ///
+    /// * `system` - The system or set to run after.
+    ///
+    /// # See also
+    ///
+    /// * [`SystemBuilder::before()`]
+    /// * [`SystemBuilder::after_set()`]
+    pub fn after(&mut self, system: impl Into<Entity>) -> &mut Self {
+        let entity = self.desc.entity;
+        add_constraint(self.world(), *system.into(), entity);
+        self
+    }
+
+    /// Runs the system before all systems in the set `Set`.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `Set` - The set to run before.
+    ///
+    /// # See also
+    ///
+    /// * [`SystemBuilder::in_set()`]
+    pub fn before_set<Set: ComponentId>(&mut self) -> &mut Self {
+        let set = Set::id(self.world());
+        self.before(set)
+    }
+
+    /// Runs the system after all systems in the set `Set`.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `Set` - The set to run after.
+    ///
+    /// # See also
+    ///
+    /// * [`SystemBuilder::in_set()`]
+    pub fn after_set<Set: ComponentId>(&mut self) -> &mut Self {
+        let set = Set::id(self.world());
+        self.after(set)
+    }
+
+    /// Adds the system to the set `Set`.
+    ///
+    /// Sets group systems that are declared in different places, so other systems can be
+    /// ordered relative to all of them with [`SystemBuilder::before_set()`] and
+    /// [`SystemBuilder::after_set()`], including systems that are added to the set later. A
+    /// system can be in multiple sets.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `Set` - The set, which is any component type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct PhysicsSet;
+    ///
+    /// let world = World::new();
+    ///
+    /// let render = world
+    ///     .system_named::<()>("Render")
+    ///     .after_set::<PhysicsSet>()
+    ///     .run(|_| {});
+    ///
+    /// let physics = world
+    ///     .system_named::<()>("Physics")
+    ///     .in_set::<PhysicsSet>()
+    ///     .run(|_| {});
+    ///
+    /// let order = world.resolve_system_order().unwrap();
+    /// let physics_index = order.iter().position(|&s| s == physics.id());
+    /// let render_index = order.iter().position(|&s| s == render.id());
+    /// assert!(physics_index < render_index);
+    /// ```
+    pub fn in_set<Set: ComponentId>(&mut self) -> &mut Self {
+        let entity = self.desc.entity;
+        let world = self.world();
+        let set = Set::id(world);
+        let ordering = &mut world.world_ctx_mut().system_ordering;
+        ordering.sets.entry(entity).or_default().push(set);
+        ordering.dirty = true;
+        install_ordered_pipeline(world);
+        self
+    }
+}
+
+impl World {
+    /// Resolves the ordering constraints of the systems into the order of the pipeline.
+    ///
+    /// The order is resolved automatically before the next frame after constraints were
+    /// declared, which logs an error and keeps the previous order when the constraints can't be
+    /// satisfied. This method can be used to check the constraints explicitly.
+    ///
+    /// # Returns
+    ///
+    /// The systems in the order in which they run, or an error when the constraints form a
+    /// cycle or order a system before a system of an earlier phase.
+    ///
+    /// # See also
+    ///
+    /// * [`SystemBuilder::before()`]
+    /// * [`SystemBuilder::after()`]
+    /// * [`SystemBuilder::in_set()`]
+    pub fn resolve_system_order(&self) -> Result<Vec<Entity>, SystemOrderError> {
+        resolve(self.into())
+    }
+}
+
+fn add_constraint(world: WorldRef, before: u64, after: u64) {
+    let ordering = &mut world.world_ctx_mut().system_ordering;
+    ordering.constraints.push((before, after));
+    ordering.dirty = true;
+    install_ordered_pipeline(world);
+}
+
+/// Resolves the order before a frame, if constraints were declared since the last frame.
+///
+/// Constraints that can't be satisfied are logged once, and the systems keep their previous
+/// order.
+pub(crate) fn resolve_if_dirty(world: WorldRef) {
+    let ordering = &mut world.world_ctx_mut().system_ordering;
+    if !ordering.dirty {
+        return;
+    }
+    if let Err(err) = resolve(world) {
+        world.world_ctx_mut().system_ordering.dirty = false;
+        let message = CString::new(err.to_string()).unwrap_or_default();
+        let file = CString::new(file!()).unwrap_or_default();
+        unsafe {
+            sys::ecs_log_(
+                -3,
+                file.as_ptr(),
+                line!() as i32,
+                c"%s".as_ptr(),
+                message.as_ptr(),
+            );
+        }
+    }
+}
+
+/// Returns the rank of `system` in the resolved order, or 0 when no constraints were declared.
+pub(crate) fn system_rank(world: WorldRef, system: u64) -> u32 {
+    if world.world_ctx().system_ordering.pipeline == 0 {
+        return 0;
+    }
+    let order_id = SystemOrder::id(world);
+    let order =
+        unsafe { sys::ecs_get_id(world.world_ptr(), system, order_id) } as *const SystemOrder;
+    if order.is_null() {
+        0
+    } else {
+        unsafe { (*order).0 }
+    }
+}
+
+/// Returns the enabled systems with `id`, in the order of the pipeline.
+pub(crate) fn ordered_systems(world: WorldRef, id: u64) -> Vec<u64> {
+    let world_ptr = world.real_world().world_ptr();
+
+    let mut systems = Vec::new();
+    unsafe {
+        let mut it = sys::ecs_each_id(world_ptr, id);
+        while sys::ecs_each_next(&mut it) {
+            for i in 0..it.count as usize {
+                let system = *it.entities.add(i);
+                if sys::ecs_has_id(world_ptr, system, ECS_SYSTEM)
+                    && !sys::ecs_has_id(world_ptr, system, ECS_DISABLED)
+                {
+                    systems.push((system_rank(world, system), system));
+                }
+            }
+        }
+    }
+    systems.sort_unstable();
+    systems.into_iter().map(|(_, system)| system).collect()
+}
+
+/// Compares systems by their [`SystemOrder`], and by entity id for equal ranks.
+unsafe extern "C" fn compare_system_order(
+    e1: sys::ecs_entity_t,
+    ptr1: *const c_void,
+    e2: sys::ecs_entity_t,
+    ptr2: *const c_void,
+) -> i32 {
+    let r1 = (*(ptr1 as *const SystemOrder)).0;
+    let r2 = (*(ptr2 as *const SystemOrder)).0;
+    (r1, e1).cmp(&(r2, e2)) as i32
+}
+
+/// Replaces the builtin pipeline with a pipeline that orders systems by [`SystemOrder`].
+fn install_ordered_pipeline(world: WorldRef) {
+    if world.world_ctx().system_ordering.pipeline != 0 {
+        return;
+    }
+
+    let world_ptr = world.real_world().world_ptr_mut();
+    let order_id = SystemOrder::id(world);
+
+    // new systems are ranked after all existing systems
+    world
+        .observer::<flecs::OnAdd, ()>()
+        .with::<flecs::system::System>()
+        .each_entity(|e, _| {
+            let world = e.world();
+            let ordering = &mut world.world_ctx_mut().system_ordering;
+            let rank = ordering.next_rank;
+            ordering.next_rank += 1;
+            ordering.systems.push(*e.id());
+            e.set(SystemOrder(rank));
+        });
+
+    // constraints of deleted systems no longer apply
+    world
+        .observer::<flecs::OnRemove, ()>()
+        .with::<flecs::system::System>()
+        .each_entity(|e, _| {
+            let system = *e.id();
+            let world = e.world();
+            let ordering = &mut world.world_ctx_mut().system_ordering;
+            ordering.systems.retain(|&s| s != system);
+            ordering.sets.remove(&system);
+            ordering
+                .constraints
+                .retain(|&(before, after)| before != system && after != system);
+            ordering.dirty = true;
+        });
+
+    let mut desc = sys::ecs_pipeline_desc_t::default();
+    let terms = &mut desc.query.terms;
+    terms[0].id = ECS_SYSTEM;
+    terms[1].id = ECS_PHASE;
+    terms[1].src.id = ECS_CASCADE;
+    terms[1].trav = ECS_DEPENDS_ON;
+    terms[2].id = ecs_dependson(ECS_ON_START);
+    terms[2].trav = ECS_DEPENDS_ON;
+    terms[2].oper = OperKind::Not as i16;
+    terms[3].id = ECS_DISABLED;
+    terms[3].src.id = ECS_UP;
+    terms[3].trav = ECS_DEPENDS_ON;
+    terms[3].oper = OperKind::Not as i16;
+    terms[4].id = ECS_DISABLED;
+    terms[4].src.id = ECS_UP;
+    terms[4].trav = ECS_CHILD_OF;
+    terms[4].oper = OperKind::Not as i16;
+    terms[5].id = order_id;
+    terms[5].src.id = ECS_SELF;
+    desc.query.order_by = order_id;
+    desc.query.order_by_callback = Some(compare_system_order);
+
+    unsafe {
+        let builtin = sys::ecs_get_pipeline(world_ptr);
+        let pipeline = sys::ecs_pipeline_init(world_ptr, &desc);
+        world.world_ctx_mut().system_ordering.pipeline = pipeline;
+
+        // systems that were created before are ranked in the order of their ids
+        let mut systems = Vec::new();
+        world
+            .query::<()>()
+            .with::<flecs::system::System>()
+            .build()
+            .each_entity(|e, _| systems.push(e.id()));
+        for system in systems {
+            if !sys::ecs_has_id(world_ptr, *system, order_id) {
+                let ordering = &mut world.world_ctx_mut().system_ordering;
+                let rank = ordering.next_rank;
+                ordering.next_rank += 1;
+                ordering.systems.push(*system);
+                EntityView::new_from(world, system).set(SystemOrder(rank));
+            }
+        }
+
+        if builtin == sys::ecs_lookup(world_ptr, c"flecs.pipeline.BuiltinPipeline".as_ptr()) {
+            sys::ecs_set_pipeline(world_ptr, pipeline);
+        }
+    }
+}
+
+/// Returns the number of `DependsOn` relationships between `system` and the first phase.
+fn phase_depth(world: WorldRef, system: u64) -> u32 {
+    let mut depth = 0;
+    let mut current = system;
+    loop {
+        current = unsafe { sys::ecs_get_target(world.world_ptr(), current, ECS_DEPENDS_ON, 0) };
+        if current == 0 || depth > 64 {
+            return depth;
+        }
+        depth += 1;
+    }
+}
+
+fn resolve(world: WorldRef) -> Result<Vec<Entity>, SystemOrderError> {
+    let world = world.real_world();
+    let ordering = &mut world.world_ctx_mut().system_ordering;
+    if ordering.pipeline == 0 {
+        // without constraints the systems aren't tracked yet
+        let mut systems = Vec::new();
+        world
+            .query::<()>()
+            .with::<flecs::system::System>()
+            .build()
+            .each_entity(|e, _| systems.push(*e.id()));
+        ordering.systems = systems;
+    }
+
+    // priority of unconstrained systems: phase first, then declaration order
+    let mut keys: Vec<(u32, u64)> = ordering
+        .systems
+        .iter()
+        .map(|&system| (phase_depth(world, system), system))
+        .collect();
+    keys.sort_unstable();
+    let index: HashMap<u64, usize> = keys
+        .iter()
+        .enumerate()
+        .map(|(i, &(_, system))| (system, i))
+        .collect();
+
+    let mut members: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (system, sets) in &ordering.sets {
+        if let Some(&i) = index.get(system) {
+            for set in sets {
+                members.entry(*set).or_default().push(i);
+            }
+        }
+    }
+    let expand = |node: u64| -> Vec<usize> {
+        match (index.get(&node), members.get(&node)) {
+            (Some(&i), _) => vec![i],
+            (None, Some(systems)) => systems.clone(),
+            (None, None) => Vec::new(),
+        }
+    };
+
+    let mut edges: Vec<HashSet<usize>> = vec![HashSet::new(); keys.len()];
+    let mut in_degree = vec![0usize; keys.len()];
+    for &(before, after) in &ordering.constraints {
+        for &b in &expand(before) {
+            for &a in &expand(after) {
+                if a == b {
+                    continue;
+                }
+                if keys[b].0 > keys[a].0 {
+                    return Err(SystemOrderError::PhaseConflict {
+                        before: Entity::new(keys[b].1),
+                        after: Entity::new(keys[a].1),
+                    });
+                }
+                if keys[b].0 == keys[a].0 && edges[b].insert(a) {
+                    in_degree[a] += 1;
+                }
+            }
+        }
+    }
+
+    let mut ready: BinaryHeap<Reverse<usize>> = (0..keys.len())
+        .filter(|&i| in_degree[i] == 0)
+        .map(Reverse)
+        .collect();
+    let mut order = Vec::with_capacity(keys.len());
+    while let Some(Reverse(i)) = ready.pop() {
+        order.push(i);
+        for &next in &edges[i] {
+            in_degree[next] -= 1;
+            if in_degree[next] == 0 {
+                ready.push(Reverse(next));
+            }
+        }
+    }
+
+    if order.len() < keys.len() {
+        return Err(SystemOrderError::Cycle {
+            systems: find_cycle(&edges, &in_degree)
+                .into_iter()
+                .map(|i| Entity::new(keys[i].1))
+                .collect(),
+        });
+    }
+
+    ordering.dirty = false;
+    let order_id = SystemOrder::id(world);
+    for (rank, &i) in order.iter().enumerate() {
+        let system = EntityView::new_from(world, keys[i].1);
+        let rank = rank as u32;
+        let current = unsafe { sys::ecs_get_id(world.world_ptr(), *system.id(), order_id) }
+            as *const SystemOrder;
+        if current.is_null() || unsafe { (*current).0 } != rank {
+            system.set(SystemOrder(rank));
+        }
+    }
+    let ordering = &mut world.world_ctx_mut().system_ordering;
+    ordering.next_rank = ordering.next_rank.max(order.len() as u32);
+
+    Ok(order.into_iter().map(|i| Entity::new(keys[i].1)).collect())
+}
+
+/// Finds a cycle among the systems that were not ordered, which all have a predecessor.
+fn find_cycle(edges: &[HashSet<usize>], in_degree: &[usize]) -> Vec<usize> {
+    let Some(start) = (0..in_degree.len()).find(|&i| in_degree[i] > 0) else {
+        return Vec::new();
+    };
+
+    // walk backwards through unordered predecessors until a system repeats
+    let mut path = vec![start];
+    let mut visited = HashMap::from([(start, 0)]);
+    let mut current = start;
+    loop {
+        let Some(prev) =
+            (0..edges.len()).find(|&p| in_degree[p] > 0 && edges[p].contains(&current))
+        else {
+            return path;
+        };
+        if let Some(&pos) = visited.get(&prev) {
+            // the systems were visited from successor to predecessor
+            let mut cycle = path.split_off(pos);
+            cycle.reverse();
+            let first = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap_or(0);
+            cycle.rotate_left(first);
+            return cycle;
+        }
+        visited.insert(prev, path.len());
+        path.push(prev);
+        current = prev;
+    }
+}
@@ -144,13 +144,22 @@ impl<'a, T: QueryTuple> internals::QueryConfig<'a> for PipelineBuilder<'a, T> {
 
     #[inline(always)]
     fn count_generic_terms(&self) -> i32 {
-        T::COUNT
+        T::TERM_COUNT
     }
 }
 
 impl<'a, T: QueryTuple> QueryBuilderImpl<'a> for PipelineBuilder<'a, T> {}
 impl<'a, T: QueryTuple> TermBuilderImpl<'a> for PipelineBuilder<'a, T> {}
 
+impl<T> Drop for PipelineBuilder<'_, T>
+where
+    T: QueryTuple,
+{
+    fn drop(&mut self) {
+        free_unbuilt_group_callbacks(&mut self.desc.query);
+    }
+}
+
 impl<'a, T> Builder<'a> for PipelineBuilder<'a, T>
 where
     T: QueryTuple,
@@ -158,7 +167,11 @@ where
     type BuiltType = Pipeline<'a, T>;
 
     fn build(&mut self) -> Self::BuiltType {
-        let pipeline = Pipeline::<T>::new(self.world(), self.desc);
+        self.assert_term_count();
+        let world = self.world();
+        let desc = self.desc;
+        let pipeline =
+            init_with_group_callbacks(&mut self.desc.query, || Pipeline::<T>::new(world, desc));
         for string_parts in self.term_builder.str_ptrs_to_free.iter() {
             unsafe {
                 String::from_raw_parts(
@@ -0,0 +1,331 @@
+//! The schedule of a pipeline, which shows in which order systems run and where the pipeline
+//! merges the commands they enqueued.
+
+use std::fmt::Write;
+
+use super::Pipeline;
+use crate::core::*;
+use crate::sys;
+
+/// A system in a [`PipelineSchedule`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledSystem {
+    /// The system entity.
+    pub system: Entity,
+    /// The name of the system, or `#` followed by its id for anonymous systems.
+    pub name: String,
+    /// The phase of the system, or 0 when it has no phase.
+    pub phase: Entity,
+    /// The name of the phase, or `#` followed by its id for anonymous phases.
+    pub phase_name: String,
+}
+
+/// A segment of a [`PipelineSchedule`], whose systems run without merging in between.
+///
+/// The commands enqueued by the systems of an op are merged at the sync point that follows it,
+/// before the systems of the next op run. The commands of the last op are merged at the end of
+/// the frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleOp {
+    /// The systems of the op, in the order in which they run.
+    pub systems: Vec<ScheduledSystem>,
+    /// Whether the systems run on the worker threads.
+    pub multi_threaded: bool,
+    /// Whether the systems run immediate, without staging their commands.
+    pub immediate: bool,
+}
+
+/// The schedule of a pipeline, returned by [`Pipeline::schedule()`].
+///
+/// The pipeline splits its systems in ops. A sync point is inserted between two ops when a
+/// system reads components that an earlier system may have written through commands, or when
+/// the threading or staging of systems changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipelineSchedule {
+    /// The pipeline entity.
+    pub pipeline: Entity,
+    /// The ops of the pipeline, in the order in which they run.
+    pub ops: Vec<ScheduleOp>,
+}
+
+impl PipelineSchedule {
+    /// Returns the systems of the pipeline, in the order in which they run.
+    pub fn systems(&self) -> impl Iterator<Item = &ScheduledSystem> {
+        self.ops.iter().flat_map(|op| op.systems.iter())
+    }
+
+    /// Returns the number of sync points the pipeline inserted between its ops.
+    pub fn sync_points(&self) -> usize {
+        self.ops.len().saturating_sub(1)
+    }
+
+    /// Renders the schedule as a Graphviz DOT graph.
+    ///
+    /// Every op is a cluster of systems, and the sync points between ops are diamond shaped
+    /// nodes.
+    ///
+    /// # See also
+    ///
+    /// * [`PipelineSchedule::to_mermaid()`]
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        dot.push_str("digraph pipeline {\n");
+        dot.push_str("    rankdir=TB;\n");
+        dot.push_str("    node [shape=box];\n");
+
+        for (index, op) in self.ops.iter().enumerate() {
+            let _ = writeln!(dot, "    subgraph cluster_op{} {{", index);
+            let _ = writeln!(dot, "        label=\"{}\";", op_label(index, op));
+            for system in &op.systems {
+                let _ = writeln!(
+                    dot,
+                    "        s{} [label=\"{}\\n{}\"];",
+                    system.system,
+                    escape_dot(&system.name),
+                    escape_dot(&system.phase_name)
+                );
+            }
+            dot.push_str("    }\n");
+            if index + 1 < self.ops.len() {
+                let _ = writeln!(
+                    dot,
+                    "    sync{} [label=\"sync point\", shape=diamond];",
+                    index
+                );
+            }
+        }
+
+        for (from, to) in self.edges() {
+            let _ = writeln!(dot, "    {} -> {};", from, to);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Renders the schedule as a Mermaid flowchart.
+    ///
+    /// Every op is a subgraph of systems, and the sync points between ops are hexagon shaped
+    /// nodes.
+    ///
+    /// # See also
+    ///
+    /// * [`PipelineSchedule::to_dot()`]
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = String::new();
+        mermaid.push_str("flowchart TD\n");
+
+        for (index, op) in self.ops.iter().enumerate() {
+            let _ = writeln!(
+                mermaid,
+                "    subgraph op{} [\"{}\"]",
+                index,
+                op_label(index, op)
+            );
+            for system in &op.systems {
+                let _ = writeln!(
+                    mermaid,
+                    "        s{}[\"{}<br/>{}\"]",
+                    system.system,
+                    escape_mermaid(&system.name),
+                    escape_mermaid(&system.phase_name)
+                );
+            }
+            mermaid.push_str("    end\n");
+            if index + 1 < self.ops.len() {
+                let _ = writeln!(mermaid, "    sync{}{{{{\"sync point\"}}}}", index);
+            }
+        }
+
+        for (from, to) in self.edges() {
+            let _ = writeln!(mermaid, "    {} --> {}", from, to);
+        }
+        mermaid
+    }
+
+    /// Returns the edges between the nodes of the graph, which connect the systems in the order
+    /// in which they run, passing through the sync points.
+    fn edges(&self) -> Vec<(String, String)> {
+        let mut edges = Vec::new();
+        let mut previous: Option<String> = None;
+        for (index, op) in self.ops.iter().enumerate() {
+            for system in &op.systems {
+                let node = format!("s{}", system.system);
+                if let Some(previous) = previous.replace(node.clone()) {
+                    edges.push((previous, node));
+                }
+            }
+            if index + 1 < self.ops.len() {
+                let node = format!("sync{}", index);
+                if let Some(previous) = previous.replace(node.clone()) {
+                    edges.push((previous, node));
+                }
+            }
+        }
+        edges
+    }
+}
+
+fn op_label(index: usize, op: &ScheduleOp) -> String {
+    let mut label = format!("op {}", index);
+    if op.multi_threaded {
+        label.push_str(", multi-threaded");
+    }
+    if op.immediate {
+        label.push_str(", immediate");
+    }
+    label
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_mermaid(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+fn entity_name(world: WorldRef, entity: u64) -> String {
+    match world.entity_from_id(entity).get_name() {
+        Some(name) => name.to_string(),
+        None => format!("#{}", entity),
+    }
+}
+
+/// Returns the schedule of `pipeline`, which is rebuilt first if systems changed.
+pub(crate) fn schedule_of(world: WorldRef, pipeline: u64) -> PipelineSchedule {
+    let world_ptr = world.world_ptr_mut();
+
+    let mut system_count = 0;
+    let op_count = unsafe {
+        sys::ecs_rust_pipeline_schedule(
+            world_ptr,
+            pipeline,
+            std::ptr::null_mut(),
+            0,
+            std::ptr::null_mut(),
+            0,
+            &mut system_count,
+        )
+    };
+    ecs_assert!(
+        op_count >= 0,
+        FlecsErrorCode::InvalidParameter,
+        "entity is not a pipeline"
+    );
+
+    let empty_op = sys::ecs_rust_pipeline_op_t {
+        offset: 0,
+        count: 0,
+        multi_threaded: false,
+        immediate: false,
+    };
+    let mut ops = vec![empty_op; op_count.max(0) as usize];
+    let mut systems: Vec<sys::ecs_entity_t> = vec![0; system_count.max(0) as usize];
+    unsafe {
+        sys::ecs_rust_pipeline_schedule(
+            world_ptr,
+            pipeline,
+            ops.as_mut_ptr(),
+            ops.len() as i32,
+            systems.as_mut_ptr(),
+            systems.len() as i32,
+            &mut system_count,
+        );
+    }
+
+    let ops = ops
+        .iter()
+        .map(|op| ScheduleOp {
+            systems: systems[op.offset as usize..(op.offset + op.count) as usize]
+                .iter()
+                .map(|&system| {
+                    let phase =
+                        unsafe { sys::ecs_get_target(world_ptr, system, ECS_DEPENDS_ON, 0) };
+                    ScheduledSystem {
+                        system: Entity::new(system),
+                        name: entity_name(world, system),
+                        phase: Entity::new(phase),
+                        phase_name: if phase == 0 {
+                            String::new()
+                        } else {
+                            entity_name(world, phase)
+                        },
+                    }
+                })
+                .collect(),
+            multi_threaded: op.multi_threaded,
+            immediate: op.immediate,
+        })
+        .collect();
+
+    PipelineSchedule {
+        pipeline: Entity::new(pipeline),
+        ops,
+    }
+}
+
+impl<'a, T> Pipeline<'a, T>
+where
+    T: QueryTuple,
+{
+    /// Returns the schedule of the pipeline: the order of its systems, their phases, and the
+    /// sync points at which the pipeline merges commands.
+    ///
+    /// The schedule is rebuilt first when systems were added or changed since the last frame.
+    /// Systems that don't match any entities are inactive, and are left out of the schedule
+    /// until they match entities.
+    /// It can be rendered with [`PipelineSchedule::to_dot()`] and
+    /// [`PipelineSchedule::to_mermaid()`].
+    ///
+    /// # Panics
+    ///
+    /// Must not be called while the world is progressing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    /// }
+    ///
+    /// let world = World::new();
+    ///
+    /// let pipeline = world
+    ///     .pipeline()
+    ///     .with::<flecs::system::System>()
+    ///     .build();
+    ///
+    /// world
+    ///     .system_named::<&mut Position>("Move")
+    ///     .each(|p| p.x += 1.0);
+    ///
+    /// world.entity().set(Position { x: 0.0 });
+    ///
+    /// let schedule = pipeline.schedule();
+    /// let names: Vec<_> = schedule.systems().map(|s| s.name.as_str()).collect();
+    /// assert_eq!(names, ["Move"]);
+    /// assert!(schedule.to_dot().starts_with("digraph pipeline"));
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`World::schedule()`]
+    pub fn schedule(&self) -> PipelineSchedule {
+        schedule_of(self.world(), *self.id)
+    }
+}
+
+impl World {
+    /// Returns the schedule of the pipeline that [`World::progress()`] runs.
+    ///
+    /// # See also
+    ///
+    /// * [`Pipeline::schedule()`]
+    /// * [`World::get_pipeline()`]
+    pub fn schedule(&self) -> PipelineSchedule {
+        schedule_of(self.into(), *self.get_pipeline().id())
+    }
+}
@@ -0,0 +1,375 @@
+//! States, which switch between sets of systems with enum singletons.
+
+use super::ordering::ordered_systems;
+use crate::addons::system::SystemBuilder;
+use crate::core::*;
+use crate::sys;
+
+/// Relationship of systems that run once when the world enters a state.
+///
+/// Added by [`SystemBuilder::on_enter()`] as `(OnEnter, constant)`.
+#[derive(flecs_ecs_derive::Component)]
+pub struct OnEnter;
+
+/// Relationship of systems that run once when the world exits a state.
+///
+/// Added by [`SystemBuilder::on_exit()`] as `(OnExit, constant)`.
+#[derive(flecs_ecs_derive::Component)]
+pub struct OnExit;
+
+/// Relationship of systems that only run in a state.
+///
+/// Added by [`SystemBuilder::in_state()`] as `(InState, constant)`.
+#[derive(flecs_ecs_derive::Component)]
+pub struct InState;
+
+/// A state change that is applied at the start of the next frame.
+struct Transition {
+    /// The enum component of the state.
+    state: u64,
+    /// The constant the state changes to.
+    to: u64,
+    /// Whether the transition only enters `to`, which is the case for the initial state.
+    enter_only: bool,
+}
+
+/// The states of a world, added with [`World::add_state()`].
+#[derive(Default)]
+pub(crate) struct States {
+    /// The system that applies transitions, or 0 before the first state is added.
+    driver: u64,
+    /// The transitions applied at the start of the next frame, at most one per state.
+    pending: Vec<Transition>,
+}
+
+/// Returns the current constant of `state`, or 0 when the state wasn't added.
+fn current_state(world: WorldRef, state: u64) -> u64 {
+    unsafe { sys::ecs_get_target(world.world_ptr(), state, state, 0) }
+}
+
+fn queue_transition(world: WorldRef, transition: Transition) {
+    let pending = &mut world.world_ctx_mut().states.pending;
+    // the last transition of a frame wins
+    pending.retain(|pending| pending.state != transition.state);
+    pending.push(transition);
+}
+
+/// Applies the pending transitions, which runs the [`OnExit`] systems of the current state,
+/// changes the state and runs the [`OnEnter`] systems of the new state.
+fn apply_transitions(world: WorldRef, delta_time: FTime) {
+    let pending = std::mem::take(&mut world.world_ctx_mut().states.pending);
+    let world_ptr = world.world_ptr_mut();
+
+    for transition in pending {
+        let from = current_state(world, transition.state);
+        if !transition.enter_only {
+            if from == transition.to {
+                continue;
+            }
+            let on_exit = ecs_pair(OnExit::id(world), from);
+            for system in ordered_systems(world, on_exit) {
+                unsafe { sys::ecs_run(world_ptr, system, delta_time, std::ptr::null_mut()) };
+            }
+        }
+
+        // change the state immediately, so the OnEnter systems and the systems of this frame
+        // see the new state
+        world.defer_suspend();
+        unsafe {
+            sys::ecs_add_id(
+                world_ptr,
+                transition.state,
+                ecs_pair(transition.state, transition.to),
+            );
+        }
+        world.defer_resume();
+
+        let on_enter = ecs_pair(OnEnter::id(world), transition.to);
+        for system in ordered_systems(world, on_enter) {
+            unsafe { sys::ecs_run(world_ptr, system, delta_time, std::ptr::null_mut()) };
+        }
+    }
+}
+
+/// Returns whether `system` runs in the current states.
+///
+/// A system runs when, for every state it has [`InState`] constants of, the current state is
+/// one of those constants.
+fn is_in_state(world: WorldRef, system: u64) -> bool {
+    let world_ptr = world.world_ptr();
+    let in_state = InState::id(world);
+
+    let mut constants = Vec::new();
+    unsafe {
+        let mut index = 0;
+        loop {
+            let constant = sys::ecs_get_target(world_ptr, system, in_state, index);
+            if constant == 0 {
+                break;
+            }
+            constants.push((sys::ecs_get_parent(world_ptr, constant), constant));
+            index += 1;
+        }
+    }
+
+    constants.iter().all(|&(state, _)| {
+        let current = current_state(world, state);
+        constants.contains(&(state, current))
+    })
+}
+
+impl World {
+    /// Adds the state `S` to the world, starting in `initial`.
+    ///
+    /// A state is an enum singleton that changes through transitions. Transitions requested
+    /// with [`World::set_state()`] are applied at the start of the next frame, before the
+    /// systems of the [`flecs::pipeline::OnLoad`] phase run. A transition runs the systems of
+    /// [`SystemBuilder::on_exit()`] of the current state, changes the state, and then runs the
+    /// systems of [`SystemBuilder::on_enter()`] of the new state. From then on the systems of
+    /// [`SystemBuilder::in_state()`] of the new state run.
+    ///
+    /// The state is stored as the pair `(S, constant)` on the singleton entity of `S`, and `S`
+    /// is made an exclusive relationship. Transitions can be observed with
+    /// [`flecs::OnAdd`] and [`flecs::OnRemove`] observers for the pair.
+    ///
+    /// The `on_enter` systems of `initial` run at the start of the next frame.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `S` - The enum of the state.
+    ///
+    /// # Arguments
+    ///
+    /// * `initial` - The initial state.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component, Debug, PartialEq)]
+    /// #[repr(C)]
+    /// enum GameState {
+    ///     Menu,
+    ///     Playing,
+    /// }
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    /// }
+    ///
+    /// let world = World::new();
+    /// world.add_state(GameState::Menu);
+    ///
+    /// world
+    ///     .system::<&mut Position>()
+    ///     .in_state(GameState::Playing)
+    ///     .each(|p| p.x += 1.0);
+    ///
+    /// let e = world.entity().set(Position { x: 0.0 });
+    /// world.progress();
+    /// e.get::<&Position>(|p| assert_eq!(p.x, 0.0));
+    ///
+    /// world.set_state(GameState::Playing);
+    /// world.progress();
+    /// e.get::<&Position>(|p| assert_eq!(p.x, 1.0));
+    /// world.state::<GameState>(|state| assert_eq!(*state, GameState::Playing));
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`World::set_state()`]
+    /// * [`World::state()`]
+    pub fn add_state<S>(&self, initial: S)
+    where
+        S: ComponentId + ComponentType<Enum> + EnumComponentInfo,
+    {
+        let world = WorldRef::from(self);
+        let state = S::id(self);
+        let to = *initial.id_variant(self).id();
+
+        let state_entity = self.entity_from_id(state);
+        if !state_entity.has::<flecs::Exclusive>() {
+            state_entity.add::<flecs::Exclusive>();
+        }
+        self.add_enum(initial);
+
+        queue_transition(
+            world,
+            Transition {
+                state,
+                to,
+                enter_only: true,
+            },
+        );
+
+        if self.world_ctx().states.driver == 0 {
+            let driver = self
+                .system::<()>()
+                .kind_id(ECS_PRE_FRAME)
+                .immediate(true)
+                .run(|it| apply_transitions(it.world(), it.delta_time()));
+            self.world_ctx_mut().states.driver = *driver.id();
+        }
+    }
+
+    /// Changes the state `S` to `next` at the start of the next frame.
+    ///
+    /// When the state is set multiple times in a frame, the last value wins. Setting the state
+    /// to the current state doesn't run any systems.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `S` - The enum of the state.
+    ///
+    /// # Arguments
+    ///
+    /// * `next` - The state to change to.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `S` wasn't added with [`World::add_state()`].
+    ///
+    /// # See also
+    ///
+    /// * [`World::add_state()`]
+    pub fn set_state<S>(&self, next: S)
+    where
+        S: ComponentId + ComponentType<Enum> + EnumComponentInfo,
+    {
+        let world = WorldRef::from(self);
+        let state = S::id(self);
+        if current_state(world, state) == 0 {
+            panic!("state {} was not added", std::any::type_name::<S>());
+        }
+
+        queue_transition(
+            world,
+            Transition {
+                state,
+                to: *next.id_variant(self).id(),
+                enter_only: false,
+            },
+        );
+    }
+
+    /// Calls `callback` with the current value of the state `S`.
+    ///
+    /// The state changes at the start of the frame after [`World::set_state()`] is called.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `S` - The enum of the state.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `S` wasn't added with [`World::add_state()`].
+    pub fn state<S>(&self, callback: impl FnOnce(&S))
+    where
+        S: ComponentId + ComponentType<Enum> + EnumComponentInfo + DataComponent,
+    {
+        self.get::<&S>(callback);
+    }
+}
+
+impl<'a, T> SystemBuilder<'a, T>
+where
+    T: QueryTuple,
+{
+    /// Only run the system while the world is in `state`.
+    ///
+    /// Calling this multiple times with constants of the same state runs the system in any of
+    /// them. With constants of different states, the system runs when all states match. The
+    /// system doesn't run while its state wasn't added with [`World::add_state()`].
+    ///
+    /// The state is checked with a run condition, see [`SystemBuilder::run_if()`], so the
+    /// system can still be enabled and disabled independently of its states.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state in which the system runs.
+    ///
+    /// # See also
+    ///
+    /// * [`World::add_state()`]
+    /// * [`SystemBuilder::on_enter()`]
+    /// * [`SystemBuilder::on_exit()`]
+    pub fn in_state<S>(&mut self, state: S) -> &mut Self
+    where
+        S: ComponentId + ComponentType<Enum> + EnumComponentInfo,
+    {
+        let world = self.world();
+        let constant = *state.id_variant(world).id();
+        let system = self.desc.entity;
+        let in_state = InState::id(world);
+        let world_ptr = world.world_ptr_mut();
+        let first = unsafe { sys::ecs_get_target(world_ptr, system, in_state, 0) } == 0;
+        unsafe { sys::ecs_add_id(world_ptr, system, ecs_pair(in_state, constant)) };
+
+        // one condition checks all constants, which are read from the system
+        if first {
+            self.run_if(move |world| is_in_state(world, system));
+        }
+        self
+    }
+
+    /// Run the system once when the world enters `state`, instead of every frame.
+    ///
+    /// The system is removed from its phase, and runs when the transition to `state` is
+    /// applied at the start of a frame. Systems that enter the same state run in the order of
+    /// the pipeline.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state whose entry runs the system.
+    ///
+    /// # See also
+    ///
+    /// * [`World::add_state()`]
+    /// * [`SystemBuilder::on_exit()`]
+    pub fn on_enter<S>(&mut self, state: S) -> &mut Self
+    where
+        S: ComponentId + ComponentType<Enum> + EnumComponentInfo,
+    {
+        let on_enter = OnEnter::id(self.world());
+        self.on_transition(on_enter, state)
+    }
+
+    /// Run the system once when the world exits `state`, instead of every frame.
+    ///
+    /// The system is removed from its phase, and runs when a transition from `state` is applied
+    /// at the start of a frame, before the state changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state whose exit runs the system.
+    ///
+    /// # See also
+    ///
+    /// * [`World::add_state()`]
+    /// * [`SystemBuilder::on_enter()`]
+    pub fn on_exit<S>(&mut self, state: S) -> &mut Self
+    where
+        S: ComponentId + ComponentType<Enum> + EnumComponentInfo,
+    {
+        let on_exit = OnExit::id(self.world());
+        self.on_transition(on_exit, state)
+    }
+
+    fn on_transition<S>(&mut self, relationship: u64, state: S) -> &mut Self
+    where
+        S: ComponentId + ComponentType<Enum> + EnumComponentInfo,
+    {
+        let constant = *state.id_variant(self.world()).id();
+        self.kind_id(0);
+        unsafe {
+            sys::ecs_add_id(
+                self.world_ptr_mut(),
+                self.desc.entity,
+                ecs_pair(relationship, constant),
+            );
+        }
+        self
+    }
+}
@@ -0,0 +1,131 @@
+//! Derived components, which are computed from other components of the same entity.
+
+use std::rc::Rc;
+
+use crate::core::*;
+
+use super::System;
+
+impl World {
+    /// Registers `Out` as a component that is derived from the components matched by `T`.
+    ///
+    /// Every entity that matches `T` gets an `Out` component computed by `func`, which is kept up
+    /// to date when the inputs change:
+    ///
+    /// * Writes through [`EntityView::set()`] and [`EntityView::modified()`] recompute `Out`
+    ///   immediately, with an [`flecs::OnSet`] observer.
+    /// * Other changes, such as writes through mutable query fields, are picked up by a system
+    ///   that runs in the [`flecs::pipeline::PostUpdate`] phase. The system uses the change
+    ///   detection of its query, so only tables in which an input changed are recomputed.
+    /// * When an input is removed, `Out` is removed as well, with an [`flecs::OnRemove`]
+    ///   observer.
+    ///
+    /// The observers and the system are created as children of the returned entity. Deleting
+    /// the entity stops deriving `Out`, but leaves the `Out` components that were computed.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `Out` - The derived component.
+    /// * `T` - The components the derived component is computed from.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - Computes `Out` from the inputs.
+    ///
+    /// # Returns
+    ///
+    /// The entity that owns the observers and the system of the derived component.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    ///
+    /// #[derive(Component)]
+    /// struct Size {
+    ///     width: f32,
+    ///     height: f32,
+    /// }
+    ///
+    /// #[derive(Component, Debug, PartialEq)]
+    /// struct Bounds {
+    ///     min: (f32, f32),
+    ///     max: (f32, f32),
+    /// }
+    ///
+    /// let world = World::new();
+    ///
+    /// world.derive::<Bounds, (&Position, &Size)>(|(p, s)| Bounds {
+    ///     min: (p.x, p.y),
+    ///     max: (p.x + s.width, p.y + s.height),
+    /// });
+    ///
+    /// let e = world
+    ///     .entity()
+    ///     .set(Position { x: 1.0, y: 2.0 })
+    ///     .set(Size {
+    ///         width: 3.0,
+    ///         height: 4.0,
+    ///     });
+    /// e.get::<&Bounds>(|b| assert_eq!(b.max, (4.0, 6.0)));
+    ///
+    /// e.set(Position { x: 0.0, y: 0.0 });
+    /// e.get::<&Bounds>(|b| assert_eq!(b.max, (3.0, 4.0)));
+    ///
+    /// e.remove::<Size>();
+    /// assert!(!e.has::<Bounds>());
+    /// ```
+    pub fn derive<Out, T>(&self, func: impl Fn(T::TupleType<'_>) -> Out + 'static) -> EntityView<'_>
+    where
+        Out: ComponentId + DataComponent,
+        T: QueryTuple,
+    {
+        let func = Rc::new(func);
+        let derived = self.entity();
+
+        let on_set = func.clone();
+        self.observer::<flecs::OnSet, T>()
+            .each_entity(move |e, inputs| {
+                e.set(on_set(inputs));
+            })
+            .child_of_id(derived);
+
+        self.observer::<flecs::OnRemove, T>()
+            .each_entity(|e, _| {
+                e.remove::<Out>();
+            })
+            .child_of_id(derived);
+
+        let system = self
+            .system::<T>()
+            .kind::<flecs::pipeline::PostUpdate>()
+            .run_each_entity(
+                |mut it| {
+                    while it.next() {
+                        if it.is_changed() {
+                            it.each();
+                        }
+                    }
+                },
+                move |e, inputs| {
+                    e.set(func(inputs));
+                },
+            );
+        enable_change_detection(system);
+        system.child_of_id(derived);
+
+        derived
+    }
+}
+
+/// Enables change detection for the query of `system`, which starts when the changed state of
+/// the query is first requested.
+fn enable_change_detection(system: System) {
+    system.query().is_changed();
+}
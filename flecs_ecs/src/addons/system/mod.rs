@@ -4,8 +4,12 @@
 //! query in combination with a callback function. In addition systems have
 //! support for time management, scheduling via pipeline and can be monitored by the stats addon.
 
+mod derived;
+mod run_condition;
+mod run_hooks;
 mod system_builder;
 mod system_runner_fluent;
+pub use run_hooks::*;
 pub use system_builder::*;
 pub use system_runner_fluent::*;
 
@@ -104,15 +108,46 @@ impl<'a> System<'a> {
     /// * C++ API: `system::ctx`
     #[doc(alias = "system::ctx")]
     pub fn set_context(&mut self, context: *mut c_void) {
-        let desc: sys::ecs_system_desc_t = sys::ecs_system_desc_t {
-            entity: *self.id(),
-            ctx: context,
-            ..Default::default()
-        };
+        self.set_context_with_free(context, None);
+    }
+
+    /// Set a typed context for the system, which replaces and drops the previous typed context
+    /// and is dropped when the system is deleted.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `C` - The type of the context.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The context to set.
+    ///
+    /// # See also
+    ///
+    /// * [`System::context_typed()`]
+    /// * [`SystemAPI::set_context_typed()`]
+    /// * C++ API: `system::ctx`
+    #[doc(alias = "system::ctx")]
+    pub fn set_context_typed<C: 'static>(&mut self, context: C) {
+        self.set_context_with_free(typed_context_into_raw(context), Some(free_typed_context));
+    }
 
-        unsafe {
-            sys::ecs_system_init(self.world.world_ptr_mut(), &desc);
+    /// Replaces the context and its free callback, freeing the previous context.
+    ///
+    /// The fields are set directly, as `ecs_system_init` keeps the previous free callback when
+    /// no free callback is passed, which would free a raw context as a typed context.
+    fn set_context_with_free(&mut self, context: *mut c_void, ctx_free: sys::ecs_ctx_free_t) {
+        let system = unsafe {
+            &mut *(sys::ecs_system_get(self.world.world_ptr(), *self.id())
+                as *mut sys::ecs_system_t)
+        };
+        if let Some(free) = system.ctx_free {
+            if !system.ctx.is_null() && system.ctx != context {
+                unsafe { free(system.ctx) };
+            }
         }
+        system.ctx = context;
+        system.ctx_free = ctx_free;
     }
 
     /// Get the context for the system
@@ -125,6 +160,33 @@ impl<'a> System<'a> {
         unsafe { (*sys::ecs_system_get(self.world.world_ptr(), *self.id())).ctx }
     }
 
+    /// Get the typed context for the system
+    ///
+    /// # Type Parameters
+    ///
+    /// * `C` - The type of the context.
+    ///
+    /// # Panics
+    ///
+    /// Panics when no typed context was set, or when the context is not of type `C`. A context
+    /// set with [`System::set_context()`] can only be accessed with [`System::context()`].
+    ///
+    /// # See also
+    ///
+    /// * [`System::set_context_typed()`]
+    /// * [`SystemAPI::set_context_typed()`]
+    /// * C++ API: `system::ctx`
+    #[doc(alias = "system::ctx")]
+    pub fn context_typed<C: 'static>(&self) -> &C {
+        let system = unsafe { &*sys::ecs_system_get(self.world.world_ptr(), *self.id()) };
+        let context = if is_typed_context(system.ctx_free) {
+            system.ctx
+        } else {
+            std::ptr::null_mut()
+        };
+        unsafe { typed_context(context) }
+    }
+
     /// Get the underlying query for the system
     ///
     /// # See also
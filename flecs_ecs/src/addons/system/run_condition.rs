@@ -0,0 +1,312 @@
+//! Run conditions, which decide every time a system runs whether it iterates its query.
+
+use std::os::raw::c_void;
+use std::sync::{Condvar, Mutex};
+
+use crate::core::*;
+use crate::sys;
+
+use super::SystemBuilder;
+
+/// A condition that is evaluated before a system runs.
+pub(crate) type RunCondition = Box<dyn Fn(WorldRef) -> bool>;
+
+/// The context of a system with run conditions, which wraps the run callback and context the
+/// system was created with.
+struct ConditionalRun {
+    conditions: Vec<RunCondition>,
+    run: sys::ecs_run_action_t,
+    run_ctx: *mut c_void,
+    run_ctx_free: sys::ecs_ctx_free_t,
+    /// The result of the conditions for the workers of a multithreaded run.
+    result: Mutex<SharedResult>,
+    /// Notifies the workers once the main stage evaluated the conditions, and the main stage
+    /// once the workers took the result.
+    evaluated: Condvar,
+}
+
+/// The result of the conditions, which the main stage shares with the workers of a run.
+#[derive(Default)]
+struct SharedResult {
+    /// The number of multithreaded runs of the system, which identifies the result of a run.
+    run: u64,
+    /// Whether the conditions passed in the last run.
+    passed: bool,
+    /// The number of workers that haven't taken the result of the last run yet.
+    pending: usize,
+    /// The last run of which every worker took the result, by worker index.
+    taken: Vec<u64>,
+}
+
+impl ConditionalRun {
+    /// Evaluates the conditions once per run of the system.
+    ///
+    /// A run that is spread over workers evaluates the conditions on the main stage, which
+    /// shares the result with the other workers of the run. Conditions are only called on the
+    /// main thread, so conditions that consume state such as
+    /// [`SystemBuilder::run_if_singleton_changed()`] see every run once.
+    fn passes(&self, world: WorldRef, it: &sys::ecs_iter_t) -> bool {
+        let worker_next =
+            sys::ecs_worker_next as unsafe extern "C" fn(*mut sys::ecs_iter_t) -> bool;
+        let is_worker = it
+            .next
+            .is_some_and(|next| std::ptr::fn_addr_eq(next, worker_next));
+        if !is_worker {
+            return self.conditions.iter().all(|condition| condition(world));
+        }
+
+        let worker = unsafe { it.priv_.iter.worker };
+        let index = worker.index as usize;
+        if index == 0 {
+            // the workers of the previous run take its result before it's replaced
+            drop(
+                self.evaluated
+                    .wait_while(self.result.lock().unwrap(), |result| result.pending != 0)
+                    .unwrap(),
+            );
+            let passed = self.conditions.iter().all(|condition| condition(world));
+            let mut result = self.result.lock().unwrap();
+            result.run += 1;
+            result.passed = passed;
+            result.pending = worker.count as usize - 1;
+            self.evaluated.notify_all();
+            passed
+        } else {
+            let mut result = self
+                .evaluated
+                .wait_while(self.result.lock().unwrap(), |result| {
+                    result.pending == 0 || result.taken.get(index) == Some(&result.run)
+                })
+                .unwrap();
+            if result.taken.len() <= index {
+                result.taken.resize(index + 1, 0);
+            }
+            let run = result.run;
+            result.taken[index] = run;
+            result.pending -= 1;
+            self.evaluated.notify_all();
+            result.passed
+        }
+    }
+}
+
+impl Drop for ConditionalRun {
+    fn drop(&mut self) {
+        if let Some(free) = self.run_ctx_free {
+            unsafe { free(self.run_ctx) };
+        }
+    }
+}
+
+/// Makes the system described by `desc` check `conditions` before it iterates its query.
+pub(crate) fn wrap_run(desc: &mut sys::ecs_system_desc_t, conditions: Vec<RunCondition>) {
+    let conditional = Box::new(ConditionalRun {
+        conditions,
+        run: desc.run,
+        run_ctx: desc.run_ctx,
+        run_ctx_free: desc.run_ctx_free,
+        result: Mutex::new(SharedResult::default()),
+        evaluated: Condvar::new(),
+    });
+
+    desc.run = Some(run_conditional);
+    desc.run_ctx = Box::leak(conditional) as *mut ConditionalRun as *mut c_void;
+    desc.run_ctx_free = Some(free_conditional);
+}
+
+unsafe extern "C" fn run_conditional(it: *mut sys::ecs_iter_t) {
+    unsafe {
+        let conditional = &*((*it).run_ctx as *const ConditionalRun);
+        let world = WorldRef::from_ptr((*it).world);
+
+        if !conditional.passes(world, &*it) {
+            // systems without terms are finished by the caller
+            if (*it).field_count > 0 {
+                sys::ecs_iter_fini(it);
+            }
+            return;
+        }
+
+        (*it).run_ctx = conditional.run_ctx;
+        if let Some(run) = conditional.run {
+            run(it);
+        } else if let Some(callback) = (*it).callback {
+            while sys::ecs_iter_next(it) {
+                callback(it);
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn free_conditional(ctx: *mut c_void) {
+    unsafe { drop(Box::from_raw(ctx as *mut ConditionalRun)) };
+}
+
+impl<'a, T> SystemBuilder<'a, T>
+where
+    T: QueryTuple,
+{
+    /// Only run the system in frames in which `condition` returns true.
+    ///
+    /// Conditions are evaluated every time the system runs, before its query is iterated, so a
+    /// skipped system doesn't iterate any tables. When a system has multiple conditions, it runs
+    /// only when all of them return true. Conditions are evaluated after the tick source, rate
+    /// and interval of the system, which skip the system before its conditions are checked.
+    ///
+    /// Conditions are evaluated on the main thread, once every time the system runs, such as
+    /// every step of a [`FixedUpdate`](crate::addons::pipeline::FixedUpdate) system. The
+    /// workers of a multithreaded system use the result of the main thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `condition` - The condition, which receives the world the system runs in.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    /// }
+    ///
+    /// #[derive(Component)]
+    /// struct Paused;
+    ///
+    /// let world = World::new();
+    ///
+    /// world
+    ///     .system::<&mut Position>()
+    ///     .run_if(|world| !world.has::<Paused>())
+    ///     .each(|p| p.x += 1.0);
+    ///
+    /// let e = world.entity().set(Position { x: 0.0 });
+    /// world.progress();
+    ///
+    /// world.add::<Paused>();
+    /// world.progress();
+    ///
+    /// e.get::<&Position>(|p| assert_eq!(p.x, 1.0));
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`SystemBuilder::run_if_singleton()`]
+    /// * [`SystemBuilder::run_if_singleton_changed()`]
+    /// * [`SystemBuilder::run_if_equals()`]
+    /// * [`SystemBuilder::run_if_enum()`]
+    /// * [`SystemBuilder::run_if_ticked()`]
+    pub fn run_if(&mut self, condition: impl Fn(WorldRef) -> bool + 'static) -> &mut Self {
+        self.conditions.push(Box::new(condition));
+        self
+    }
+
+    /// Only run the system when the singleton `C` exists.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `C` - The singleton component or tag.
+    pub fn run_if_singleton<C>(&mut self) -> &mut Self
+    where
+        C: ComponentOrPairId,
+    {
+        self.run_if(|world| world.has::<C>())
+    }
+
+    /// Only run the system when the singleton `C` was set or modified since the previous frame
+    /// in which the condition was evaluated, or since the system was created.
+    ///
+    /// The condition uses the change detection of a cached query for `C`, which is created as
+    /// a child of the system. Changes made through [`World::set()`] and [`World::modified()`]
+    /// are detected, as are writes by queries with mutable `C` terms. As the query matches all
+    /// entities with `C`, changes to `C` on entities other than the singleton are detected as
+    /// well.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `C` - The singleton component.
+    pub fn run_if_singleton_changed<C>(&mut self) -> &mut Self
+    where
+        C: ComponentId + DataComponent,
+    {
+        let query = self.world().query::<&C>().set_cached().build();
+        // enable change detection, and store the current state as unchanged
+        query.is_changed();
+        query.run(|mut it| while it.next() {});
+        // the query is owned by its entity, which is deleted with the system
+        query.entity().child_of_id(self.desc.entity);
+        let query = query.query.as_ptr();
+
+        self.run_if(move |world| unsafe {
+            if !sys::ecs_query_changed(query) {
+                return false;
+            }
+            // iterating the query stores the current state for the next evaluation
+            let mut it = sys::ecs_query_iter(world.world_ptr(), query);
+            while sys::ecs_query_next(&mut it) {}
+            true
+        })
+    }
+
+    /// Only run the system when the singleton `C` is equal to `value`.
+    ///
+    /// This is a convenient way to run systems in a single state of a state machine that is
+    /// stored as singleton.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `C` - The singleton component.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value the singleton must be equal to.
+    pub fn run_if_equals<C>(&mut self, value: C) -> &mut Self
+    where
+        C: ComponentId + DataComponent + PartialEq,
+    {
+        self.run_if(move |world| {
+            let mut equals = false;
+            world.try_get::<&C>(|current| equals = *current == value);
+            equals
+        })
+    }
+
+    /// Only run the system when the world has the enum constant `constant`, which is added with
+    /// [`World::add_enum()`].
+    ///
+    /// # Type Parameters
+    ///
+    /// * `C` - The enum component.
+    ///
+    /// # Arguments
+    ///
+    /// * `constant` - The enum constant the world must have.
+    pub fn run_if_enum<C>(&mut self, constant: C) -> &mut Self
+    where
+        C: ComponentId + ComponentType<Enum> + EnumComponentInfo,
+    {
+        let enum_id = C::id(self.world());
+        let constant = constant.id_variant(self.world()).id();
+        self.run_if(move |world| world.entity_from_id(enum_id).has_id((enum_id, constant)))
+    }
+
+    /// Only run the system in frames in which `tick_source` ticked.
+    ///
+    /// Unlike [`SystemBuilder::tick_source_id()`], this doesn't change the delta time of the
+    /// system, and it can be combined with another tick source or rate.
+    ///
+    /// # Arguments
+    ///
+    /// * `tick_source` - An entity with a [`flecs::system::TickSource`], such as a timer.
+    pub fn run_if_ticked(&mut self, tick_source: impl Into<Entity>) -> &mut Self {
+        let tick_source = tick_source.into();
+        self.run_if(move |world| {
+            let mut ticked = false;
+            world
+                .entity_from_id(tick_source)
+                .try_get::<&flecs::system::TickSource>(|source| ticked = source.tick);
+            ticked
+        })
+    }
+}
@@ -0,0 +1,541 @@
+//! Hooks that are called around every system and observer invocation, for profiling and tracing.
+
+use std::collections::HashSet;
+use std::os::raw::c_void;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::core::*;
+use crate::sys;
+
+/// An invocation of a system or observer, passed to the hooks of [`World::on_system_run()`].
+#[derive(Clone, Copy)]
+pub struct SystemRun<'a> {
+    /// The system or observer.
+    pub entity: EntityView<'a>,
+    /// Whether `entity` is an observer.
+    pub is_observer: bool,
+    /// The phase of a system, or 0 for observers and systems without a phase.
+    pub phase: Entity,
+    /// The event that invoked an observer, or 0 for systems.
+    pub event: Entity,
+    /// The number of entities a system iterated, or the number of entities an observer is
+    /// invoked for. Systems count their entities while they run, so this is 0 for systems in
+    /// the `pre` hook.
+    pub entity_count: i32,
+    /// The delta time passed to the system.
+    pub delta_time: FTime,
+}
+
+/// Handle to hooks added with [`World::on_system_run()`], which removes them with
+/// [`World::remove_system_run_hook()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SystemRunHook(u64);
+
+/// A hook that is called before a system or observer runs.
+type PreHook = Rc<dyn Fn(&SystemRun)>;
+
+/// A hook that is called after a system or observer ran, with the time it took.
+type PostHook = Rc<dyn Fn(&SystemRun, Duration)>;
+
+#[derive(Clone)]
+struct RunHook {
+    id: u64,
+    pre: PreHook,
+    post: PostHook,
+}
+
+/// The run hooks of a world.
+#[derive(Default)]
+pub(crate) struct SystemRunHooks {
+    /// The hooks, which are shared with running systems so hooks can be added while they run.
+    hooks: Rc<Vec<RunHook>>,
+    /// The id given to the next hook.
+    next_id: u64,
+    /// Whether the systems and observers that existed when the first hook was added were
+    /// instrumented. Systems and observers built after that are instrumented by their builder.
+    installed: bool,
+    /// The systems and observers that were instrumented, or that must not be instrumented.
+    instrumented: HashSet<u64>,
+}
+
+/// The run context of an instrumented system or observer, which wraps the run callback and
+/// context it was created with.
+struct InstrumentedRun {
+    is_observer: bool,
+    run: sys::ecs_run_action_t,
+    run_ctx: *mut c_void,
+    run_ctx_free: sys::ecs_ctx_free_t,
+}
+
+impl Drop for InstrumentedRun {
+    fn drop(&mut self) {
+        if let Some(free) = self.run_ctx_free {
+            unsafe { free(self.run_ctx) };
+        }
+    }
+}
+
+impl InstrumentedRun {
+    unsafe fn run_inner(&self, it: *mut sys::ecs_iter_t) {
+        unsafe {
+            (*it).run_ctx = self.run_ctx;
+            if let Some(run) = self.run {
+                run(it);
+            } else if let Some(callback) = (*it).callback {
+                if self.is_observer {
+                    callback(it);
+                } else {
+                    while sys::ecs_iter_next(it) {
+                        callback(it);
+                    }
+                }
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn run_instrumented(it: *mut sys::ecs_iter_t) {
+    unsafe {
+        let instrumented = &*((*it).run_ctx as *const InstrumentedRun);
+        let real_world = (*it).real_world;
+
+        // hooks are only called on the main thread, as they don't have to be thread safe, so
+        // workers don't access them at all
+        if sys::ecs_stage_get_id((*it).world) != 0 {
+            instrumented.run_inner(it);
+            return;
+        }
+
+        let world = WorldRef::from_ptr(real_world);
+        let hooks = world.world_ctx().system_run_hooks.hooks.clone();
+        if hooks.is_empty() {
+            instrumented.run_inner(it);
+            return;
+        }
+
+        let entity = (*it).system;
+        let mut run = if instrumented.is_observer {
+            SystemRun {
+                entity: EntityView::new_from(world, entity),
+                is_observer: true,
+                phase: Entity::new(0),
+                event: Entity::new((*it).event),
+                entity_count: (*it).count,
+                delta_time: (*it).delta_time,
+            }
+        } else {
+            SystemRun {
+                entity: EntityView::new_from(world, entity),
+                is_observer: false,
+                phase: Entity::new(sys::ecs_get_target(real_world, entity, ECS_DEPENDS_ON, 0)),
+                event: Entity::new(0),
+                entity_count: 0,
+                delta_time: (*it).delta_time,
+            }
+        };
+
+        for hook in hooks.iter() {
+            (hook.pre)(&run);
+        }
+        let start = Instant::now();
+        instrumented.run_inner(it);
+        let duration = start.elapsed();
+        if !instrumented.is_observer {
+            // query iterators add up the entities they returned in `frame_offset`, the query
+            // iterator of a multithreaded system is chained to the iterator of its worker
+            let query_it = if (*it).chain_it.is_null() {
+                it
+            } else {
+                (*it).chain_it
+            };
+            run.entity_count = (*query_it).frame_offset;
+        }
+        for hook in hooks.iter() {
+            (hook.post)(&run, duration);
+        }
+    }
+}
+
+unsafe extern "C" fn free_instrumented(ctx: *mut c_void) {
+    unsafe { drop(Box::from_raw(ctx as *mut InstrumentedRun)) };
+}
+
+/// Returns whether `entity` is part of the flecs modules.
+fn is_builtin(world_ptr: *const sys::ecs_world_t, entity: u64) -> bool {
+    let mut parent = unsafe { sys::ecs_get_parent(world_ptr, entity) };
+    while parent != 0 {
+        if parent == ECS_FLECS {
+            return true;
+        }
+        parent = unsafe { sys::ecs_get_parent(world_ptr, parent) };
+    }
+    false
+}
+
+/// Replaces the run callback of a system or observer with one that calls the run hooks.
+fn instrument(world: WorldRef, entity: u64) {
+    let world_ptr = world.real_world().world_ptr_mut();
+    if !world
+        .world_ctx_mut()
+        .system_run_hooks
+        .instrumented
+        .insert(entity)
+        || is_builtin(world_ptr, entity)
+    {
+        return;
+    }
+
+    unsafe {
+        if sys::ecs_has_id(world_ptr, entity, ECS_SYSTEM) {
+            let system = sys::ecs_system_get(world_ptr, entity) as *mut sys::ecs_system_t;
+            let instrumented = Box::new(InstrumentedRun {
+                is_observer: false,
+                run: (*system).run,
+                run_ctx: (*system).run_ctx,
+                run_ctx_free: (*system).run_ctx_free,
+            });
+            (*system).run = Some(run_instrumented);
+            (*system).run_ctx = Box::into_raw(instrumented) as *mut c_void;
+            (*system).run_ctx_free = Some(free_instrumented);
+        } else if sys::ecs_has_id(world_ptr, entity, ECS_OBSERVER) {
+            let observer = sys::ecs_observer_get(world_ptr, entity) as *mut sys::ecs_observer_t;
+            let instrumented = Box::new(InstrumentedRun {
+                is_observer: true,
+                run: (*observer).run,
+                run_ctx: (*observer).run_ctx,
+                run_ctx_free: (*observer).run_ctx_free,
+            });
+            (*observer).run = Some(run_instrumented);
+            (*observer).run_ctx = Box::into_raw(instrumented) as *mut c_void;
+            (*observer).run_ctx_free = Some(free_instrumented);
+        }
+    }
+}
+
+/// Instruments a system or observer that was just built, if run hooks were added to the world.
+pub(crate) fn instrument_if_hooked(world: WorldRef, entity: u64) {
+    if world.world_ctx().system_run_hooks.installed {
+        instrument(world, entity);
+    }
+}
+
+/// Keeps a system or observer from being instrumented, which is used for systems that are
+/// part of the instrumentation.
+#[cfg(feature = "flecs_pipeline")]
+fn skip_instrumentation(world: WorldRef, entity: u64) {
+    world
+        .world_ctx_mut()
+        .system_run_hooks
+        .instrumented
+        .insert(entity);
+}
+
+/// Instruments all systems and observers that exist.
+fn install(world: WorldRef) {
+    let world_ptr = world.real_world().world_ptr_mut();
+    let mut entities = Vec::new();
+    for tag in [ECS_SYSTEM, ECS_OBSERVER] {
+        unsafe {
+            let mut it = sys::ecs_each_id(world_ptr, tag);
+            while sys::ecs_each_next(&mut it) {
+                for i in 0..it.count as usize {
+                    entities.push(*it.entities.add(i));
+                }
+            }
+        }
+    }
+
+    for entity in entities {
+        instrument(world, entity);
+    }
+    world.world_ctx_mut().system_run_hooks.installed = true;
+}
+
+impl World {
+    /// Adds hooks that are called before and after every invocation of a system or observer.
+    ///
+    /// The hooks receive the system or observer, its phase or event, the number of entities it
+    /// matches and its delta time. The post hook also receives how long the invocation took.
+    /// This makes it possible to feed profilers such as Tracy, to open `tracing` spans, or to
+    /// record a trace with [`World::record_chrome_trace()`].
+    ///
+    /// Adding the first hook instruments the systems and observers of the world, and those that
+    /// are created after. Until then, systems and observers run without any overhead. Systems
+    /// and observers of the flecs modules are not instrumented.
+    ///
+    /// Hooks are only called on the main thread: multi-threaded systems call them for the part
+    /// of their entities that runs on the main thread. Systems that are skipped by their run
+    /// conditions still call the hooks.
+    ///
+    /// # Arguments
+    ///
+    /// * `pre` - Called before the system or observer runs.
+    /// * `post` - Called after the system or observer ran, with the duration of the run.
+    ///
+    /// # Returns
+    ///
+    /// A handle that removes the hooks with [`World::remove_system_run_hook()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    /// }
+    ///
+    /// let world = World::new();
+    ///
+    /// world
+    ///     .system_named::<&mut Position>("Move")
+    ///     .each(|p| p.x += 1.0);
+    ///
+    /// world.entity().set(Position { x: 0.0 });
+    ///
+    /// let runs = Rc::new(RefCell::new(Vec::new()));
+    /// let runs_post = runs.clone();
+    /// world.on_system_run(
+    ///     |_| {},
+    ///     move |run, _duration| {
+    ///         runs_post
+    ///             .borrow_mut()
+    ///             .push((run.entity.name().to_string(), run.entity_count))
+    ///     },
+    /// );
+    ///
+    /// world.progress();
+    /// assert_eq!(*runs.borrow(), [("Move".to_string(), 1)]);
+    /// ```
+    pub fn on_system_run(
+        &self,
+        pre: impl Fn(&SystemRun) + 'static,
+        post: impl Fn(&SystemRun, Duration) + 'static,
+    ) -> SystemRunHook {
+        let world = WorldRef::from(self);
+        let hooks = &mut self.world_ctx_mut().system_run_hooks;
+        let id = hooks.next_id;
+        hooks.next_id += 1;
+        Rc::make_mut(&mut hooks.hooks).push(RunHook {
+            id,
+            pre: Rc::new(pre),
+            post: Rc::new(post),
+        });
+
+        if !hooks.installed {
+            install(world);
+        }
+        SystemRunHook(id)
+    }
+
+    /// Removes hooks added with [`World::on_system_run()`].
+    ///
+    /// Systems and observers stay instrumented, but without hooks they only check whether
+    /// hooks were added before they run.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - The hooks to remove.
+    pub fn remove_system_run_hook(&self, hook: SystemRunHook) {
+        let hooks = &mut self.world_ctx_mut().system_run_hooks;
+        Rc::make_mut(&mut hooks.hooks).retain(|run_hook| run_hook.id != hook.0);
+    }
+}
+
+#[cfg(feature = "flecs_pipeline")]
+pub use chrome_trace::ChromeTrace;
+
+#[cfg(feature = "flecs_pipeline")]
+mod chrome_trace {
+    use std::cell::RefCell;
+    use std::fmt::Write;
+    use std::io;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    struct TraceEvent {
+        name: String,
+        category: &'static str,
+        phase: String,
+        entity_count: i32,
+        start: Duration,
+        duration: Duration,
+    }
+
+    struct TraceState {
+        path: PathBuf,
+        frames_left: u32,
+        start: Instant,
+        events: Vec<TraceEvent>,
+        hook: Option<SystemRunHook>,
+        driver: u64,
+        result: Option<io::Result<()>>,
+    }
+
+    /// Handle to a trace that is recorded with [`World::record_chrome_trace()`].
+    #[derive(Clone)]
+    pub struct ChromeTrace {
+        state: Rc<RefCell<TraceState>>,
+    }
+
+    impl ChromeTrace {
+        /// Returns whether all frames were recorded and the trace was written.
+        pub fn is_finished(&self) -> bool {
+            self.state.borrow().result.is_some()
+        }
+
+        /// Returns the error that occurred while writing the trace, if any.
+        pub fn take_error(&self) -> Option<io::Error> {
+            let mut trace = self.state.borrow_mut();
+            match trace.result {
+                Some(Err(_)) => trace.result.replace(Ok(())).and_then(Result::err),
+                _ => None,
+            }
+        }
+    }
+
+    fn escape_json(text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                '"' => escaped.push_str("\\\""),
+                '\\' => escaped.push_str("\\\\"),
+                c if (c as u32) < 0x20 => {
+                    let _ = write!(escaped, "\\u{:04x}", c as u32);
+                }
+                c => escaped.push(c),
+            }
+        }
+        escaped
+    }
+
+    fn name_of(entity: EntityView) -> String {
+        match entity.get_name() {
+            Some(name) => name.to_string(),
+            None => format!("#{}", entity.id()),
+        }
+    }
+
+    /// Renders the events as a Chrome trace-event JSON document.
+    fn to_json(events: &[TraceEvent]) -> String {
+        let mut json = String::from("{\"traceEvents\":[\n");
+        for (index, event) in events.iter().enumerate() {
+            if index > 0 {
+                json.push_str(",\n");
+            }
+            let _ = write!(
+                json,
+                "{{\"name\":\"{}\",\"cat\":\"{}\",\"ph\":\"X\",\"ts\":{},\"dur\":{},\"pid\":1,\"tid\":1,\"args\":{{\"phase\":\"{}\",\"entities\":{}}}}}",
+                escape_json(&event.name),
+                event.category,
+                event.start.as_micros(),
+                event.duration.as_micros(),
+                escape_json(&event.phase),
+                event.entity_count
+            );
+        }
+        json.push_str("\n]}\n");
+        json
+    }
+
+    /// Ends a recorded frame, and writes the trace after the last frame.
+    unsafe extern "C" fn trace_frame_end(world: *mut sys::ecs_world_t, ctx: *mut c_void) {
+        let state = unsafe { Rc::from_raw(ctx as *const RefCell<TraceState>) };
+        let mut trace = state.borrow_mut();
+        if trace.result.is_some() {
+            return;
+        }
+        trace.frames_left = trace.frames_left.saturating_sub(1);
+        if trace.frames_left > 0 {
+            return;
+        }
+
+        let world = unsafe { WorldRef::from_ptr(world) };
+        if let Some(hook) = trace.hook.take() {
+            world.remove_system_run_hook(hook);
+        }
+        let driver = trace.driver;
+        trace.result = Some(std::fs::write(&trace.path, to_json(&trace.events)));
+        trace.events = Vec::new();
+        drop(trace);
+        world.entity_from_id(driver).destruct();
+    }
+
+    impl World {
+        /// Records the systems and observers that run in the next `frames` frames, and writes
+        /// them to `path` as Chrome trace-event JSON.
+        ///
+        /// The trace can be opened in `chrome://tracing`, Perfetto or Speedscope. Every system
+        /// and observer invocation is a complete event, with its phase and entity count as
+        /// arguments. Invocations that run inside other invocations, such as systems run by the
+        /// fixed timestep, are nested.
+        ///
+        /// The trace is written at the end of the last frame. Uses [`World::on_system_run()`]
+        /// to record the invocations.
+        ///
+        /// # Arguments
+        ///
+        /// * `path` - The file to write the trace to.
+        /// * `frames` - The number of frames to record.
+        ///
+        /// # Returns
+        ///
+        /// A handle to check whether the trace was written.
+        pub fn record_chrome_trace(&self, path: impl Into<PathBuf>, frames: u32) -> ChromeTrace {
+            let world = WorldRef::from(self);
+            let state = Rc::new(RefCell::new(TraceState {
+                path: path.into(),
+                frames_left: frames.max(1),
+                start: Instant::now(),
+                events: Vec::new(),
+                hook: None,
+                driver: 0,
+                result: None,
+            }));
+
+            let driver_state = state.clone();
+            let mut driver = self.system::<()>();
+            skip_instrumentation(world, driver.desc.entity);
+            let driver = driver.kind_id(ECS_PRE_FRAME).run(move |it| unsafe {
+                let ctx = Rc::into_raw(driver_state.clone()) as *mut c_void;
+                sys::ecs_run_post_frame(it.world().world_ptr_mut(), Some(trace_frame_end), ctx);
+            });
+            state.borrow_mut().driver = *driver.id();
+
+            let post_state = state.clone();
+            let hook = self.on_system_run(
+                |_| {},
+                move |run, duration| {
+                    let mut trace = post_state.borrow_mut();
+                    let start = trace.start.elapsed().saturating_sub(duration);
+                    trace.events.push(TraceEvent {
+                        name: name_of(run.entity),
+                        category: if run.is_observer {
+                            "observer"
+                        } else {
+                            "system"
+                        },
+                        phase: if run.is_observer {
+                            name_of(run.entity.world().entity_from_id(run.event))
+                        } else if run.phase == 0 {
+                            String::new()
+                        } else {
+                            name_of(run.entity.world().entity_from_id(run.phase))
+                        },
+                        entity_count: run.entity_count,
+                        start,
+                        duration,
+                    });
+                },
+            );
+            state.borrow_mut().hook = Some(hook);
+
+            ChromeTrace { state }
+        }
+    }
+}
@@ -1,5 +1,6 @@
 //! `SystemBuilder` is a builder pattern for creating systems.
 
+use crate::addons::system::run_condition::{wrap_run, RunCondition};
 use crate::addons::system::*;
 use crate::core::internals::*;
 use crate::core::private::internal_SystemAPI;
@@ -14,6 +15,7 @@ where
     term_builder: TermBuilder,
     world: WorldRef<'a>,
     is_instanced: bool,
+    pub(crate) conditions: Vec<RunCondition>,
     _phantom: std::marker::PhantomData<&'a T>,
 }
 
@@ -29,6 +31,7 @@ where
             world: world.into(),
             _phantom: std::marker::PhantomData,
             is_instanced: false,
+            conditions: Vec::new(),
         };
 
         obj.desc.entity = unsafe { sys::ecs_entity_init(obj.world_ptr_mut(), &Default::default()) };
@@ -55,6 +58,7 @@ where
             world: world.into(),
             _phantom: std::marker::PhantomData,
             is_instanced: false,
+            conditions: Vec::new(),
         };
 
         if obj.desc.entity == 0 {
@@ -87,6 +91,7 @@ where
             world: world.into(),
             _phantom: std::marker::PhantomData,
             is_instanced: false,
+            conditions: Vec::new(),
         };
 
         let entity_desc: sys::ecs_entity_desc_t = sys::ecs_entity_desc_t {
@@ -321,7 +326,7 @@ impl<'a, T: QueryTuple> internals::QueryConfig<'a> for SystemBuilder<'a, T> {
     }
     #[inline(always)]
     fn count_generic_terms(&self) -> i32 {
-        T::COUNT
+        T::TERM_COUNT
     }
 }
 
@@ -329,6 +334,15 @@ impl<'a, T: QueryTuple> TermBuilderImpl<'a> for SystemBuilder<'a, T> {}
 
 impl<'a, T: QueryTuple> QueryBuilderImpl<'a> for SystemBuilder<'a, T> {}
 
+impl<T> Drop for SystemBuilder<'_, T>
+where
+    T: QueryTuple,
+{
+    fn drop(&mut self) {
+        free_unbuilt_group_callbacks(&mut self.desc.query);
+    }
+}
+
 impl<'a, T> Builder<'a> for SystemBuilder<'a, T>
 where
     T: QueryTuple,
@@ -342,7 +356,18 @@ where
     /// * C++ API: `node_builder::build`
     #[doc(alias = "node_builder::build")]
     fn build(&mut self) -> Self::BuiltType {
-        let system = System::new(self.world(), self.desc, self.is_instanced);
+        self.assert_term_count();
+        if !self.conditions.is_empty() {
+            wrap_run(&mut self.desc, std::mem::take(&mut self.conditions));
+        }
+        #[cfg(feature = "flecs_pipeline")]
+        crate::addons::pipeline::fixed_tick_source(self.world(), &mut self.desc);
+        let world = self.world();
+        let (desc, is_instanced) = (self.desc, self.is_instanced);
+        let system = init_with_group_callbacks(&mut self.desc.query, || {
+            System::new(world, desc, is_instanced)
+        });
+        instrument_if_hooked(self.world(), *system.id());
         for string_parts in self.term_builder.str_ptrs_to_free.iter() {
             unsafe {
                 String::from_raw_parts(
@@ -1,6 +1,13 @@
 //! When running a pipeline, systems are ran each time [`World::progress()`](crate::core::World::progress) is called.
 //! The `flecs_timer` feature addon makes it possible to run systems at a specific time interval or rate.
 //!
+//! Timers can also call Rust callbacks, with [`World::after()`], [`World::every()`],
+//! [`EntityView::after()`] and [`EntityView::every()`].
+
+mod callbacks;
+pub(crate) use callbacks::Timers;
+pub use callbacks::{TimerHandle, TimerOf};
+
 use std::ops::{Deref, DerefMut};
 
 use flecs_ecs_sys::{self as sys};
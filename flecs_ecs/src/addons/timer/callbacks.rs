@@ -0,0 +1,492 @@
+//! Timers that call Rust callbacks, for gameplay code that runs something after a delay or at
+//! an interval.
+
+use std::collections::HashMap;
+
+use crate::core::*;
+
+/// Relationship from a timer to the entity it was started on, with [`EntityView::after()`] or
+/// [`EntityView::every()`].
+///
+/// Timers are deleted together with their entity.
+#[derive(flecs_ecs_derive::Component)]
+pub struct TimerOf;
+
+/// Handle of a timer started with [`World::after()`], [`World::every()`],
+/// [`EntityView::after()`] or [`EntityView::every()`].
+///
+/// # See also
+///
+/// * [`World::cancel_timer()`]
+/// * [`World::pause_timer()`]
+/// * [`World::resume_timer()`]
+/// * [`World::is_timer_active()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerHandle(Entity);
+
+impl TimerHandle {
+    /// Returns the entity of the timer.
+    pub fn entity(&self) -> Entity {
+        self.0
+    }
+}
+
+enum TimerCallback {
+    Once(Box<dyn FnOnce(EntityView)>),
+    Repeat(Box<dyn FnMut(EntityView)>),
+}
+
+/// A timer with a callback, progressed by the timer system of the world.
+///
+/// The callback is stored in [`Timers`], as components can't hold closures that aren't `Send`.
+#[derive(flecs_ecs_derive::Component)]
+struct CallbackTimer {
+    /// The entity passed to the callback, or 0 for world timers, which pass the timer entity.
+    owner: u64,
+    /// The delay of one-shot timers, or the interval of repeating timers.
+    interval: FTime,
+    /// The time elapsed since the timer started or last fired.
+    elapsed: FTime,
+    /// Whether a one-shot timer fired, after which it is deleted.
+    fired: bool,
+    /// Whether the timer is paused, in which case it isn't progressed.
+    paused: bool,
+}
+
+/// The callback timers of a world.
+#[derive(Default)]
+pub(crate) struct Timers {
+    /// The system that progresses the timers, or 0 before the first timer is started.
+    driver: u64,
+    /// The callbacks of the timers, by timer entity.
+    callbacks: HashMap<u64, TimerCallback>,
+    /// The timer of which the callback is running while the timer system holds the timers, and
+    /// whether it repeats.
+    running: Option<(u64, bool)>,
+    /// The pause changes made from timer callbacks, which are applied after the callback
+    /// returns, by timer entity.
+    paused: HashMap<u64, bool>,
+}
+
+/// Applies the pause change made from a timer callback to `timer`, if any.
+fn apply_paused(world: WorldRef, entity: u64, timer: &mut CallbackTimer) {
+    let timers = &mut world.world_ctx_mut().timers;
+    if timers.paused.is_empty() {
+        return;
+    }
+    if let Some(paused) = timers.paused.remove(&entity) {
+        timer.paused = paused;
+    }
+}
+
+/// Progresses `timer` by the delta time of the frame, and calls its callback when it expires.
+fn progress_timer(it: &TableIter<false>, index: usize, timer: &mut CallbackTimer) {
+    let world = it.world();
+    let entity = it.entity(index);
+    apply_paused(world, *entity.id(), timer);
+    if timer.fired || timer.paused {
+        return;
+    }
+
+    timer.elapsed += it.delta_time();
+
+    while timer.elapsed >= timer.interval {
+        let target = if timer.owner == 0 {
+            entity
+        } else {
+            world.entity_from_id(timer.owner)
+        };
+
+        // the callback is taken out while it runs, so that it can start other timers
+        let Some(callback) = world.world_ctx_mut().timers.callbacks.remove(&*entity.id()) else {
+            return;
+        };
+        match callback {
+            TimerCallback::Once(callback) => {
+                timer.fired = true;
+                run_callback(world, (*entity.id(), false), || callback(target));
+                entity.destruct();
+                return;
+            }
+            TimerCallback::Repeat(mut callback) => {
+                run_callback(world, (*entity.id(), true), || callback(target));
+                world
+                    .world_ctx_mut()
+                    .timers
+                    .callbacks
+                    .insert(*entity.id(), TimerCallback::Repeat(callback));
+                apply_paused(world, *entity.id(), timer);
+                if timer.paused {
+                    return;
+                }
+                if timer.interval <= 0.0 {
+                    // fire once per frame
+                    timer.elapsed = 0.0;
+                    return;
+                }
+                timer.elapsed -= timer.interval;
+            }
+        }
+    }
+}
+
+/// Runs the callback of `timer`, during which pause changes are queued, as the timer system
+/// holds the timers.
+fn run_callback(world: WorldRef, timer: (u64, bool), callback: impl FnOnce()) {
+    let timers = &mut world.world_ctx_mut().timers;
+    let running = timers.running.replace(timer);
+    callback();
+    world.world_ctx_mut().timers.running = running;
+}
+
+fn start_timer(
+    world: WorldRef,
+    owner: u64,
+    interval: FTime,
+    callback: TimerCallback,
+) -> TimerHandle {
+    if world.world_ctx().timers.driver == 0 {
+        world
+            .component::<TimerOf>()
+            .add_trait::<(flecs::OnDeleteTarget, flecs::Delete)>();
+        world
+            .observer::<flecs::OnRemove, &CallbackTimer>()
+            .each_entity(|timer, _| {
+                let world = timer.world();
+                let timers = &mut world.world_ctx_mut().timers;
+                timers.callbacks.remove(&*timer.id());
+                timers.paused.remove(&*timer.id());
+            });
+        let driver = world
+            .system::<&mut CallbackTimer>()
+            .kind::<flecs::pipeline::PreUpdate>()
+            .each_iter(|it, index, timer| progress_timer(&it, index, timer));
+        world.world_ctx_mut().timers.driver = *driver.id();
+    }
+
+    let timer = world.entity();
+    if owner != 0 {
+        timer.add_first::<TimerOf>(owner);
+    }
+    world
+        .world_ctx_mut()
+        .timers
+        .callbacks
+        .insert(*timer.id(), callback);
+    timer.set(CallbackTimer {
+        owner,
+        interval,
+        elapsed: 0.0,
+        fired: false,
+        paused: false,
+    });
+    TimerHandle(timer.id())
+}
+
+impl World {
+    /// Call `callback` once, after `delay` seconds.
+    ///
+    /// Timers are progressed by the delta time of every frame, including the time scale, so
+    /// they are paused while the time scale set with [`World::set_time_scale()`] is 0. They
+    /// fire in the [`flecs::pipeline::PreUpdate`] phase, where changes to the world are deferred
+    /// until the end of the phase.
+    ///
+    /// # Arguments
+    ///
+    /// * `delay` - The time after which the callback is called.
+    /// * `callback` - The callback, which is passed the world.
+    ///
+    /// # Returns
+    ///
+    /// The handle of the timer, which cancels it with [`World::cancel_timer()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct GameOver;
+    ///
+    /// let world = World::new();
+    /// world.set_deterministic(1.0);
+    ///
+    /// world.after(2.5, |world| {
+    ///     world.add::<GameOver>();
+    /// });
+    ///
+    /// world.step_frames(2);
+    /// assert!(!world.has::<GameOver>());
+    /// world.step_frames(1);
+    /// assert!(world.has::<GameOver>());
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`World::every()`]
+    /// * [`EntityView::after()`]
+    pub fn after(&self, delay: FTime, callback: impl FnOnce(WorldRef) + 'static) -> TimerHandle {
+        let callback = Box::new(move |entity: EntityView| callback(entity.world()));
+        start_timer(self.into(), 0, delay, TimerCallback::Once(callback))
+    }
+
+    /// Call `callback` every `interval` seconds, until the timer is canceled.
+    ///
+    /// See [`World::after()`] for when timers fire. When more than one interval passed in a
+    /// frame, the callback is called once for every interval. An interval of 0 calls the
+    /// callback every frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The time between calls.
+    /// * `callback` - The callback, which is passed the world.
+    ///
+    /// # Returns
+    ///
+    /// The handle of the timer, which cancels it with [`World::cancel_timer()`].
+    ///
+    /// # See also
+    ///
+    /// * [`World::after()`]
+    /// * [`EntityView::every()`]
+    pub fn every(
+        &self,
+        interval: FTime,
+        mut callback: impl FnMut(WorldRef) + 'static,
+    ) -> TimerHandle {
+        let callback = Box::new(move |entity: EntityView| callback(entity.world()));
+        start_timer(self.into(), 0, interval, TimerCallback::Repeat(callback))
+    }
+
+    /// Cancel a timer, so that its callback isn't called anymore.
+    ///
+    /// # Arguments
+    ///
+    /// * `timer` - The handle of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Whether the timer was active.
+    ///
+    /// # See also
+    ///
+    /// * [`World::is_timer_active()`]
+    pub fn cancel_timer(&self, timer: TimerHandle) -> bool {
+        let active = self.is_timer_active(timer);
+        if active {
+            self.entity_from_id(timer.0).destruct();
+        }
+        active
+    }
+
+    /// Pause a timer, so that it doesn't progress until it is resumed.
+    ///
+    /// The time that elapsed before the timer was paused is kept, so a resumed timer fires
+    /// after the remaining time. Timers paused or resumed from a timer callback change once the
+    /// callback returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `timer` - The handle of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Whether the timer was active.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Spawned;
+    ///
+    /// let world = World::new();
+    /// world.set_deterministic(1.0);
+    ///
+    /// let timer = world.after(2.0, |world| {
+    ///     world.add::<Spawned>();
+    /// });
+    ///
+    /// world.step_frames(1);
+    /// world.pause_timer(timer);
+    /// world.step_frames(3);
+    /// assert!(!world.has::<Spawned>());
+    ///
+    /// world.resume_timer(timer);
+    /// world.step_frames(1);
+    /// assert!(world.has::<Spawned>());
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`World::resume_timer()`]
+    /// * [`World::is_timer_paused()`]
+    pub fn pause_timer(&self, timer: TimerHandle) -> bool {
+        self.set_timer_paused(timer, true)
+    }
+
+    /// Resume a timer that was paused with [`World::pause_timer()`].
+    ///
+    /// # Arguments
+    ///
+    /// * `timer` - The handle of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Whether the timer was active.
+    ///
+    /// # See also
+    ///
+    /// * [`World::pause_timer()`]
+    /// * [`World::is_timer_paused()`]
+    pub fn resume_timer(&self, timer: TimerHandle) -> bool {
+        self.set_timer_paused(timer, false)
+    }
+
+    /// Returns whether a timer is paused with [`World::pause_timer()`].
+    ///
+    /// # Arguments
+    ///
+    /// * `timer` - The handle of the timer.
+    ///
+    /// # See also
+    ///
+    /// * [`World::pause_timer()`]
+    /// * [`World::resume_timer()`]
+    pub fn is_timer_paused(&self, timer: TimerHandle) -> bool {
+        let timers = &self.world_ctx().timers;
+        if let Some(&paused) = timers.paused.get(&*timer.0) {
+            return paused;
+        }
+        // the timer of which the callback is running isn't paused, and is held by the system
+        if timers
+            .running
+            .is_some_and(|(running, _)| running == *timer.0)
+        {
+            return false;
+        }
+        let entity = self.entity_from_id(timer.0);
+        let mut paused = false;
+        if entity.is_alive() {
+            entity.try_get::<&CallbackTimer>(|timer| paused = timer.paused);
+        }
+        paused
+    }
+
+    fn set_timer_paused(&self, timer: TimerHandle, paused: bool) -> bool {
+        let active = self.is_timer_active(timer);
+        if active {
+            let timers = &mut self.world_ctx_mut().timers;
+            if timers.running.is_some() {
+                // the timer system holds the timers, the change is applied once the callback
+                // returns, or when the timer is progressed
+                timers.paused.insert(*timer.0, paused);
+            } else {
+                timers.paused.remove(&*timer.0);
+                self.entity_from_id(timer.0)
+                    .get::<&mut CallbackTimer>(|timer| timer.paused = paused);
+            }
+        }
+        active
+    }
+
+    /// Returns whether a timer will still call its callback.
+    ///
+    /// Timers stop being active when they are canceled, when a one-shot timer fired, or when
+    /// the entity the timer was started on is deleted. Paused timers are still active.
+    ///
+    /// # Arguments
+    ///
+    /// * `timer` - The handle of the timer.
+    pub fn is_timer_active(&self, timer: TimerHandle) -> bool {
+        // the timer system holds the timer of which the callback is running
+        if let Some((running, repeats)) = self.world_ctx().timers.running {
+            if running == *timer.0 {
+                return repeats;
+            }
+        }
+        let entity = self.entity_from_id(timer.0);
+        let mut active = false;
+        if entity.is_alive() {
+            entity.try_get::<&CallbackTimer>(|timer| active = !timer.fired);
+        }
+        active
+    }
+}
+
+impl<'a> EntityView<'a> {
+    /// Call `callback` once with this entity, after `delay` seconds.
+    ///
+    /// The timer is deleted together with the entity. See [`World::after()`] for when timers
+    /// fire.
+    ///
+    /// # Arguments
+    ///
+    /// * `delay` - The time after which the callback is called.
+    /// * `callback` - The callback, which is passed the entity.
+    ///
+    /// # Returns
+    ///
+    /// The handle of the timer, which cancels it with [`World::cancel_timer()`].
+    ///
+    /// # See also
+    ///
+    /// * [`EntityView::every()`]
+    /// * [`World::after()`]
+    pub fn after(self, delay: FTime, callback: impl FnOnce(EntityView) + 'static) -> TimerHandle {
+        start_timer(
+            self.world(),
+            *self.id(),
+            delay,
+            TimerCallback::Once(Box::new(callback)),
+        )
+    }
+
+    /// Call `callback` with this entity every `interval` seconds, until the timer is canceled or
+    /// the entity is deleted.
+    ///
+    /// See [`World::every()`] for when repeating timers fire.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The time between calls.
+    /// * `callback` - The callback, which is passed the entity.
+    ///
+    /// # Returns
+    ///
+    /// The handle of the timer, which cancels it with [`World::cancel_timer()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Health(i32);
+    ///
+    /// let world = World::new();
+    /// world.set_deterministic(0.5);
+    ///
+    /// let player = world.entity().set(Health(10));
+    /// player.every(1.0, |player| {
+    ///     player.get::<&mut Health>(|health| health.0 -= 1);
+    /// });
+    ///
+    /// world.step_frames(6);
+    /// player.get::<&Health>(|health| assert_eq!(health.0, 7));
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`EntityView::after()`]
+    /// * [`World::every()`]
+    pub fn every(self, interval: FTime, callback: impl FnMut(EntityView) + 'static) -> TimerHandle {
+        start_timer(
+            self.world(),
+            *self.id(),
+            interval,
+            TimerCallback::Repeat(Box::new(callback)),
+        )
+    }
+}
@@ -193,6 +193,7 @@ impl From<OperKind> for i16 {
 /// - `None`: No caching
 #[allow(clippy::unnecessary_cast)]
 #[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum QueryCacheKind {
     Default = sys::ecs_query_cache_kind_t_EcsQueryCacheDefault as u32,
     Auto = sys::ecs_query_cache_kind_t_EcsQueryCacheAuto as u32,
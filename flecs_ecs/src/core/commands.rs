@@ -0,0 +1,466 @@
+//! Thread-safe command buffer for recording world mutations from any thread.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread::ThreadId;
+
+use crate::core::*;
+use crate::sys;
+
+type Command = Box<dyn FnOnce(&World, &Created) + Send>;
+
+/// Marks the ids handed out by [`Commands::spawn()`] after the reserved ids ran out. The lower
+/// bits hold the number of deferred ids handed out before it.
+const DEFERRED_ID: u64 = 1 << 55;
+
+/// The number of entity ids a buffer created with [`World::commands()`] keeps reserved.
+const DEFAULT_RESERVE: usize = 32;
+
+pub(crate) struct CommandQueue {
+    state: Mutex<QueueState>,
+}
+
+/// The commands and reserved ids of a buffer, which are locked together so that the commands
+/// drained at a sync point are resolved against all deferred ids they use.
+struct QueueState {
+    /// The recorded commands, in the order they were recorded.
+    commands: Vec<Command>,
+    /// Entity ids that can be handed out from any thread.
+    ids: Vec<Entity>,
+    /// The number of ids that are reserved at every sync point, which grows to the number of
+    /// ids that were handed out from other threads between two sync points.
+    capacity: usize,
+    /// The number of ids handed out from other threads since the last sync point.
+    spawned: usize,
+    /// The deferred ids handed out since the last sync point.
+    deferred: Vec<u64>,
+    /// The number of deferred ids handed out, which makes every deferred id unique.
+    deferred_count: u64,
+    /// The entities created for deferred ids, until they are resolved with
+    /// [`Commands::resolve()`].
+    created: HashMap<u64, Entity>,
+    /// The world, or null once it has been destroyed.
+    world: *mut sys::ecs_world_t,
+    /// The thread of the world, on which ids are created directly.
+    thread: ThreadId,
+}
+
+// the world pointer is only used on the thread of the world
+unsafe impl Send for QueueState {}
+
+impl QueueState {
+    /// Returns the entity created for a deferred id, or `entity` when it isn't created yet or
+    /// isn't deferred.
+    fn resolve(&self, entity: Entity) -> Entity {
+        self.created.get(&*entity).copied().unwrap_or(entity)
+    }
+}
+
+/// The entities created for the deferred ids of a sync point.
+#[derive(Default)]
+struct Created {
+    entities: HashMap<u64, Entity>,
+}
+
+impl Created {
+    /// Returns the entity created for a deferred id, or `entity` for other ids.
+    fn resolve(&self, entity: Entity) -> Entity {
+        self.entities.get(&*entity).copied().unwrap_or(entity)
+    }
+}
+
+impl CommandQueue {
+    fn new(world: &World, capacity: usize) -> Self {
+        Self {
+            state: Mutex::new(QueueState {
+                commands: Vec::new(),
+                ids: Vec::new(),
+                capacity,
+                spawned: 0,
+                deferred: Vec::new(),
+                deferred_count: 0,
+                created: HashMap::new(),
+                world: world.ptr_mut(),
+                thread: std::thread::current().id(),
+            }),
+        }
+    }
+
+    /// Takes the recorded commands, and creates the entities for the deferred ids handed out
+    /// since the last sync point. Both happen under one lock, so every deferred id used by the
+    /// taken commands is created.
+    fn take_commands(&self, world: &World) -> (Vec<Command>, Created) {
+        let mut state = self.state.lock().unwrap();
+        let commands = std::mem::take(&mut state.commands);
+        let mut created = Created::default();
+        for id in std::mem::take(&mut state.deferred) {
+            let entity = Entity::from(unsafe { sys::ecs_new(world.ptr_mut()) });
+            state.created.insert(id, entity);
+            created.entities.insert(id, entity);
+        }
+        (commands, created)
+    }
+
+    /// Reserves entity ids until as many ids are available as were handed out from other
+    /// threads since the last sync point.
+    fn refill(&self, world: &World) {
+        let mut state = self.state.lock().unwrap();
+        state.capacity = state.capacity.max(state.spawned);
+        state.spawned = 0;
+        while state.ids.len() < state.capacity {
+            state
+                .ids
+                .push(Entity::from(unsafe { sys::ecs_new(world.ptr_mut()) }));
+        }
+    }
+
+    /// Deletes the entity ids that were reserved but never handed out.
+    fn release(&self, world: &World) {
+        for entity in self.state.lock().unwrap().ids.drain(..) {
+            unsafe { sys::ecs_delete(world.ptr_mut(), *entity) };
+        }
+    }
+
+    /// Detaches the queue from its world, which is destroyed.
+    pub(crate) fn detach(&self) {
+        self.state.lock().unwrap().world = std::ptr::null_mut();
+    }
+}
+
+/// Applies the commands of the world, at the end of a frame.
+#[cfg(feature = "flecs_pipeline")]
+unsafe extern "C" fn apply_commands_action(
+    world: *mut sys::ecs_world_t,
+    _ctx: *mut std::ffi::c_void,
+) {
+    unsafe { WorldRef::from_ptr(world) }.apply_commands();
+}
+
+/// A command buffer that records world mutations from any thread.
+///
+/// `Commands` is `Send`, `Sync` and `Clone`, which makes it possible to hand it to worker
+/// threads (such as rayon or tokio workers) that don't have access to the world. All clones
+/// share the same buffer. Recorded commands are applied in the order they were recorded:
+///
+/// - at the end of every frame that runs the pipeline, such as [`World::progress()`] and the
+///   frames of an [`App`](crate::addons::app::App)
+/// - explicitly, with [`World::apply()`]
+///
+/// [`Commands::spawn()`] returns the id of the new entity immediately. On the thread of the
+/// world the entity is created directly. Other threads get an id that was reserved on the
+/// thread of the world in advance, at the last sync point. Each sync point reserves as many ids
+/// as other threads spawned since the previous one. When the reserved ids run out, other
+/// threads get a deferred id, for which the entity is created when the buffer is applied, see
+/// [`Commands::resolve()`].
+///
+/// Commands that target entities which are no longer alive when the buffer is applied are skipped.
+///
+/// # Example
+///
+/// ```
+/// use flecs_ecs::prelude::*;
+///
+/// #[derive(Component)]
+/// struct Position {
+///     x: f32,
+///     y: f32,
+/// }
+///
+/// let world = World::new();
+/// let commands = world.commands();
+///
+/// let worker = commands.clone();
+/// let entity = std::thread::spawn(move || {
+///     // the id was reserved when the buffer was created
+///     let entity = worker.spawn();
+///     worker.insert(entity, Position { x: 1.0, y: 2.0 });
+///     entity
+/// })
+/// .join()
+/// .unwrap();
+///
+/// world.apply(&commands);
+/// assert!(world.entity_from_id(entity).has::<Position>());
+/// ```
+#[derive(Clone)]
+pub struct Commands {
+    queue: Arc<CommandQueue>,
+}
+
+impl Commands {
+    /// Returns the id of a new entity.
+    ///
+    /// The entity is alive but empty until components are inserted into it, either with
+    /// recorded commands or directly on the world. When the reserved ids ran out on a thread
+    /// other than the thread of the world, the returned id is deferred: the entity is created
+    /// when the buffer is applied, and commands recorded for the id are applied to it.
+    ///
+    /// # See also
+    ///
+    /// * [`Commands::try_spawn()`]
+    /// * [`Commands::resolve()`]
+    pub fn spawn(&self) -> Entity {
+        let mut state = self.queue.state.lock().unwrap();
+        if let Some(entity) = self.pop_reserved(&mut state) {
+            return entity;
+        }
+        if !state.world.is_null() && state.thread == std::thread::current().id() {
+            return Entity::from(unsafe { sys::ecs_new(state.world) });
+        }
+
+        state.spawned += 1;
+        let id = DEFERRED_ID | state.deferred_count;
+        state.deferred_count += 1;
+        state.deferred.push(id);
+        Entity::from(id)
+    }
+
+    /// Returns an entity id that is reserved for this command buffer, or `None` when all
+    /// reserved ids are handed out.
+    pub fn try_spawn(&self) -> Option<Entity> {
+        self.pop_reserved(&mut self.queue.state.lock().unwrap())
+    }
+
+    fn pop_reserved(&self, state: &mut QueueState) -> Option<Entity> {
+        let entity = state.ids.pop()?;
+        if state.thread != std::thread::current().id() {
+            state.spawned += 1;
+        }
+        Some(entity)
+    }
+
+    /// Returns the entity that was created for a deferred id, after the buffer is applied.
+    ///
+    /// The buffer remembers the entity of a deferred id until it is resolved, after which the
+    /// returned entity should be used instead of the deferred id. Ids that aren't deferred, and
+    /// deferred ids of which the entity isn't created yet, are returned as is.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity` - An id returned by [`Commands::spawn()`].
+    pub fn resolve(&self, entity: impl Into<Entity>) -> Entity {
+        let entity = entity.into();
+        self.queue
+            .state
+            .lock()
+            .unwrap()
+            .created
+            .remove(&*entity)
+            .unwrap_or(entity)
+    }
+
+    /// Records setting a component value on an entity.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity` - The entity to set the component on.
+    /// * `component` - The component value.
+    pub fn insert<T: ComponentId + DataComponent>(&self, entity: impl Into<Entity>, component: T) {
+        self.record_for(entity.into(), move |world, entity| {
+            if world.is_alive(entity) {
+                world.entity_from_id(entity).set(component);
+            }
+        });
+    }
+
+    /// Records adding a tag, component or pair to an entity.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `T` - The tag, component or pair to add.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity` - The entity to add `T` to.
+    pub fn add<T: ComponentOrPairId>(&self, entity: impl Into<Entity>) {
+        self.record_for(entity.into(), move |world, entity| {
+            if world.is_alive(entity) {
+                world.entity_from_id(entity).add::<T>();
+            }
+        });
+    }
+
+    /// Records removing a tag, component or pair from an entity.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `T` - The tag, component or pair to remove.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity` - The entity to remove `T` from.
+    pub fn remove<T: ComponentOrPairId>(&self, entity: impl Into<Entity>) {
+        self.record_for(entity.into(), move |world, entity| {
+            if world.is_alive(entity) {
+                world.entity_from_id(entity).remove::<T>();
+            }
+        });
+    }
+
+    /// Records deleting an entity.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity` - The entity to delete.
+    pub fn despawn(&self, entity: impl Into<Entity>) {
+        self.record_for(entity.into(), move |world, entity| {
+            if world.is_alive(entity) {
+                world.entity_from_id(entity).destruct();
+            }
+        });
+    }
+
+    /// Records a custom command, which is invoked with the world when the buffer is applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The closure to run on the main thread.
+    pub fn push(&self, command: impl FnOnce(&World) + Send + 'static) {
+        self.record(move |world, _| command(world));
+    }
+
+    fn record(&self, command: impl FnOnce(&World, &Created) + Send + 'static) {
+        self.queue
+            .state
+            .lock()
+            .unwrap()
+            .commands
+            .push(Box::new(command));
+    }
+
+    /// Records a command for `entity`, which is passed the entity created for a deferred id.
+    fn record_for(&self, entity: Entity, command: impl FnOnce(&World, Entity) + Send + 'static) {
+        let mut state = self.queue.state.lock().unwrap();
+        // deferred ids of earlier sync points are resolved now, those of the current sync
+        // point when the command is applied
+        let entity = state.resolve(entity);
+        state.commands.push(Box::new(move |world, created| {
+            command(world, created.resolve(entity))
+        }));
+    }
+
+    /// Returns the number of recorded commands that have not been applied yet.
+    pub fn len(&self) -> usize {
+        self.queue.state.lock().unwrap().commands.len()
+    }
+
+    /// Returns `true` if there are no recorded commands waiting to be applied.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of reserved entity ids that can still be spawned before the next
+    /// sync point.
+    pub fn reserved_count(&self) -> usize {
+        self.queue.state.lock().unwrap().ids.len()
+    }
+}
+
+impl World {
+    /// Creates a new [`Commands`] buffer for this world.
+    ///
+    /// The buffer reserves a small number of entity ids up front. The ids reserved at every
+    /// sync point grow to the number of ids spawned from other threads between two sync points.
+    ///
+    /// # See also
+    ///
+    /// * [`World::commands_with_capacity()`]
+    /// * [`World::apply()`]
+    pub fn commands(&self) -> Commands {
+        self.commands_with_capacity(DEFAULT_RESERVE)
+    }
+
+    /// Creates a new [`Commands`] buffer for this world, which keeps at least `capacity` entity
+    /// ids reserved for [`Commands::spawn()`] between two sync points.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The number of entity ids to keep reserved.
+    ///
+    /// # See also
+    ///
+    /// * [`World::commands()`]
+    /// * [`World::apply()`]
+    pub fn commands_with_capacity(&self, capacity: usize) -> Commands {
+        let queue = Arc::new(CommandQueue::new(self, capacity));
+        queue.refill(self);
+        self.world_ctx_mut().command_queues.push(queue.clone());
+        self.init_commands_system();
+        Commands { queue }
+    }
+
+    /// Creates the system that applies the command buffers of the world at the end of every
+    /// frame, if it doesn't exist yet.
+    fn init_commands_system(&self) {
+        #[cfg(feature = "flecs_pipeline")]
+        if !self.world_ctx().commands_system && !self.is_readonly() {
+            self.world_ctx_mut().commands_system = true;
+            self.system::<()>()
+                .kind_id(ECS_POST_FRAME)
+                .run(|it| unsafe {
+                    sys::ecs_run_post_frame(
+                        it.world().world_ptr_mut(),
+                        Some(apply_commands_action),
+                        std::ptr::null_mut(),
+                    );
+                });
+        }
+    }
+
+    /// Applies the commands recorded in a [`Commands`] buffer.
+    ///
+    /// Commands are applied in the order they were recorded, after which the reserved entity
+    /// ids of the buffer are topped up.
+    ///
+    /// # Panics
+    ///
+    /// The world must not be in readonly mode.
+    ///
+    /// # See also
+    ///
+    /// * [`World::commands()`]
+    pub fn apply(&self, commands: &Commands) {
+        ecs_assert!(
+            !self.is_readonly(),
+            FlecsErrorCode::InvalidOperation,
+            "cannot apply commands while the world is in readonly mode"
+        );
+
+        self.run_commands(&commands.queue);
+        commands.queue.refill(self);
+        self.init_commands_system();
+    }
+
+    fn run_commands(&self, queue: &CommandQueue) {
+        // commands recorded while applying are applied in the next sync point
+        let (commands, created) = queue.take_commands(self);
+        for command in commands {
+            command(self, &created);
+        }
+    }
+
+    /// Applies the commands of all command buffers created for this world, and drops the
+    /// buffers of which no handle is left.
+    pub(crate) fn apply_commands(&self) {
+        if self.world_ctx().command_queues.is_empty() || self.is_readonly() {
+            return;
+        }
+
+        let queues = std::mem::take(&mut self.world_ctx_mut().command_queues);
+        let mut alive = Vec::with_capacity(queues.len());
+        for queue in queues {
+            self.run_commands(&queue);
+            if Arc::strong_count(&queue) > 1 {
+                queue.refill(self);
+                alive.push(queue);
+            } else {
+                queue.release(self);
+            }
+        }
+
+        // command buffers created while applying were added to the world in the meantime
+        let ctx = self.world_ctx_mut();
+        alive.append(&mut ctx.command_queues);
+        ctx.command_queues = alive;
+    }
+}
@@ -77,8 +77,19 @@ pub fn register_copy_lifecycle_action<T: Clone>(type_hooks: &mut sys::ecs_type_h
 }
 
 pub fn register_copy_panic_lifecycle_action<T>(type_hooks: &mut sys::ecs_type_hooks_t) {
-    type_hooks.copy = Some(panic_copy::<T>);
-    type_hooks.copy_ctor = Some(panic_copy::<T>); //same implementation as copy
+    type_hooks.copy = Some(panic_copy);
+    type_hooks.copy_ctor = Some(panic_copy); //same implementation as copy
+}
+
+/// Returns whether the copy hook of a type is the one registered for types that don't
+/// implement `Clone`, in which case copying a value panics.
+pub(crate) fn is_copy_panic(type_hooks: &sys::ecs_type_hooks_t) -> bool {
+    type_hooks.copy.is_some_and(|copy| {
+        std::ptr::fn_addr_eq(
+            copy,
+            panic_copy as unsafe extern "C" fn(*mut c_void, *const c_void, i32, *const _),
+        )
+    })
 }
 
 /// Initialize the memory with the default constructor.
@@ -205,13 +216,15 @@ extern "C" fn panic_ctor<T>(
     panic!("Default is not implemented for type {} which requires drop and it's being used in an operation which calls the constructor", std::any::type_name::<T>());
 }
 
-extern "C" fn panic_copy<T>(
+// not generic, so `is_copy_panic` can recognize it by its address
+extern "C" fn panic_copy(
     _dst_ptr: *mut c_void,
     _src_ptr: *const c_void,
     _count: i32,
-    _type_info: *const sys::ecs_type_info_t,
+    type_info: *const sys::ecs_type_info_t,
 ) {
-    panic!("Clone is not implemented for type {} and it's being used in a copy / duplicate operation such as component overriding or duplicating entities / components", std::any::type_name::<T>());
+    let component = unsafe { type_info.as_ref() }.map_or(0, |type_info| type_info.component);
+    panic!("Clone is not implemented for component {} and it's being used in a copy / duplicate operation such as component overriding or duplicating entities / components", component);
 }
 
 /// This is the generic move for non-trivial types
@@ -0,0 +1,224 @@
+//! Typed fields for `Or` terms.
+
+use crate::core::*;
+use crate::sys;
+
+/// A query field that matches one of two components, which adds an `Or` term to the query.
+///
+/// `Either<&Circle, &Rect>` matches entities that have `Circle` or `Rect`. For every matched
+/// table the field resolves to the component that the table has, as [`Either::Left`] or
+/// [`Either::Right`]. Alternatives can be nested to match more components, such as
+/// `Either<&Circle, Either<&Rect, &Triangle>>`. When a table has more than one of the
+/// components, the first one is matched.
+///
+/// The components must have data, and can't be wrapped in an `Option`.
+///
+/// # Example
+///
+/// ```
+/// use flecs_ecs::prelude::*;
+///
+/// #[derive(Component)]
+/// struct Circle {
+///     radius: f32,
+/// }
+///
+/// #[derive(Component)]
+/// struct Rect {
+///     width: f32,
+///     height: f32,
+/// }
+///
+/// let world = World::new();
+///
+/// world.entity().set(Circle { radius: 1.0 });
+/// world.entity().set(Rect {
+///     width: 2.0,
+///     height: 3.0,
+/// });
+///
+/// let mut area = 0.0;
+/// world
+///     .new_query::<Either<&Circle, &Rect>>()
+///     .each(|shape| match shape {
+///         Either::Left(circle) => area += 3.0 * circle.radius * circle.radius,
+///         Either::Right(rect) => area += rect.width * rect.height,
+///     });
+/// assert_eq!(area, 9.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Either<L, R> {
+    /// The first alternative was matched.
+    Left(L),
+    /// The second alternative was matched.
+    Right(R),
+}
+
+impl<L, R> Either<L, R> {
+    /// Returns `true` if the first alternative was matched.
+    pub fn is_left(&self) -> bool {
+        matches!(self, Either::Left(_))
+    }
+
+    /// Returns `true` if the second alternative was matched.
+    pub fn is_right(&self) -> bool {
+        matches!(self, Either::Right(_))
+    }
+
+    /// Returns the first alternative, or `None` if the second was matched.
+    pub fn left(self) -> Option<L> {
+        match self {
+            Either::Left(left) => Some(left),
+            Either::Right(_) => None,
+        }
+    }
+
+    /// Returns the second alternative, or `None` if the first was matched.
+    pub fn right(self) -> Option<R> {
+        match self {
+            Either::Left(_) => None,
+            Either::Right(right) => Some(right),
+        }
+    }
+}
+
+impl<L, R> IterableTypeOperation for Either<L, R>
+where
+    L: IterableTypeOperation,
+    R: IterableTypeOperation,
+{
+    type CastType = *mut u8;
+    type ActualType<'w> = Either<L::ActualType<'w>, R::ActualType<'w>>;
+    type SliceType<'w> = Either<L::SliceType<'w>, R::SliceType<'w>>;
+    type OnlyType = L::OnlyType;
+    type OnlyPairType = L::OnlyPairType;
+    const TERM_COUNT: i32 = L::TERM_COUNT + R::TERM_COUNT;
+    const VARIANT_COUNT: u8 = L::VARIANT_COUNT + R::VARIANT_COUNT;
+
+    fn populate_term(_term: &mut sys::ecs_term_t) {
+        // the terms of the alternatives are populated by `populate_terms`
+    }
+
+    fn populate_terms<'a>(query: &mut impl QueryBuilderImpl<'a>) {
+        L::populate_terms(query);
+        query.current_term_mut().oper = OperKind::Or as i16;
+        R::populate_terms(query);
+    }
+
+    fn register_terms_at(world: WorldRef, terms: &mut [sys::ecs_term_t], index: &mut usize) {
+        L::register_terms_at(world, terms, index);
+        terms[*index - 1].oper = OperKind::Or as i16;
+        R::register_terms_at(world, terms, index);
+    }
+
+    fn variant_of(world: WorldRef, id: sys::ecs_id_t) -> Option<u8> {
+        L::variant_of(world, id)
+            .or_else(|| R::variant_of(world, id).map(|variant| variant + L::VARIANT_COUNT))
+    }
+
+    fn field_data(it: &sys::ecs_iter_t, index: i32) -> (*mut u8, u8) {
+        let id = unsafe { *it.ids.add(index as usize) };
+        let src = if it.sources.is_null() {
+            0
+        } else {
+            unsafe { *it.sources.add(index as usize) }
+        };
+
+        // the iterator doesn't provide data for `Or` fields, so it's looked up on the source
+        let data = unsafe {
+            if src != 0 {
+                sys::ecs_get_mut_id(it.real_world, src, id)
+            } else if !it.table.is_null() {
+                sys::ecs_table_get_id(it.real_world, it.table, id, it.offset)
+            } else {
+                std::ptr::null_mut()
+            }
+        } as *mut u8;
+
+        let world = unsafe { WorldRef::from_ptr(it.real_world) };
+        let variant = Self::variant_of(world, id).unwrap_or(0);
+        (data, variant)
+    }
+
+    fn create_tuple_data<'a>(
+        array_components_data: *mut u8,
+        variant: u8,
+        index: usize,
+    ) -> Self::ActualType<'a> {
+        if variant < L::VARIANT_COUNT {
+            Either::Left(L::create_tuple_data(array_components_data, variant, index))
+        } else {
+            Either::Right(R::create_tuple_data(
+                array_components_data,
+                variant - L::VARIANT_COUNT,
+                index,
+            ))
+        }
+    }
+
+    fn create_tuple_with_ref_data<'a>(
+        array_components_data: *mut u8,
+        is_ref: bool,
+        variant: u8,
+        index: usize,
+    ) -> Self::ActualType<'a> {
+        if variant < L::VARIANT_COUNT {
+            Either::Left(L::create_tuple_with_ref_data(
+                array_components_data,
+                is_ref,
+                variant,
+                index,
+            ))
+        } else {
+            Either::Right(R::create_tuple_with_ref_data(
+                array_components_data,
+                is_ref,
+                variant - L::VARIANT_COUNT,
+                index,
+            ))
+        }
+    }
+
+    fn create_tuple_slice_data<'a>(
+        array_components_data: *mut u8,
+        variant: u8,
+        count: usize,
+    ) -> Self::SliceType<'a> {
+        if variant < L::VARIANT_COUNT {
+            Either::Left(L::create_tuple_slice_data(
+                array_components_data,
+                variant,
+                count,
+            ))
+        } else {
+            Either::Right(R::create_tuple_slice_data(
+                array_components_data,
+                variant - L::VARIANT_COUNT,
+                count,
+            ))
+        }
+    }
+
+    fn create_tuple_slices_with_ref_data<'a>(
+        array_components_data: *mut u8,
+        is_ref_array_components: bool,
+        variant: u8,
+        count: usize,
+    ) -> Self::SliceType<'a> {
+        if variant < L::VARIANT_COUNT {
+            Either::Left(L::create_tuple_slices_with_ref_data(
+                array_components_data,
+                is_ref_array_components,
+                variant,
+                count,
+            ))
+        } else {
+            Either::Right(R::create_tuple_slices_with_ref_data(
+                array_components_data,
+                is_ref_array_components,
+                variant - L::VARIANT_COUNT,
+                count,
+            ))
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! Generation-checked weak entity references and ref-counted strong entity handles.
+
+use std::sync::{Arc, Weak};
+
+use crate::core::*;
+use crate::sys;
+
+/// A weak reference to an entity that detects when the entity was deleted.
+///
+/// Unlike [`Entity`], which is an id that may be recycled for a different entity after the
+/// original entity is deleted, a `WeakEntity` stores the generation of the entity separately
+/// from its id. [`WeakEntity::upgrade()`] only returns the entity when the generation still matches,
+/// so a stale reference never resolves to a recycled id.
+///
+/// A `WeakEntity` is a plain value. With the `serde` feature enabled it can be serialized, which
+/// keeps references stable in saved games as long as the entity ids are restored as well.
+///
+/// # Example
+///
+/// ```
+/// use flecs_ecs::prelude::*;
+///
+/// let world = World::new();
+///
+/// let entity = world.entity();
+/// let weak = entity.downgrade();
+///
+/// assert_eq!(weak.upgrade(&world), Some(entity));
+///
+/// entity.destruct();
+/// // recycles the id of the deleted entity
+/// let recycled = world.entity();
+///
+/// assert_eq!(strip_generation(recycled), strip_generation(weak.id()));
+/// assert!(weak.upgrade(&world).is_none());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WeakEntity {
+    index: u64,
+    generation: u32,
+}
+
+impl WeakEntity {
+    /// Creates a weak reference from an entity id, which includes the generation.
+    pub fn new(entity: impl Into<Entity>) -> Self {
+        let entity = entity.into();
+        Self {
+            index: strip_generation(entity),
+            generation: get_generation(entity),
+        }
+    }
+
+    /// Returns the entity id, including the generation.
+    pub fn id(&self) -> Entity {
+        Entity::from(self.index | ((self.generation as u64) << 32))
+    }
+
+    /// Returns the generation of the referenced entity.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Returns the entity if it is still alive in `world`.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the entity was deleted, even when its id was recycled for a new entity.
+    pub fn upgrade<'a>(&self, world: impl WorldProvider<'a>) -> Option<EntityView<'a>> {
+        let id = self.id();
+        if unsafe { sys::ecs_is_alive(world.world_ptr(), *id) } {
+            Some(EntityView::new_from(world, id))
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if the entity is still alive in `world`.
+    pub fn is_alive<'a>(&self, world: impl WorldProvider<'a>) -> bool {
+        self.upgrade(world).is_some()
+    }
+}
+
+impl From<WeakEntity> for Entity {
+    fn from(weak: WeakEntity) -> Self {
+        weak.id()
+    }
+}
+
+struct EntityHandleInner {
+    entity: Entity,
+    commands: Commands,
+}
+
+impl Drop for EntityHandleInner {
+    fn drop(&mut self) {
+        self.commands.despawn(self.entity);
+    }
+}
+
+/// Tracks the handles of an entity, so all handles created for it share one reference count.
+#[derive(flecs_ecs_derive::Component, Default)]
+struct EntityHandleRef(Weak<EntityHandleInner>);
+
+/// Copies of an entity, such as duplicates and prefab instances, don't share the handles of
+/// the entity. The copy gets its own handles from [`EntityView::handle()`].
+impl Clone for EntityHandleRef {
+    fn clone(&self) -> Self {
+        Self(Weak::new())
+    }
+}
+
+/// A ref-counted handle that keeps an entity alive.
+///
+/// All handles for an entity share one reference count, also when they were created separately
+/// with [`EntityView::handle()`]. When the last handle is dropped the entity is deleted. As handles
+/// may be dropped on any thread, the deletion is recorded as a [`Commands`] and happens at the
+/// next sync point, see [`Commands`].
+///
+/// Handles are `Send` and `Sync`, so they can be stored in components.
+///
+/// # Example
+///
+/// ```
+/// use flecs_ecs::prelude::*;
+///
+/// let world = World::new();
+///
+/// let entity = world.entity();
+/// let handle = entity.handle();
+/// let handle2 = handle.clone();
+///
+/// drop(handle);
+/// world.progress();
+/// assert!(entity.is_alive());
+///
+/// drop(handle2);
+/// world.progress();
+/// assert!(!entity.is_alive());
+/// ```
+#[derive(Clone)]
+pub struct EntityHandle {
+    inner: Arc<EntityHandleInner>,
+}
+
+impl EntityHandle {
+    /// Returns the id of the entity the handle keeps alive.
+    pub fn id(&self) -> Entity {
+        self.inner.entity
+    }
+
+    /// Returns a weak reference to the entity.
+    pub fn downgrade(&self) -> WeakEntity {
+        WeakEntity::new(self.inner.entity)
+    }
+
+    /// Returns the entity for `world`.
+    ///
+    /// # Arguments
+    ///
+    /// * `world` - The world the entity belongs to.
+    pub fn entity_view<'a>(&self, world: impl WorldProvider<'a>) -> EntityView<'a> {
+        EntityView::new_from(world, self.inner.entity)
+    }
+
+    /// Returns the number of handles that keep the entity alive.
+    pub fn handle_count(&self) -> usize {
+        Arc::strong_count(&self.inner)
+    }
+}
+
+impl PartialEq for EntityHandle {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner.entity == other.inner.entity
+    }
+}
+
+impl Eq for EntityHandle {}
+
+impl std::fmt::Debug for EntityHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EntityHandle")
+            .field("entity", &self.inner.entity)
+            .finish()
+    }
+}
+
+impl<'a> EntityView<'a> {
+    /// Returns a weak reference to the entity, which detects when the entity was deleted.
+    ///
+    /// # See also
+    ///
+    /// * [`WeakEntity`]
+    pub fn downgrade(self) -> WeakEntity {
+        WeakEntity::new(self.id)
+    }
+
+    /// Returns a ref-counted handle that keeps the entity alive.
+    ///
+    /// When the entity already has handles, the returned handle shares their reference count.
+    ///
+    /// # See also
+    ///
+    /// * [`EntityHandle`]
+    pub fn handle(self) -> EntityHandle {
+        if let Some(inner) =
+            self.try_map::<&EntityHandleRef, _>(|handle_ref| handle_ref.0.upgrade())
+        {
+            return EntityHandle { inner };
+        }
+
+        let world = self.world.real_world();
+        let commands = match world.world_ctx().handle_commands.clone() {
+            Some(commands) => commands,
+            None => {
+                let commands = world.commands_with_capacity(0);
+                world.world_ctx_mut().handle_commands = Some(commands.clone());
+                commands
+            }
+        };
+
+        let inner = Arc::new(EntityHandleInner {
+            entity: self.id,
+            commands,
+        });
+        self.set(EntityHandleRef(Arc::downgrade(&inner)));
+        EntityHandle { inner }
+    }
+}
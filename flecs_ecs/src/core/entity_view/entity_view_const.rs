@@ -2010,6 +2010,210 @@ impl<'a> EntityView<'a> {
         dest_entity
     }
 
+    /// Clones the current entity to a new entity, only copying the ids accepted by `filter`.
+    ///
+    /// This behaves like [`EntityView::duplicate`], except that every id of the source
+    /// entity is passed to `filter` first. Only ids for which `filter` returns `true`
+    /// are added to the clone. Like [`EntityView::duplicate`], the name of the entity is not copied.
+    /// When `copy_value` is `true`, components that can't be copied, as described for
+    /// [`EntityView::copy_to`], are skipped.
+    ///
+    /// # Arguments
+    /// - `copy_value`: A boolean indicating whether to copy the component values to the destination entity.
+    /// - `filter`: The closure deciding for each id whether it is cloned. Must match the signature `FnMut(IdView) -> bool`.
+    ///
+    /// # Returns
+    /// - The newly created entity.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component, Clone)]
+    /// struct Position {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    ///
+    /// #[derive(Component)]
+    /// struct Selected;
+    ///
+    /// let world = World::new();
+    ///
+    /// let entity = world
+    ///     .entity()
+    ///     .set(Position { x: 1.0, y: 2.0 })
+    ///     .add::<Selected>();
+    ///
+    /// let selected_id = world.component_id::<Selected>();
+    /// let clone = entity.duplicate_filtered(true, |id| id != selected_id);
+    ///
+    /// assert!(clone.has::<Position>());
+    /// assert!(!clone.has::<Selected>());
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`EntityView::duplicate()`]
+    pub fn duplicate_filtered(
+        self,
+        copy_value: bool,
+        mut filter: impl FnMut(IdView) -> bool,
+    ) -> EntityView<'a> {
+        let world = self.world.world_ptr_mut();
+        let name_id = ecs_pair(flecs::Identifier::ID, flecs::Name::ID);
+        let ids = self
+            .archetype()
+            .as_slice()
+            .iter()
+            .map(|id| **id)
+            .filter(|&id| id != name_id && filter(IdView::new_from(self.world, id)))
+            .filter(|&id| !copy_value || can_copy_value(world, id))
+            .collect::<Vec<_>>();
+
+        let dest_entity = EntityView::new(self.world());
+
+        // Add all ids before copying any values, so the destination entity no longer
+        // moves between tables while values are read from the source entity.
+        for &id in &ids {
+            unsafe { sys::ecs_add_id(world, *dest_entity.id, id) };
+        }
+
+        if copy_value {
+            for &id in &ids {
+                let type_info = unsafe { sys::ecs_get_type_info(world, id) };
+                if type_info.is_null() || unsafe { (*type_info).size } == 0 {
+                    continue;
+                }
+                unsafe {
+                    let src = sys::ecs_get_id(world, *self.id, id);
+                    sys::ecs_set_id(world, *dest_entity.id, id, (*type_info).size as usize, src);
+                }
+            }
+        }
+
+        dest_entity
+    }
+
+    /// Copies the current entity and its children into another world.
+    ///
+    /// Ids are remapped by the symbol or path of the entities they consist of, so
+    /// components registered from the same Rust type in both worlds are matched, even
+    /// when they have a different id. Components that are not yet registered in the
+    /// destination world are registered with the same name, symbol, size and lifecycle
+    /// hooks as in the source world. Ids that cannot be resolved in the destination
+    /// world, such as pairs with anonymous targets, are skipped.
+    ///
+    /// Component values are copied with `Clone` for components that implement it, and
+    /// bitwise for components without a copy hook that don't need to be dropped. Other
+    /// components that don't implement `Clone` can't be copied, and are skipped. Entity ids
+    /// stored inside of component values are copied as is and are not remapped.
+    ///
+    /// Children (`ChildOf`) of the entity are copied recursively. The copy of the entity
+    /// itself is created in the root of the destination world.
+    ///
+    /// # Arguments
+    /// - `world`: The world to copy the entity into.
+    ///
+    /// # Returns
+    /// - The copy of the entity in the destination world.
+    ///
+    /// # Panics
+    /// - When an entity with the same name already exists in the root of the destination world.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component, Clone)]
+    /// struct Position {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    ///
+    /// let editor = World::new();
+    /// let runtime = World::new();
+    ///
+    /// let ship = editor.entity_named("Ship").set(Position { x: 1.0, y: 2.0 });
+    /// editor.entity_named("Engine").child_of_id(ship);
+    ///
+    /// let copy = ship.copy_to(&runtime);
+    ///
+    /// assert_eq!(copy.name(), "Ship");
+    /// copy.get::<&Position>(|pos| {
+    ///     assert_eq!(pos.x, 1.0);
+    /// });
+    /// assert!(runtime.try_lookup("Ship::Engine").is_some());
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`EntityView::duplicate()`]
+    pub fn copy_to<'w>(self, world: impl WorldProvider<'w>) -> EntityView<'w> {
+        self.copy_to_parent(world.world(), 0)
+    }
+
+    fn copy_to_parent<'w>(self, dst: WorldRef<'w>, parent: sys::ecs_entity_t) -> EntityView<'w> {
+        let src_world = self.world.world_ptr_mut();
+        let dst_world = dst.world_ptr_mut();
+
+        let dest_entity = unsafe { sys::ecs_new(dst_world) };
+        if parent != 0 {
+            unsafe {
+                sys::ecs_add_id(dst_world, dest_entity, ecs_pair(flecs::ChildOf::ID, parent));
+            };
+        }
+        if let Some(name) = self.get_name_cstr() {
+            unsafe { sys::ecs_set_name(dst_world, dest_entity, name.as_ptr()) };
+        }
+
+        let mut values = Vec::new();
+        for &id in self.archetype().as_slice() {
+            let id = *id;
+            if ecs_is_pair(id)
+                && (*ecs_first(id) == flecs::Identifier::ID || *ecs_first(id) == flecs::ChildOf::ID)
+            {
+                // names and hierarchy are reconstructed for the destination world.
+                continue;
+            }
+
+            if !can_copy_value(src_world, id) {
+                continue;
+            }
+
+            let Some(dst_id) = remap_id(self.world, dst, id) else {
+                continue;
+            };
+
+            unsafe { sys::ecs_add_id(dst_world, dest_entity, dst_id) };
+            values.push((id, dst_id));
+        }
+
+        for (src_id, dst_id) in values {
+            let src_info = unsafe { sys::ecs_get_type_info(src_world, src_id) };
+            let dst_info = unsafe { sys::ecs_get_type_info(dst_world, dst_id) };
+            if src_info.is_null() || dst_info.is_null() {
+                continue;
+            }
+            let size = unsafe { (*src_info).size };
+            if size == 0 || size != unsafe { (*dst_info).size } {
+                continue;
+            }
+            unsafe {
+                let src = sys::ecs_get_id(src_world, *self.id, src_id);
+                sys::ecs_set_id(dst_world, dest_entity, dst_id, size as usize, src);
+            }
+        }
+
+        self.each_child(|child| {
+            child.copy_to_parent(dst, dest_entity);
+        });
+
+        EntityView::new_from(dst, dest_entity)
+    }
+
     /// Returns a mutable entity handle for the current stage.
     ///
     /// When an entity handle created from the world is used while the world is
@@ -2667,3 +2871,118 @@ impl<'a> EntityView<'a> {
         }
     }
 }
+
+/// Returns whether the value of `id` can be copied, which is not the case for components that
+/// don't implement `Clone`, as copying them panics or drops the value twice.
+fn can_copy_value(world: *mut sys::ecs_world_t, id: u64) -> bool {
+    let type_info = unsafe { sys::ecs_get_type_info(world, id) };
+    if type_info.is_null() {
+        return true;
+    }
+    let hooks = unsafe { &(*type_info).hooks };
+    !is_copy_panic(hooks) && !(hooks.dtor.is_some() && hooks.copy.is_none())
+}
+
+/// Resolves an id of the `src` world to the id in the `dst` world that refers to the same
+/// entities, preserving pair and id flags.
+fn remap_id(src: WorldRef, dst: WorldRef, id: u64) -> Option<u64> {
+    let flags = id & RUST_ecs_id_FLAGS_MASK;
+    if flags & ECS_PAIR != 0 {
+        let first = remap_entity(src, dst, *ecs_first(id))?;
+        let second = remap_entity(src, dst, *ecs_second(id))?;
+        Some(flags | (ecs_pair(first, second) & RUST_ECS_COMPONENT_MASK))
+    } else {
+        remap_entity(src, dst, id & RUST_ECS_COMPONENT_MASK).map(|entity| flags | entity)
+    }
+}
+
+/// Resolves an entity of the `src` world by symbol or path in the `dst` world.
+///
+/// Components that don't exist yet in the `dst` world are registered with the type info
+/// of the `src` world, which is how the Rust API registers them as well.
+fn remap_entity(src: WorldRef, dst: WorldRef, entity: u64) -> Option<u64> {
+    let src_world = src.world_ptr_mut();
+    let dst_world = dst.world_ptr_mut();
+
+    let entity = unsafe { sys::ecs_get_alive(src_world, entity) };
+    if entity == 0 {
+        return None;
+    }
+
+    let symbol = unsafe { sys::ecs_get_symbol(src_world, entity) };
+    if !symbol.is_null() {
+        let id = unsafe { sys::ecs_lookup_symbol(dst_world, symbol, false, false) };
+        if id != 0 {
+            return Some(id);
+        }
+    }
+
+    // anonymous entities can't be matched between worlds
+    let entity_view = EntityView::new_from(src, entity);
+    entity_view.get_name_cstr()?;
+    let path = entity_view.path_w_sep("::", "")?;
+    let path = compact_str::format_compact!("{}\0", path);
+
+    let id = unsafe {
+        sys::ecs_lookup_path_w_sep(
+            dst_world,
+            0,
+            path.as_ptr() as *const _,
+            SEPARATOR.as_ptr(),
+            ptr::null(),
+            false,
+        )
+    };
+    if id != 0 {
+        return Some(id);
+    }
+
+    if symbol.is_null() || !unsafe { sys::ecs_has_id(src_world, entity, flecs::Component::ID) } {
+        return None;
+    }
+
+    // tags have no type info, in which case an empty one is registered
+    let type_info = unsafe { sys::ecs_get_type_info(src_world, entity) };
+    let type_info = if type_info.is_null() {
+        sys::ecs_type_info_t {
+            size: 0,
+            alignment: 0,
+            hooks: Default::default(),
+            component: 0,
+            name: ptr::null(),
+        }
+    } else {
+        // hooks that carry context are bound to the source world and are not copied
+        let type_info = unsafe { *type_info };
+        sys::ecs_type_info_t {
+            hooks: sys::ecs_type_hooks_t {
+                on_add: None,
+                on_set: None,
+                on_remove: None,
+                ctx: ptr::null_mut(),
+                binding_ctx: ptr::null_mut(),
+                ctx_free: None,
+                binding_ctx_free: None,
+                ..type_info.hooks
+            },
+            component: 0,
+            name: ptr::null(),
+            ..type_info
+        }
+    };
+
+    let prev_scope = unsafe { sys::ecs_set_scope(dst_world, 0) };
+    let prev_with = unsafe { sys::ecs_set_with(dst_world, 0) };
+
+    let entity_desc = create_entity_desc(path.as_ptr() as *const _, symbol);
+    let id = unsafe { sys::ecs_entity_init(dst_world, &entity_desc) };
+    let component_desc = create_component_desc(id, type_info);
+    let id = unsafe { sys::ecs_component_init(dst_world, &component_desc) };
+
+    unsafe {
+        sys::ecs_set_with(dst_world, prev_with);
+        sys::ecs_set_scope(dst_world, prev_scope);
+    }
+
+    Some(id)
+}
@@ -1407,7 +1407,9 @@ impl<'a> EntityView<'a> {
     /// * C++ API: `entity::modified`
     #[doc(alias = "entity::modified")]
     pub fn modified_id(self, id: impl IntoId) {
-        unsafe { sys::ecs_modified_id(self.world.world_ptr_mut(), *self.id, *id.into()) }
+        let id = *id.into();
+        mark_row_changed(self.world.world_ptr(), *self.id, id);
+        unsafe { sys::ecs_modified_id(self.world.world_ptr_mut(), *self.id, id) }
     }
 
     /// Signal that component was modified.
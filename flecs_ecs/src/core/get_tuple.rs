@@ -218,7 +218,11 @@ where
         } else if A::IS_IMMUTABLE { 
             unsafe { sys::ecs_rust_get_id(world_ptr, entity, record,table,id) }
          } else {
-           unsafe { sys::ecs_rust_mut_get_id(world_ptr, entity, record,table,id)}
+           let ptr = unsafe { sys::ecs_rust_mut_get_id(world_ptr, entity, record,table,id)};
+           if !ptr.is_null() {
+               mark_row_changed(world_ptr, entity, id);
+           }
+           ptr
          };
          
         
@@ -349,7 +353,11 @@ macro_rules! impl_get_tuple {
                     } else if $t::IS_IMMUTABLE {
                         unsafe { sys::ecs_rust_get_id(world_ptr, entity, record,table,id) }
                      } else {
-                       unsafe { sys::ecs_rust_mut_get_id(world_ptr, entity, record,table,id)}
+                       let ptr = unsafe { sys::ecs_rust_mut_get_id(world_ptr, entity, record,table,id)};
+                       if !ptr.is_null() {
+                           mark_row_changed(world_ptr, entity, id);
+                       }
+                       ptr
                      };
 
 
@@ -0,0 +1,302 @@
+//! Prefab instantiation with overrides, slot lookup and a map of instantiated children.
+
+use std::collections::HashMap;
+
+use crate::core::*;
+use crate::sys;
+
+type Override<'a> = Box<dyn FnOnce(EntityView<'a>) + 'a>;
+
+/// Builder for creating an instance of a prefab.
+///
+/// Created with [`EntityView::instantiate()`]. The instance is created when [`InstanceBuilder::build()`]
+/// is called: the entity is created with its name and parent, the `(IsA, prefab)` pair is added,
+/// which instantiates the children of the prefab, after which the overrides are set.
+///
+/// # Example
+///
+/// ```
+/// use flecs_ecs::prelude::*;
+///
+/// #[derive(Component, Debug, PartialEq)]
+/// struct Position {
+///     x: f32,
+///     y: f32,
+/// }
+///
+/// let world = World::new();
+///
+/// let spaceship = world.prefab_named("SpaceShip").set(Position { x: 0.0, y: 0.0 });
+/// world.prefab_named("Turret").child_of_id(spaceship).slot();
+///
+/// let fleet = world.entity_named("Fleet");
+/// let instance = spaceship
+///     .instantiate()
+///     .named("MyShip")
+///     .child_of_id(fleet)
+///     .override_with(Position { x: 10.0, y: 20.0 })
+///     .build();
+///
+/// assert_eq!(instance.path().unwrap(), "::Fleet::MyShip");
+/// assert!(instance.owns::<Position>());
+///
+/// let turret = instance.slot("Turret");
+/// assert!(turret.has_id((flecs::ChildOf::ID, instance.id())));
+/// ```
+///
+/// # See also
+///
+/// * [`EntityView::instantiate()`]
+/// * [`Instance`]
+pub struct InstanceBuilder<'a> {
+    prefab: EntityView<'a>,
+    name: Option<String>,
+    parent: Option<Entity>,
+    overrides: Vec<Override<'a>>,
+}
+
+impl<'a> InstanceBuilder<'a> {
+    pub(crate) fn new(prefab: EntityView<'a>) -> Self {
+        Self {
+            prefab,
+            name: None,
+            parent: None,
+            overrides: Vec::new(),
+        }
+    }
+
+    /// Sets the name of the instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the instance.
+    pub fn named(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Creates the instance as a child of `parent`.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent` - The parent of the instance.
+    pub fn child_of_id(mut self, parent: impl Into<Entity>) -> Self {
+        self.parent = Some(parent.into());
+        self
+    }
+
+    /// Creates the instance as a child of the entity associated with `T`.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `T` - The type associated with the parent entity.
+    pub fn child_of<T: ComponentId>(self) -> Self {
+        let parent = T::id(self.prefab.world);
+        self.child_of_id(parent)
+    }
+
+    /// Overrides a component of the prefab with `value`.
+    ///
+    /// The value is set on the instance after it inherited from the prefab, which gives the
+    /// instance its own copy of the component. Components that the prefab does not have are
+    /// added to the instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value of the component for the instance.
+    pub fn override_with<T: ComponentId + DataComponent>(mut self, value: T) -> Self {
+        self.overrides.push(Box::new(move |entity| {
+            entity.set(value);
+        }));
+        self
+    }
+
+    /// Creates the instance.
+    pub fn build(self) -> Instance<'a> {
+        let world = self.prefab.world;
+        let name = self
+            .name
+            .map(|name| compact_str::format_compact!("{}\0", name));
+
+        let desc = sys::ecs_entity_desc_t {
+            _canary: 0,
+            id: 0,
+            parent: self.parent.map_or(0, |parent| *parent),
+            name: name
+                .as_ref()
+                .map_or(std::ptr::null(), |name| name.as_ptr() as *const _),
+            sep: SEPARATOR.as_ptr(),
+            root_sep: SEPARATOR.as_ptr(),
+            symbol: std::ptr::null(),
+            use_low_id: false,
+            add: std::ptr::null(),
+            add_expr: std::ptr::null(),
+            set: std::ptr::null(),
+        };
+        let id = unsafe { sys::ecs_entity_init(world.world_ptr_mut(), &desc) };
+        let entity = EntityView::new_from(world, id).is_a_id(self.prefab);
+
+        for apply_override in self.overrides {
+            apply_override(entity);
+        }
+
+        let mut children = HashMap::new();
+        map_children(self.prefab, entity, &mut children);
+
+        Instance {
+            entity,
+            prefab: self.prefab,
+            children,
+        }
+    }
+}
+
+/// Maps the (named) children of `prefab` to the children of `instance` they were instantiated
+/// to, recursively.
+fn map_children<'a>(
+    prefab: EntityView<'a>,
+    instance: EntityView<'a>,
+    children: &mut HashMap<Entity, EntityView<'a>>,
+) {
+    prefab.each_child(|prefab_child| {
+        let Some(name) = prefab_child.get_name() else {
+            return;
+        };
+        if let Some(instance_child) = instance.try_lookup(name) {
+            let prefab_child = EntityView::new_from(prefab.world, prefab_child.id);
+            let instance_child = EntityView::new_from(instance.world, instance_child.id);
+            children.insert(prefab_child.id, instance_child);
+            map_children(prefab_child, instance_child, children);
+        }
+    });
+}
+
+/// An instance of a prefab, created with [`InstanceBuilder::build()`].
+///
+/// `Instance` dereferences to the [`EntityView`] of the instance. In addition it provides access
+/// to the slots of the instance and to the children that were instantiated from the prefab
+/// hierarchy.
+///
+/// # See also
+///
+/// * [`EntityView::instantiate()`]
+/// * [`InstanceBuilder`]
+#[derive(Clone)]
+pub struct Instance<'a> {
+    entity: EntityView<'a>,
+    prefab: EntityView<'a>,
+    children: HashMap<Entity, EntityView<'a>>,
+}
+
+impl<'a> Instance<'a> {
+    /// Returns the entity of the instance.
+    pub fn entity(&self) -> EntityView<'a> {
+        self.entity
+    }
+
+    /// Returns the prefab the instance was created from.
+    pub fn prefab(&self) -> EntityView<'a> {
+        self.prefab
+    }
+
+    /// Returns the instantiated children of the prefab hierarchy, keyed by the prefab child they
+    /// were instantiated from.
+    ///
+    /// Children are matched by name, which flecs preserves when instantiating, so only named
+    /// prefab children are included.
+    pub fn children(&self) -> &HashMap<Entity, EntityView<'a>> {
+        &self.children
+    }
+
+    /// Returns the child that was instantiated from `prefab_child`.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefab_child` - A (nested) child of the prefab.
+    pub fn child(&self, prefab_child: impl Into<Entity>) -> Option<EntityView<'a>> {
+        self.children.get(&prefab_child.into()).copied()
+    }
+
+    /// Returns the entity of the slot with the name `name`.
+    ///
+    /// Slots are prefab children marked with [`EntityView::slot()`] or [`EntityView::slot_of_id()`].
+    /// The slot is looked up on the instance, so it also finds slots of nested prefab children.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the slot.
+    ///
+    /// # Returns
+    ///
+    /// The entity if found, otherwise `None`.
+    ///
+    /// # See also
+    ///
+    /// * [`Instance::slot()`]
+    pub fn try_slot(&self, name: &str) -> Option<EntityView<'a>> {
+        let world = self.entity.world;
+        let world_ptr = world.world_ptr();
+        let table = unsafe { sys::ecs_get_table(world_ptr, *self.entity.id) };
+        if table.is_null() {
+            return None;
+        }
+
+        let type_ = unsafe { &*sys::ecs_table_get_type(table) };
+        let ids = unsafe { std::slice::from_raw_parts(type_.array, type_.count as usize) };
+        ids.iter()
+            .filter(|&&id| ecs_is_pair(id))
+            .map(|&id| unsafe { sys::ecs_get_alive(world_ptr, *ecs_first(id)) })
+            .find(|&slot| unsafe {
+                sys::ecs_has_id(world_ptr, slot, ecs_pair(ECS_SLOT_OF, ECS_WILDCARD))
+                    && EntityView::new_from(world, slot).get_name() == Some(name)
+            })
+            .and_then(|slot| unsafe {
+                let target = sys::ecs_get_target(world_ptr, *self.entity.id, slot, 0);
+                (target != 0).then(|| EntityView::new_from(world, target))
+            })
+    }
+
+    /// Returns the entity of the slot with the name `name`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the slot.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the instance has no slot with the name `name`.
+    ///
+    /// # See also
+    ///
+    /// * [`Instance::try_slot()`]
+    pub fn slot(&self, name: &str) -> EntityView<'a> {
+        self.try_slot(name)
+            .unwrap_or_else(|| panic!("instance has no slot named `{name}`"))
+    }
+}
+
+impl<'a> std::ops::Deref for Instance<'a> {
+    type Target = EntityView<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.entity
+    }
+}
+
+impl<'a> From<Instance<'a>> for EntityView<'a> {
+    fn from(instance: Instance<'a>) -> Self {
+        instance.entity
+    }
+}
+
+impl<'a> EntityView<'a> {
+    /// Returns a builder that creates an instance of this prefab.
+    ///
+    /// # See also
+    ///
+    /// * [`InstanceBuilder`]
+    /// * [`EntityView::is_a_id()`]
+    pub fn instantiate(self) -> InstanceBuilder<'a> {
+        InstanceBuilder::new(self)
+    }
+}
@@ -2,23 +2,35 @@ mod archetype;
 pub mod builder;
 pub mod c_types;
 pub(crate) mod cloned_tuple;
+mod commands;
 pub mod component_registration;
 mod components;
+mod either;
 mod entity;
+mod entity_handle;
 mod entity_view;
 mod event;
 pub mod flecs;
 pub(crate) mod get_tuple;
 mod id;
 mod id_view;
+mod instance;
 mod observer;
 mod observer_builder;
+mod ordered_children;
 mod query;
 pub mod query_builder;
+mod query_description;
+mod query_error;
 mod query_iter;
+mod query_iterator;
+mod query_par;
 pub(crate) mod query_tuple;
+mod query_var;
+mod row_changes;
 pub mod table;
 pub mod term;
+mod typed_context;
 pub mod utility;
 mod world;
 pub(crate) mod world_ctx;
@@ -29,28 +41,40 @@ pub use builder::*;
 #[doc(hidden)]
 pub use c_types::*;
 pub(crate) use cloned_tuple::*;
+pub(crate) use commands::CommandQueue;
+pub use commands::Commands;
 #[doc(hidden)]
 pub use component_registration::*;
 #[doc(inline)]
 pub use components::*;
+pub use either::Either;
 pub use entity::Entity;
+pub use entity_handle::{EntityHandle, WeakEntity};
 pub use entity_view::EntityView;
 pub use event::EventBuilder;
 pub(crate) use get_tuple::*;
 pub use id::Id;
 pub use id_view::IdView;
+pub use instance::{Instance, InstanceBuilder};
 pub use observer::Observer;
 pub use observer_builder::ObserverBuilder;
 pub use query::Query;
 #[doc(hidden)]
 pub use query_builder::*;
+pub use query_description::{MatchedTable, QueryDescription};
+pub use query_error::QueryParseError;
 pub use query_iter::QueryIter;
+pub(crate) use query_iterator::{has_mutable_shared_field, TableLocks};
+pub use query_iterator::{QueryChunk, QueryChunkIter, QueryEntityIter};
 #[doc(hidden)]
 pub use query_tuple::*;
+pub use query_var::{IntoQueryVar, QueryVar, QueryVarError};
+pub(crate) use row_changes::*;
 #[doc(hidden)]
 pub use table::*;
 #[doc(hidden)]
 pub use term::*;
+pub(crate) use typed_context::*;
 #[doc(hidden)]
 pub use utility::*;
 pub use world::World;
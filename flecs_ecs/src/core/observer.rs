@@ -78,15 +78,46 @@ impl<'a> Observer<'a> {
     /// * C++ API: `observer::ctx`
     #[doc(alias = "observer::ctx")]
     pub fn set_context(&mut self, context: *mut c_void) {
-        let desc: sys::ecs_observer_desc_t = sys::ecs_observer_desc_t {
-            entity: *self.id,
-            ctx: context,
-            ..Default::default()
-        };
+        self.set_context_with_free(context, None);
+    }
 
-        unsafe {
-            sys::ecs_observer_init(self.world.world_ptr_mut(), &desc);
+    /// Set a typed context for the observer, which replaces and drops the previous typed
+    /// context and is dropped when the observer is deleted.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `C` - The type of the context.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The context to set.
+    ///
+    /// # See also
+    ///
+    /// * [`Observer::context_typed()`]
+    /// * [`SystemAPI::set_context_typed()`]
+    /// * C++ API: `observer::ctx`
+    #[doc(alias = "observer::ctx")]
+    pub fn set_context_typed<C: 'static>(&mut self, context: C) {
+        self.set_context_with_free(typed_context_into_raw(context), Some(free_typed_context));
+    }
+
+    /// Replaces the context and its free callback, freeing the previous context.
+    ///
+    /// The fields are set directly, as `ecs_observer_init` keeps the previous free callback when
+    /// no free callback is passed, which would free a raw context as a typed context.
+    fn set_context_with_free(&mut self, context: *mut c_void, ctx_free: sys::ecs_ctx_free_t) {
+        let observer = unsafe {
+            &mut *(sys::ecs_observer_get(self.world.world_ptr(), *self.id)
+                as *mut sys::ecs_observer_t)
+        };
+        if let Some(free) = observer.ctx_free {
+            if !observer.ctx.is_null() && observer.ctx != context {
+                unsafe { free(observer.ctx) };
+            }
         }
+        observer.ctx = context;
+        observer.ctx_free = ctx_free;
     }
 
     /// Get the context for the observer
@@ -99,6 +130,33 @@ impl<'a> Observer<'a> {
         unsafe { (*sys::ecs_observer_get(self.world.world_ptr_mut(), *self.id)).ctx }
     }
 
+    /// Get the typed context for the observer
+    ///
+    /// # Type Parameters
+    ///
+    /// * `C` - The type of the context.
+    ///
+    /// # Panics
+    ///
+    /// Panics when no typed context was set, or when the context is not of type `C`. A context
+    /// set with [`Observer::set_context()`] can only be accessed with [`Observer::context()`].
+    ///
+    /// # See also
+    ///
+    /// * [`Observer::set_context_typed()`]
+    /// * [`SystemAPI::set_context_typed()`]
+    /// * C++ API: `observer::ctx`
+    #[doc(alias = "observer::ctx")]
+    pub fn context_typed<C: 'static>(&self) -> &C {
+        let observer = unsafe { &*sys::ecs_observer_get(self.world.world_ptr(), *self.id) };
+        let context = if is_typed_context(observer.ctx_free) {
+            observer.ctx
+        } else {
+            std::ptr::null_mut()
+        };
+        unsafe { typed_context(context) }
+    }
+
     /// Get the query for the observer
     ///
     /// # See also
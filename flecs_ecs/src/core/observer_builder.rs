@@ -230,13 +230,19 @@ impl<'a, P, T: QueryTuple> internals::QueryConfig<'a> for ObserverBuilder<'a, P,
 
     #[inline(always)]
     fn count_generic_terms(&self) -> i32 {
-        T::COUNT
+        T::TERM_COUNT
     }
 }
 impl<'a, P, T: QueryTuple> TermBuilderImpl<'a> for ObserverBuilder<'a, P, T> {}
 
 impl<'a, P, T: QueryTuple> QueryBuilderImpl<'a> for ObserverBuilder<'a, P, T> {}
 
+impl<P, T: QueryTuple> Drop for ObserverBuilder<'_, P, T> {
+    fn drop(&mut self) {
+        free_unbuilt_group_callbacks(&mut self.desc.query);
+    }
+}
+
 impl<'a, P, T> Builder<'a> for ObserverBuilder<'a, P, T>
 where
     T: QueryTuple,
@@ -250,7 +256,14 @@ where
     /// * C++ API: `node_builder::build`
     #[doc(alias = "node_builder::build")]
     fn build(&mut self) -> Self::BuiltType {
-        let observer = Observer::new(self.world(), self.desc, self.is_instanced);
+        self.assert_term_count();
+        let world = self.world();
+        let (desc, is_instanced) = (self.desc, self.is_instanced);
+        let observer = init_with_group_callbacks(&mut self.desc.query, || {
+            Observer::new(world, desc, is_instanced)
+        });
+        #[cfg(feature = "flecs_system")]
+        crate::addons::system::instrument_if_hooked(self.world(), *observer.id());
         for string_parts in self.term_builder.str_ptrs_to_free.iter() {
             unsafe {
                 String::from_raw_parts(
@@ -0,0 +1,295 @@
+//! Deterministic child order for hierarchies.
+
+use std::ffi::c_void;
+
+use crate::core::*;
+use crate::sys;
+
+/// Stores the order of the children of a parent.
+///
+/// Added to a parent by [`EntityView::enable_ordered_children()`] and kept up to date by an
+/// observer on `(ChildOf, *)`, which appends new children and removes children that are
+/// reparented or deleted.
+#[derive(flecs_ecs_derive::Component, Default)]
+pub(crate) struct OrderedChildren {
+    children: Vec<Entity>,
+}
+
+/// Copies of a parent, such as duplicates and prefab instances, don't have the children of the
+/// parent. Their order starts empty, and the observer appends children as they are added.
+impl Clone for OrderedChildren {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl OrderedChildren {
+    fn position(&self, child: Entity) -> Option<usize> {
+        self.children.iter().position(|&e| e == child)
+    }
+
+    fn remove(&mut self, child: Entity) {
+        self.children.retain(|&e| e != child);
+    }
+
+    fn insert(&mut self, index: usize, child: Entity) {
+        self.remove(child);
+        let index = index.min(self.children.len());
+        self.children.insert(index, child);
+    }
+}
+
+/// Creates the observer that keeps [`OrderedChildren`] consistent, once per world.
+///
+/// Observers can't be created while the world is deferred, such as from systems. Deferring is
+/// suspended while the observer is created, unless the world is readonly, in which case the
+/// observer is created at the end of the frame.
+fn ensure_ordered_children_observer(world: WorldRef) {
+    if world.world_ctx().ordered_children_observer {
+        return;
+    }
+    world.world_ctx_mut().ordered_children_observer = true;
+
+    let real_world = world.real_world();
+    if real_world.is_readonly() {
+        unsafe {
+            sys::ecs_run_post_frame(
+                world.world_ptr_mut(),
+                Some(init_ordered_children_action),
+                std::ptr::null_mut(),
+            );
+        }
+    } else if real_world.is_deferred() {
+        real_world.defer_suspend();
+        init_ordered_children_observer(&real_world);
+        real_world.defer_resume();
+    } else {
+        init_ordered_children_observer(&real_world);
+    }
+}
+
+/// Creates the ordered children observer at the end of a frame, and brings the orders that were
+/// set before it existed up to date with the children that were added or removed since.
+unsafe extern "C" fn init_ordered_children_action(world: *mut sys::ecs_world_t, _ctx: *mut c_void) {
+    let world = unsafe { WorldRef::from_ptr(world) }.real_world();
+    init_ordered_children_observer(&world);
+
+    world.each_entity::<&mut OrderedChildren>(|parent, order| {
+        let mut children = Vec::new();
+        parent.each_child(|child| children.push(child.id()));
+        order.children.retain(|child| children.contains(child));
+        for child in children {
+            if order.position(child).is_none() {
+                order.children.push(child);
+            }
+        }
+    });
+}
+
+fn init_ordered_children_observer(world: &World) {
+    world
+        .observer::<flecs::OnAdd, ()>()
+        .add_event::<flecs::OnRemove>()
+        .with_id((ECS_CHILD_OF, ECS_WILDCARD))
+        .each_iter(|it, index, ()| {
+            let Some(pair) = it.pair(0) else {
+                return;
+            };
+            let world = it.world();
+            let parent = *pair.second_id();
+            if !world.is_alive(parent) {
+                return;
+            }
+
+            let child = it.entity(index).id();
+            let is_add = it.event() == flecs::OnAdd::ID;
+            world
+                .entity_from_id(parent)
+                .try_get::<&mut OrderedChildren>(|order| {
+                    if !is_add {
+                        order.remove(child);
+                    } else if order.position(child).is_none() {
+                        order.children.push(child);
+                    }
+                });
+        });
+}
+
+impl<'a> EntityView<'a> {
+    /// Keeps the children of this entity in a deterministic order.
+    ///
+    /// Existing children keep their current (storage) order, children added later are
+    /// appended at the end. Children that are reparented or deleted are removed from the order.
+    /// The order can be changed with [`EntityView::insert_child_at()`],
+    /// [`EntityView::move_child_before()`] and [`EntityView::move_child_after()`], which enable
+    /// ordered children automatically.
+    ///
+    /// The order is stored as a list, so moving, reparenting and deleting a child takes time
+    /// linear in the number of children of the parent.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// let world = World::new();
+    ///
+    /// let parent = world.entity().enable_ordered_children();
+    /// let a = world.entity().child_of_id(parent);
+    /// let b = world.entity().child_of_id(parent);
+    /// let c = world.entity().child_of_id(parent);
+    ///
+    /// parent.move_child_before(c, a);
+    /// assert_eq!(parent.children(), [c, a, b]);
+    /// assert_eq!(b.index_in_parent(), Some(2));
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`EntityView::children()`]
+    /// * [`EntityView::index_in_parent()`]
+    pub fn enable_ordered_children(self) -> Self {
+        self.update_ordered_children(|_| {})
+    }
+
+    /// Calls `f` with the ordered children of this entity, enabling ordered children first.
+    ///
+    /// When ordered children aren't enabled yet, the order is built from the current children
+    /// and set once, so no value is read back after it's set, which fails while deferred.
+    fn update_ordered_children(self, f: impl FnOnce(&mut OrderedChildren)) -> Self {
+        if self.has::<OrderedChildren>() {
+            self.get::<&mut OrderedChildren>(f);
+            return self;
+        }
+
+        ensure_ordered_children_observer(self.world);
+
+        let mut order = OrderedChildren::default();
+        self.each_child(|child| order.children.push(child.id()));
+        f(&mut order);
+        self.set(order)
+    }
+
+    /// Adds `child` to this entity at position `index` of the ordered children.
+    ///
+    /// When `child` already is a child of this entity it is moved to `index`. An `index` past
+    /// the last child appends the child.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The position of the child.
+    /// * `child` - The child entity.
+    ///
+    /// # See also
+    ///
+    /// * [`EntityView::enable_ordered_children()`]
+    pub fn insert_child_at(self, index: usize, child: impl Into<Entity>) -> Self {
+        let child = child.into();
+        self.update_ordered_children(|order| order.insert(index, child));
+
+        // the child is in the order already, so the observer doesn't append it again
+        let child_view = EntityView::new_from(self.world, child);
+        if child_view.parent().map(|parent| parent.id) != Some(self.id) {
+            child_view.child_of_id(self.id);
+        }
+        self
+    }
+
+    /// Moves `child` to the position before `sibling`.
+    ///
+    /// # Arguments
+    ///
+    /// * `child` - The child to move.
+    /// * `sibling` - The child to move `child` before.
+    ///
+    /// # Panics
+    ///
+    /// Both `child` and `sibling` must be children of this entity.
+    ///
+    /// # See also
+    ///
+    /// * [`EntityView::move_child_after()`]
+    pub fn move_child_before(self, child: impl Into<Entity>, sibling: impl Into<Entity>) -> Self {
+        self.move_child_next_to(child.into(), sibling.into(), 0)
+    }
+
+    /// Moves `child` to the position after `sibling`.
+    ///
+    /// # Arguments
+    ///
+    /// * `child` - The child to move.
+    /// * `sibling` - The child to move `child` after.
+    ///
+    /// # Panics
+    ///
+    /// Both `child` and `sibling` must be children of this entity.
+    ///
+    /// # See also
+    ///
+    /// * [`EntityView::move_child_before()`]
+    pub fn move_child_after(self, child: impl Into<Entity>, sibling: impl Into<Entity>) -> Self {
+        self.move_child_next_to(child.into(), sibling.into(), 1)
+    }
+
+    fn move_child_next_to(self, child: Entity, sibling: Entity, offset: usize) -> Self {
+        self.update_ordered_children(|order| {
+            assert!(
+                order.position(child).is_some(),
+                "entity {child} is not a child of {}",
+                self.id
+            );
+            order.remove(child);
+            let index = order
+                .position(sibling)
+                .unwrap_or_else(|| panic!("entity {sibling} is not a child of {}", self.id));
+            order.insert(index + offset, child);
+        })
+    }
+
+    /// Returns the children of this entity.
+    ///
+    /// When ordered children are enabled the children are returned in order, otherwise they are
+    /// returned in storage order, which changes when the tables of the children change.
+    ///
+    /// # See also
+    ///
+    /// * [`EntityView::enable_ordered_children()`]
+    /// * [`EntityView::each_child()`]
+    pub fn children(self) -> Vec<EntityView<'a>> {
+        let world = self.world;
+        let mut children = Vec::new();
+        let ordered = self.try_get::<&OrderedChildren>(|order| {
+            children.extend(
+                order
+                    .children
+                    .iter()
+                    .map(|&child| EntityView::new_from(world, child)),
+            );
+        });
+
+        if !ordered {
+            self.each_child(|child| children.push(EntityView::new_from(world, child.id)));
+        }
+        children
+    }
+
+    /// Returns the position of this entity in the children of its parent.
+    ///
+    /// This looks up the entity in [`EntityView::children()`] of the parent, which takes time
+    /// linear in the number of children.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the entity has no parent.
+    ///
+    /// # See also
+    ///
+    /// * [`EntityView::children()`]
+    pub fn index_in_parent(self) -> Option<usize> {
+        let parent = self.parent()?;
+        parent
+            .children()
+            .iter()
+            .position(|child| child.id == self.id)
+    }
+}
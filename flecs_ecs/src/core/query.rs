@@ -85,6 +85,7 @@ where
             if self.query.as_ref().entity == 0
                 && sys::flecs_poly_release_(self.query.as_ptr() as *mut c_void) == 0
             {
+                forget_row_change_cursors(self.query.as_ptr());
                 sys::ecs_query_fini(self.query.as_ptr());
             }
         }
@@ -201,6 +202,18 @@ where
             panic!("Failed to create query, this is due to the user creating an invalid query. Most likely by using `expr` with a wrong expression.");
         }
 
+        unsafe { Self::from_raw(world_ptr, query_ptr) }
+    }
+
+    /// Wraps a query that was created with `ecs_query_init`.
+    ///
+    /// # Safety
+    ///
+    /// `query_ptr` must be a valid query of `world_ptr` that isn't owned by another `Query`.
+    pub(crate) unsafe fn from_raw(
+        world_ptr: *mut sys::ecs_world_t,
+        query_ptr: *mut sys::ecs_query_t,
+    ) -> Self {
         unsafe {
             let world_ctx = ecs_get_binding_ctx(world_ptr) as *mut WorldCtx;
             (*world_ctx).inc_query_ref_count();
@@ -261,6 +274,7 @@ where
             let world = self.world();
             let world_ctx = world.world_ctx_mut();
             world_ctx.dec_query_ref_count();
+            forget_row_change_cursors(self.query.as_ptr());
             if unsafe { sys::flecs_poly_release_(self.query.as_ptr() as *mut c_void) } > 0 {
                 world_ctx.set_is_panicking_true();
                 unsafe { sys::ecs_query_fini(self.query.as_ptr()) };
@@ -331,29 +345,86 @@ where
         unsafe { sys::ecs_query_get_group_info(self.query.as_ptr(), *group_id.into()) }
     }
 
+    /// Get the typed context of the query.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `C` - The type of the context.
+    ///
+    /// # Panics
+    ///
+    /// Panics when no context was set with [`QueryBuilder::set_context_typed()`], or when the
+    /// context is not of type `C`.
+    ///
+    /// # See also
+    ///
+    /// * C++ API: `query_base::ctx`
+    #[doc(alias = "query_base::ctx")]
+    pub fn context_typed<C: 'static>(&self) -> &C {
+        unsafe { typed_context(typed_query_context(self.query.as_ptr())) }
+    }
+
     /// Get context for group
     ///
+    /// The context of a group is created by the action passed to
+    /// [`on_group_create`](QueryBuilderImpl::on_group_create).
+    ///
+    /// # Type Parameters
+    ///
+    /// * `G` - The type of the group context
+    ///
     /// # Arguments
     ///
     /// * `group_id` - The group id to get context for
     ///
     /// # Returns
     ///
-    /// Returns a (void) pointer to the group context
+    /// Returns the group context, or `None` if the group doesn't exist or its context is not
+    /// of type `G`
     ///
     /// # See also
     ///
     /// * C++ API: `query_base::group_ctx`
     #[doc(alias = "query_base::group_ctx")]
-    pub fn group_context(&self, group_id: impl Into<Entity>) -> *mut c_void {
+    pub fn group_context<G: 'static>(&self, group_id: impl Into<Entity>) -> Option<&G> {
         let group_info = self.group_info(group_id);
 
-        if !group_info.is_null() {
-            unsafe { (*group_info).ctx }
+        if group_info.is_null() {
+            return None;
+        }
+
+        // group contexts are created as `Box<dyn Any>` by the on_group_create trampoline
+        let ctx = unsafe { (*group_info).ctx } as *const Box<dyn std::any::Any>;
+        if ctx.is_null() {
+            None
         } else {
-            std::ptr::null_mut()
+            unsafe { (*ctx).downcast_ref::<G>() }
         }
     }
+
+    /// Get the context of a group created by a C action
+    ///
+    /// The context of a group is returned by the action passed to
+    /// [`on_group_create_action`](QueryBuilderImpl::on_group_create_action).
+    ///
+    /// # Arguments
+    ///
+    /// * `group_id` - The group id to get context for
+    ///
+    /// # Returns
+    ///
+    /// Returns a (void) pointer to the group context, or null if the group doesn't exist or its
+    /// context wasn't created by a C action
+    ///
+    /// # See also
+    ///
+    /// * [`Query::group_context()`]
+    /// * C++ API: `query_base::group_ctx`
+    #[doc(alias = "query_base::group_ctx")]
+    pub fn group_context_ptr(&self, group_id: impl Into<Entity>) -> *mut c_void {
+        self.group_context::<RawGroupContext>(group_id)
+            .map_or(std::ptr::null_mut(), |ctx| ctx.0)
+    }
 }
 
 impl<T: QueryTuple> From<&Query<T>> for NonNull<sys::ecs_query_t> {
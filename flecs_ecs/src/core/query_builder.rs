@@ -1,6 +1,8 @@
 //! Builder for [`Query`].
 
+use std::any::Any;
 use std::ffi::c_void;
+use std::ptr::NonNull;
 
 use crate::core::internals::*;
 use crate::core::*;
@@ -13,6 +15,7 @@ where
 {
     pub(crate) desc: sys::ecs_query_desc_t,
     pub(crate) term_builder: TermBuilder,
+    pub(crate) vars: Vec<String>,
     world: WorldRef<'a>,
     _phantom: std::marker::PhantomData<T>,
 }
@@ -49,6 +52,7 @@ where
             desc: Default::default(),
             world: world.world(),
             term_builder: Default::default(),
+            vars: Vec::new(),
             _phantom: std::marker::PhantomData,
         };
 
@@ -76,6 +80,7 @@ where
         let mut obj = Self {
             desc,
             term_builder: Default::default(),
+            vars: Vec::new(),
             world: world.world(),
             _phantom: std::marker::PhantomData,
         };
@@ -111,6 +116,7 @@ where
         let obj = Self {
             desc: *desc,
             term_builder: Default::default(),
+            vars: Vec::new(),
             world: world.world(),
             _phantom: std::marker::PhantomData,
         };
@@ -143,7 +149,9 @@ where
                 expr_count: 0,
                 term_ref_mode: TermRefMode::Src,
                 str_ptrs_to_free: Vec::new(),
+                ..Default::default()
             },
+            vars: Vec::new(),
             world: world.world(),
             _phantom: std::marker::PhantomData,
         };
@@ -151,6 +159,34 @@ where
         T::populate(&mut obj);
         obj
     }
+
+    /// Set a typed context, which is dropped when the query is destroyed.
+    ///
+    /// The context can be accessed with [`Query::context_typed()`], and with
+    /// [`TableIter::context_typed()`] while the query is iterated.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `C` - The type of the context.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The context.
+    ///
+    /// # See also
+    ///
+    /// * C++ API: `query_builder_i::ctx`
+    #[doc(alias = "query_builder_i::ctx")]
+    pub fn set_context_typed<C: 'static>(&mut self, context: C) -> &mut Self {
+        if is_typed_context(self.desc.ctx_free) {
+            unsafe { free_typed_context(self.desc.ctx) };
+        }
+        self.desc.ctx = typed_context_into_raw(context);
+        self.desc.ctx_free = Some(free_typed_context);
+        // marks the context as typed
+        self.desc.binding_ctx = self.desc.ctx;
+        self
+    }
 }
 
 #[doc(hidden)]
@@ -177,7 +213,7 @@ impl<'a, T: QueryTuple> internals::QueryConfig<'a> for QueryBuilder<'a, T> {
 
     #[inline(always)]
     fn count_generic_terms(&self) -> i32 {
-        T::COUNT
+        T::TERM_COUNT
     }
 }
 
@@ -191,6 +227,15 @@ impl<'a, T: QueryTuple> WorldProvider<'a> for QueryBuilder<'a, T> {
     }
 }
 
+impl<T> Drop for QueryBuilder<'_, T>
+where
+    T: QueryTuple,
+{
+    fn drop(&mut self) {
+        free_unbuilt_group_callbacks(&mut self.desc);
+    }
+}
+
 impl<'a, T> Builder<'a> for QueryBuilder<'a, T>
 where
     T: QueryTuple,
@@ -204,8 +249,12 @@ where
     /// * C++ API: `node_builder::build`
     #[doc(alias = "node_builder::build")]
     fn build(&mut self) -> Self::BuiltType {
+        self.assert_term_count();
         let world = self.world;
-        let query = Query::<T>::new_from_desc(world, &mut self.desc);
+        let mut desc = self.desc;
+        let query = init_with_group_callbacks(&mut self.desc, || {
+            Query::<T>::new_from_desc(world, &mut desc)
+        });
         for string_parts in self.term_builder.str_ptrs_to_free.iter() {
             unsafe {
                 String::from_raw_parts(
@@ -306,7 +355,7 @@ pub trait QueryBuilderImpl<'a>: TermBuilderImpl<'a> {
     ///
     /// * C++ API: `query_builder_i::expr`
     #[doc(alias = "query_builder_i::expr")]
-    fn expr(&mut self, expr: &'a str) -> &mut Self {
+    fn expr(&mut self, expr: &str) -> &mut Self {
         let expr = format!("{}\0", expr);
         let expr = std::mem::ManuallyDrop::new(expr);
         ecs_assert!(
@@ -661,11 +710,8 @@ pub trait QueryBuilderImpl<'a>: TermBuilderImpl<'a> {
         }
         *self.next_term_index_mut() = next_index + 1;
 
-        ecs_assert!(
-            current_index < sys::FLECS_TERM_COUNT_MAX as i32,
-            FlecsErrorCode::InvalidParameter,
-            "Maximum number of terms reached in query builder",
-        );
+        // terms past `FLECS_TERM_COUNT_MAX` are tracked by the term builder and reported when
+        // the query is built
 
         // let term = &mut self.query_desc_mut().terms[index as usize] as *mut sys::ecs_term_t;
 
@@ -919,7 +965,8 @@ pub trait QueryBuilderImpl<'a>: TermBuilderImpl<'a> {
 
     /// Group and sort matched tables.
     ///
-    /// This function is similar to `group_by<T>`, but uses a default `group_by` action.
+    /// This function is similar to `group_by_fn<T>`, but uses a default `group_by` action,
+    /// which groups tables by the target of the `T` relationship.
     ///
     /// # Type Parameters
     ///
@@ -933,7 +980,7 @@ pub trait QueryBuilderImpl<'a>: TermBuilderImpl<'a> {
     where
         T: ComponentId,
     {
-        self.group_by_id_fn(T::id(self.world()), None)
+        self.group_by_id(T::id(self.world()))
     }
 
     /// Group and sort matched tables.
@@ -957,28 +1004,65 @@ pub trait QueryBuilderImpl<'a>: TermBuilderImpl<'a> {
     ///
     /// # Arguments
     ///
-    /// * `group_by_action`: Callback that determines group id for table.
+    /// * `group_by_action`: Callback that determines the group id for a table. It is invoked
+    ///   with the world, the table and the id of `T`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    ///
+    /// #[derive(Component)]
+    /// struct Group;
+    ///
+    /// let world = World::new();
+    ///
+    /// let query = world
+    ///     .query::<&Position>()
+    ///     // tables with more components are iterated last
+    ///     .group_by_fn::<Group>(|_world, table, _id| table.archetype().count() as u64)
+    ///     .build();
+    ///
+    /// world.entity().set(Position { x: 1.0, y: 2.0 }).add::<Group>();
+    /// world.entity().set(Position { x: 3.0, y: 4.0 });
+    ///
+    /// let mut groups = Vec::new();
+    /// query.run(|mut it| {
+    ///     while it.next() {
+    ///         groups.push(it.group_id());
+    ///     }
+    /// });
+    /// assert_eq!(groups, [1, 2]);
+    /// ```
     ///
     /// # See also
     ///
     /// * C++ API: `query_builder_i::group_by`
     #[doc(alias = "query_builder_i::group_by")]
-    fn group_by_fn<T>(&mut self, group_by_action: sys::ecs_group_by_action_t) -> &mut Self
+    fn group_by_fn<T>(
+        &mut self,
+        group_by_action: impl FnMut(&World, Table, Id) -> u64 + 'static,
+    ) -> &mut Self
     where
         T: ComponentId,
     {
-        self.group_by_id_fn(T::id(self.world()), group_by_action);
-        self
+        self.group_by_id_fn(T::id(self.world()), group_by_action)
     }
 
     /// Group and sort matched tables.
     ///
-    /// This is similar to `group_by<T>`, but uses a component identifier instead.
+    /// This is similar to `group_by_fn<T>`, but uses a component identifier instead.
     ///
     /// # Arguments
     ///
     /// * `component`: The component used to determine the group rank.
-    /// * `group_by_action`: Callback that determines group id for table.
+    /// * `group_by_action`: Callback that determines the group id for a table.
     ///
     /// # See also
     ///
@@ -987,17 +1071,21 @@ pub trait QueryBuilderImpl<'a>: TermBuilderImpl<'a> {
     fn group_by_id_fn(
         &mut self,
         component: impl Into<Entity>,
-        group_by_action: sys::ecs_group_by_action_t,
+        group_by_action: impl FnMut(&World, Table, Id) -> u64 + 'static,
     ) -> &mut Self {
         let desc = self.query_desc_mut();
-        desc.group_by_callback = group_by_action;
+        let mut group_by_action = group_by_action;
+        group_callbacks(desc).group_by = Some(Box::new(move |world, table, id, _| {
+            group_by_action(world, table, id)
+        }));
+        desc.group_by_callback = Some(group_by_trampoline);
         desc.group_by = *component.into();
         self
     }
 
     /// Group and sort matched tables.
     ///
-    /// This is similar to `group_by_default<T>`, but uses a component identifier instead.
+    /// This is similar to `group_by<T>`, but uses a component identifier instead.
     ///
     /// # Arguments
     ///
@@ -1008,14 +1096,185 @@ pub trait QueryBuilderImpl<'a>: TermBuilderImpl<'a> {
     /// * C++ API: `query_builder_i::group_by`
     #[doc(alias = "query_builder_i::group_by")]
     fn group_by_id(&mut self, component: impl Into<Entity>) -> &mut Self {
-        self.group_by_id_fn(component, None)
+        let desc = self.query_desc_mut();
+        desc.group_by_callback = None;
+        desc.group_by = *component.into();
+        self
+    }
+
+    /// Specify the `on_group_create` action.
+    ///
+    /// The action is invoked when the query creates a new group and returns the context of the
+    /// group, which can be retrieved with [`Query::group_context()`]. The context is dropped when
+    /// the group is deleted, after the `on_group_delete` action (if any) was invoked.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `G`: The type of the group context.
+    ///
+    /// # Arguments
+    ///
+    /// * `action`: The action to execute when a group is created. It is invoked with the world
+    ///   and the group id.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Group;
+    ///
+    /// struct GroupStats {
+    ///     group: u64,
+    /// }
+    ///
+    /// let world = World::new();
+    ///
+    /// let query = world
+    ///     .query::<()>()
+    ///     .with::<(Group, flecs::Wildcard)>()
+    ///     .group_by::<Group>()
+    ///     .on_group_create(|_world, group| GroupStats { group })
+    ///     .on_group_delete(|_world, group, stats: GroupStats| assert_eq!(stats.group, group))
+    ///     .build();
+    ///
+    /// let first = world.entity();
+    /// world.entity().add_id((Group::id(&world), first));
+    ///
+    /// // groups are created when the query matches new tables, which happens when it's iterated
+    /// query.run(|mut it| while it.next() {});
+    ///
+    /// let stats = query.group_context::<GroupStats>(first).unwrap();
+    /// assert_eq!(stats.group, *first.id());
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`Query::group_context()`]
+    /// * C++ API: `query_builder_i::on_group_create`
+    #[doc(alias = "query_builder_i::on_group_create")]
+    fn on_group_create<G: 'static>(
+        &mut self,
+        mut action: impl FnMut(&World, u64) -> G + 'static,
+    ) -> &mut Self {
+        let desc = self.query_desc_mut();
+        let callbacks = group_callbacks(desc);
+        callbacks.on_create = Some(Box::new(move |world, group_id, _| {
+            Box::new(action(world, group_id)) as Box<dyn Any>
+        }));
+        desc.on_group_create = Some(group_create_trampoline);
+        desc.on_group_delete = Some(group_delete_trampoline);
+        self
     }
 
-    /// Specify context to be passed to the `group_by` function.
+    /// Specify the `on_group_delete` action.
+    ///
+    /// The action takes ownership of the context that was created by the `on_group_create`
+    /// action for the group.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `G`: The type of the group context, which must match the type returned by the
+    ///   `on_group_create` action.
     ///
     /// # Arguments
     ///
-    /// * `ctx`: Context to pass to the `group_by` function.
+    /// * `action`: The action to execute when a group is deleted. It is invoked with the world,
+    ///   the group id and the group context.
+    ///
+    /// # Panics
+    ///
+    /// The action panics (which aborts) when the group context is not of type `G`.
+    ///
+    /// # See also
+    ///
+    /// * [`QueryBuilderImpl::on_group_create()`]
+    /// * C++ API: `query_builder_i::on_group_delete`
+    #[doc(alias = "query_builder_i::on_group_delete")]
+    fn on_group_delete<G: 'static>(
+        &mut self,
+        mut action: impl FnMut(&World, u64, G) + 'static,
+    ) -> &mut Self {
+        let desc = self.query_desc_mut();
+        let callbacks = group_callbacks(desc);
+        callbacks.on_delete = Some(Box::new(move |world, group_id, ctx, _| {
+            let Some(ctx) = ctx else {
+                return;
+            };
+            let ctx = ctx.downcast::<G>().unwrap_or_else(|_| {
+                panic!("group context is not of the type passed to on_group_delete")
+            });
+            action(world, group_id, *ctx);
+        }));
+        desc.on_group_delete = Some(group_delete_trampoline);
+        self
+    }
+
+    /// Group and sort matched tables with a C `group_by` action.
+    ///
+    /// This is similar to `group_by_fn<T>`, but takes a C function, which is passed the context
+    /// set with [`QueryBuilderImpl::group_by_ctx()`].
+    ///
+    /// # Type Parameters
+    ///
+    /// * `T`: The type used to determine the group rank.
+    ///
+    /// # Arguments
+    ///
+    /// * `group_by_action`: Callback that determines the group id for a table.
+    ///
+    /// # See also
+    ///
+    /// * C++ API: `query_builder_i::group_by`
+    #[doc(alias = "query_builder_i::group_by")]
+    fn group_by_action<T>(&mut self, group_by_action: sys::ecs_group_by_action_t) -> &mut Self
+    where
+        T: ComponentId,
+    {
+        self.group_by_id_action(T::id(self.world()), group_by_action)
+    }
+
+    /// Group and sort matched tables with a C `group_by` action.
+    ///
+    /// This is similar to `group_by_action<T>`, but uses a component identifier instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `component`: The component used to determine the group rank.
+    /// * `group_by_action`: Callback that determines the group id for a table, or `None` for the
+    ///   default `group_by` action.
+    ///
+    /// # See also
+    ///
+    /// * C++ API: `query_builder_i::group_by`
+    #[doc(alias = "query_builder_i::group_by")]
+    fn group_by_id_action(
+        &mut self,
+        component: impl Into<Entity>,
+        group_by_action: sys::ecs_group_by_action_t,
+    ) -> &mut Self {
+        let Some(action) = group_by_action else {
+            return self.group_by_id(component);
+        };
+        let desc = self.query_desc_mut();
+        group_callbacks(desc).group_by = Some(Box::new(move |world, table, id, ctx| unsafe {
+            action(world.world_ptr_mut(), table.table_ptr_mut(), *id, ctx)
+        }));
+        desc.group_by_callback = Some(group_by_trampoline);
+        desc.group_by = *component.into();
+        self
+    }
+
+    /// Specify context to be passed to the C group actions.
+    ///
+    /// The context is passed to the actions set with [`QueryBuilderImpl::group_by_action()`],
+    /// [`QueryBuilderImpl::on_group_create_action()`] and
+    /// [`QueryBuilderImpl::on_group_delete_action()`]. Closures capture their context instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`: Context to pass to the group actions.
     /// * `ctx_free`: Function to clean up the context (called when the query is deleted).
     ///
     /// # See also
@@ -1023,13 +1282,19 @@ pub trait QueryBuilderImpl<'a>: TermBuilderImpl<'a> {
     /// * C++ API: `query_builder_i::group_by_ctx`
     #[doc(alias = "query_builder_i::group_by_ctx")]
     fn group_by_ctx(&mut self, ctx: *mut c_void, ctx_free: sys::ecs_ctx_free_t) -> &mut Self {
-        let desc = self.query_desc_mut();
-        desc.group_by_ctx = ctx;
-        desc.group_by_ctx_free = ctx_free;
+        let callbacks = group_callbacks(self.query_desc_mut());
+        if let Some(free) = callbacks.ctx_free {
+            unsafe { free(callbacks.ctx) };
+        }
+        callbacks.ctx = ctx;
+        callbacks.ctx_free = ctx_free;
         self
     }
 
-    /// Specify the `on_group_create` action.
+    /// Specify a C `on_group_create` action.
+    ///
+    /// The pointer returned by the action is the context of the group, which can be retrieved
+    /// with [`Query::group_context_ptr()`].
     ///
     /// # Arguments
     ///
@@ -1037,15 +1302,27 @@ pub trait QueryBuilderImpl<'a>: TermBuilderImpl<'a> {
     ///
     /// # See also
     ///
+    /// * [`QueryBuilderImpl::on_group_create()`]
     /// * C++ API: `query_builder_i::on_group_create`
     #[doc(alias = "query_builder_i::on_group_create")]
-    fn on_group_create(&mut self, action: sys::ecs_group_create_action_t) -> &mut Self {
+    fn on_group_create_action(&mut self, action: sys::ecs_group_create_action_t) -> &mut Self {
         let desc = self.query_desc_mut();
-        desc.on_group_create = action;
+        let callbacks = group_callbacks(desc);
+        callbacks.on_create = action.map(|action| {
+            Box::new(move |world: &World, group_id, ctx| {
+                let group_ctx = unsafe { action(world.world_ptr_mut(), group_id, ctx) };
+                Box::new(RawGroupContext(group_ctx)) as Box<dyn Any>
+            }) as GroupCreateAction
+        });
+        desc.on_group_create = Some(group_create_trampoline);
+        desc.on_group_delete = Some(group_delete_trampoline);
         self
     }
 
-    /// Specify the `on_group_delete` action.
+    /// Specify a C `on_group_delete` action.
+    ///
+    /// The action is passed the context returned by the `on_group_create_action` for the
+    /// group, which it is responsible for freeing.
     ///
     /// # Arguments
     ///
@@ -1053,15 +1330,219 @@ pub trait QueryBuilderImpl<'a>: TermBuilderImpl<'a> {
     ///
     /// # See also
     ///
+    /// * [`QueryBuilderImpl::on_group_delete()`]
     /// * C++ API: `query_builder_i::on_group_delete`
     #[doc(alias = "query_builder_i::on_group_delete")]
-    fn on_group_delete(&mut self, action: sys::ecs_group_delete_action_t) -> &mut Self {
+    fn on_group_delete_action(&mut self, action: sys::ecs_group_delete_action_t) -> &mut Self {
         let desc = self.query_desc_mut();
-        desc.on_group_delete = action;
+        let callbacks = group_callbacks(desc);
+        callbacks.on_delete = action.map(|action| {
+            Box::new(
+                move |world: &World, group_id, group_ctx: Option<Box<dyn Any>>, ctx| {
+                    let group_ctx = group_ctx
+                        .and_then(|group_ctx| group_ctx.downcast::<RawGroupContext>().ok())
+                        .map_or(std::ptr::null_mut(), |group_ctx| group_ctx.0);
+                    unsafe { action(world.world_ptr_mut(), group_id, group_ctx, ctx) };
+                },
+            ) as GroupDeleteAction
+        });
+        desc.on_group_delete = Some(group_delete_trampoline);
         self
     }
 }
 
+// The actions receive the context set with `group_by_ctx`, which is only passed on to the
+// actions set with the `*_action` builder methods.
+type GroupByAction = Box<dyn FnMut(&World, Table, Id, *mut c_void) -> u64>;
+type GroupCreateAction = Box<dyn FnMut(&World, u64, *mut c_void) -> Box<dyn Any>>;
+type GroupDeleteAction = Box<dyn FnMut(&World, u64, Option<Box<dyn Any>>, *mut c_void)>;
+
+/// The context of a group created by an action set with
+/// [`QueryBuilderImpl::on_group_create_action()`].
+pub(crate) struct RawGroupContext(pub(crate) *mut c_void);
+
+/// The group callbacks of a query, stored as the `group_by_ctx` of the query.
+struct GroupCallbacks {
+    group_by: Option<GroupByAction>,
+    on_create: Option<GroupCreateAction>,
+    on_delete: Option<GroupDeleteAction>,
+    ctx: *mut c_void,
+    ctx_free: sys::ecs_ctx_free_t,
+    /// Whether flecs is creating an object from the desc that holds the callbacks.
+    initializing: bool,
+    /// Whether flecs freed the callbacks while it failed to create the object.
+    freed: bool,
+}
+
+impl Default for GroupCallbacks {
+    fn default() -> Self {
+        Self {
+            group_by: None,
+            on_create: None,
+            on_delete: None,
+            ctx: std::ptr::null_mut(),
+            ctx_free: None,
+            initializing: false,
+            freed: false,
+        }
+    }
+}
+
+impl Drop for GroupCallbacks {
+    fn drop(&mut self) {
+        if let Some(ctx_free) = self.ctx_free {
+            unsafe { ctx_free(self.ctx) };
+        }
+    }
+}
+
+/// Returns the group callbacks of `desc`, creating them when the query has none yet.
+fn group_callbacks(desc: &mut sys::ecs_query_desc_t) -> &mut GroupCallbacks {
+    if desc.group_by_ctx.is_null() {
+        desc.group_by_ctx = Box::into_raw(Box::<GroupCallbacks>::default()) as *mut c_void;
+        desc.group_by_ctx_free = Some(free_group_callbacks);
+    }
+    unsafe { &mut *(desc.group_by_ctx as *mut GroupCallbacks) }
+}
+
+/// Hands the group callbacks of `desc` over to the query that was built from it.
+fn group_callbacks_built(desc: &mut sys::ecs_query_desc_t) {
+    desc.group_by_ctx = std::ptr::null_mut();
+    desc.group_by_ctx_free = None;
+}
+
+/// Creates a query, system, observer or pipeline with `init` from a desc of a builder, and hands
+/// the group callbacks of `query`, the query desc of the builder, over to it.
+///
+/// Every build path passes its desc to flecs through this, as a builder frees its group
+/// callbacks when it's dropped. When flecs fails to create the object, the callbacks stay with
+/// the builder, unless flecs already freed them.
+pub(crate) fn try_init_with_group_callbacks<R, E>(
+    query: &mut sys::ecs_query_desc_t,
+    init: impl FnOnce() -> Result<R, E>,
+) -> Result<R, E> {
+    let is_group_callbacks = query.group_by_ctx_free.is_some_and(|ctx_free| {
+        std::ptr::fn_addr_eq(
+            ctx_free,
+            free_group_callbacks as unsafe extern "C" fn(*mut c_void),
+        )
+    });
+    let callbacks = if is_group_callbacks {
+        query.group_by_ctx as *mut GroupCallbacks
+    } else {
+        std::ptr::null_mut()
+    };
+    if callbacks.is_null() {
+        return init();
+    }
+
+    let guard = GroupCallbacksInit { query, callbacks };
+    unsafe { (*callbacks).initializing = true };
+    let result = init();
+    if result.is_ok() {
+        group_callbacks_built(guard.query);
+    }
+    result
+}
+
+/// Like [`try_init_with_group_callbacks()`], for build paths that can't fail.
+pub(crate) fn init_with_group_callbacks<R>(
+    query: &mut sys::ecs_query_desc_t,
+    init: impl FnOnce() -> R,
+) -> R {
+    match try_init_with_group_callbacks(query, || Ok::<_, std::convert::Infallible>(init())) {
+        Ok(result) => result,
+        Err(never) => match never {},
+    }
+}
+
+/// Finishes handing group callbacks over to flecs, also when the object couldn't be created
+/// or creating it panicked.
+struct GroupCallbacksInit<'a> {
+    query: &'a mut sys::ecs_query_desc_t,
+    callbacks: *mut GroupCallbacks,
+}
+
+impl Drop for GroupCallbacksInit<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            (*self.callbacks).initializing = false;
+            if (*self.callbacks).freed {
+                drop(Box::from_raw(self.callbacks));
+                group_callbacks_built(self.query);
+            }
+        }
+    }
+}
+
+/// Frees the group callbacks of a builder that is dropped without being built.
+pub(crate) fn free_unbuilt_group_callbacks(desc: &mut sys::ecs_query_desc_t) {
+    if let Some(ctx_free) = desc.group_by_ctx_free {
+        if !desc.group_by_ctx.is_null() {
+            unsafe { ctx_free(desc.group_by_ctx) };
+        }
+    }
+    group_callbacks_built(desc);
+}
+
+unsafe extern "C" fn free_group_callbacks(ctx: *mut c_void) {
+    let callbacks = ctx as *mut GroupCallbacks;
+    if (*callbacks).initializing {
+        // flecs failed to create the object, the callbacks are freed once it returns
+        (*callbacks).freed = true;
+    } else {
+        drop(Box::from_raw(callbacks));
+    }
+}
+
+unsafe extern "C" fn group_by_trampoline(
+    world: *mut sys::ecs_world_t,
+    table: *mut sys::ecs_table_t,
+    id: sys::ecs_id_t,
+    ctx: *mut c_void,
+) -> u64 {
+    let callbacks = &mut *(ctx as *mut GroupCallbacks);
+    let world = WorldRef::from_ptr(world);
+    let Some(group_by) = callbacks.group_by.as_mut() else {
+        return 0;
+    };
+    group_by(
+        &world,
+        Table::new(world, NonNull::new_unchecked(table)),
+        Id(id),
+        callbacks.ctx,
+    )
+}
+
+unsafe extern "C" fn group_create_trampoline(
+    world: *mut sys::ecs_world_t,
+    group_id: u64,
+    ctx: *mut c_void,
+) -> *mut c_void {
+    let callbacks = &mut *(ctx as *mut GroupCallbacks);
+    let world = WorldRef::from_ptr(world);
+    match callbacks.on_create.as_mut() {
+        Some(on_create) => {
+            Box::into_raw(Box::new(on_create(&world, group_id, callbacks.ctx))) as *mut c_void
+        }
+        None => std::ptr::null_mut(),
+    }
+}
+
+unsafe extern "C" fn group_delete_trampoline(
+    world: *mut sys::ecs_world_t,
+    group_id: u64,
+    group_ctx: *mut c_void,
+    ctx: *mut c_void,
+) {
+    let callbacks = &mut *(ctx as *mut GroupCallbacks);
+    let world = WorldRef::from_ptr(world);
+    let group_ctx = (!group_ctx.is_null()).then(|| *Box::from_raw(group_ctx as *mut Box<dyn Any>));
+    if let Some(on_delete) = callbacks.on_delete.as_mut() {
+        on_delete(&world, group_id, group_ctx, callbacks.ctx);
+    }
+}
+
 pub trait OrderByFn<T>
 where
     T: ComponentId,
@@ -0,0 +1,145 @@
+//! Structured description of a query: its terms, variables, cache kind and matched tables.
+
+use std::ffi::CStr;
+use std::ptr::NonNull;
+
+use crate::core::*;
+use crate::sys;
+
+/// A structured description of a query, returned by [`QueryAPI::describe()`].
+///
+/// Where [`QueryAPI::to_string()`] and [`QueryAPI::plan()`] format the query as text, the
+/// description exposes the same information as values, so it can be inspected by tools or
+/// asserted on in tests.
+///
+/// # Example
+///
+/// ```
+/// use flecs_ecs::prelude::*;
+///
+/// #[derive(Component)]
+/// struct Position {
+///     x: f32,
+///     y: f32,
+/// }
+///
+/// #[derive(Component)]
+/// struct Velocity {
+///     x: f32,
+///     y: f32,
+/// }
+///
+/// let world = World::new();
+///
+/// world
+///     .entity()
+///     .set(Position { x: 0.0, y: 0.0 })
+///     .set(Velocity { x: 1.0, y: 1.0 });
+/// world.entity().set(Position { x: 0.0, y: 0.0 });
+///
+/// let query = world
+///     .query::<&Position>()
+///     .without::<Velocity>()
+///     .set_cached()
+///     .build();
+///
+/// let description = query.describe();
+/// assert_eq!(description.terms.len(), 2);
+/// assert_eq!(description.terms[1].oper(), OperKind::Not);
+/// assert_eq!(description.terms[0].src(), TermSource::This);
+/// assert_eq!(description.tables.len(), 1);
+/// assert_eq!(description.tables[0].count, 1);
+/// assert!(description.tables[0]
+///     .archetype()
+///     .as_slice()
+///     .contains(&world.component_id::<Position>().into()));
+/// ```
+///
+/// # See also
+///
+/// * [`QueryAPI::describe()`]
+/// * [`QueryAPI::plan()`]
+#[derive(Debug)]
+pub struct QueryDescription<'a> {
+    /// The terms of the query.
+    pub terms: Vec<TermRef<'a>>,
+    /// The names of the variables of the query, excluding `this`.
+    pub variables: Vec<&'a str>,
+    /// The cache kind of the query. A query created with [`QueryCacheKind::Auto`] reports
+    /// [`QueryCacheKind::All`] when all of its terms are cached.
+    pub cache_kind: QueryCacheKind,
+    /// The tables the query currently matches, in iteration order.
+    pub tables: Vec<MatchedTable<'a>>,
+}
+
+/// A table matched by a query, as part of a [`QueryDescription`].
+#[derive(Debug, Clone, Copy)]
+pub struct MatchedTable<'a> {
+    /// The matched table.
+    pub table: Table<'a>,
+    /// The number of entities in the table.
+    pub count: usize,
+}
+
+impl<'a> MatchedTable<'a> {
+    /// Returns the archetype of the matched table.
+    ///
+    /// The table is locked for as long as the returned archetype is alive.
+    pub fn archetype(&self) -> Archetype<'a> {
+        self.table.archetype()
+    }
+}
+
+impl<'a> QueryDescription<'a> {
+    pub(crate) fn new(world: WorldRef<'a>, query: &'a sys::ecs_query_t) -> Self {
+        let terms = query.terms[..query.term_count as usize]
+            .iter()
+            .map(TermRef::new)
+            .collect();
+
+        let variables = if query.vars.is_null() {
+            Vec::new()
+        } else {
+            let vars = unsafe { std::slice::from_raw_parts(query.vars, query.var_count as usize) };
+            vars.iter()
+                .filter(|name| !name.is_null())
+                .filter_map(|&name| unsafe { CStr::from_ptr(name) }.to_str().ok())
+                .filter(|&name| name != "this")
+                .collect()
+        };
+
+        Self {
+            terms,
+            variables,
+            cache_kind: query.cache_kind.into(),
+            tables: matched_tables(world, query),
+        }
+    }
+
+    /// Returns the total number of entities in the matched tables.
+    pub fn entity_count(&self) -> usize {
+        self.tables.iter().map(|table| table.count).sum()
+    }
+}
+
+/// Iterates `query` and collects the distinct tables of the results.
+fn matched_tables<'a>(world: WorldRef<'a>, query: &sys::ecs_query_t) -> Vec<MatchedTable<'a>> {
+    let mut tables = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    unsafe {
+        let mut it = sys::ecs_query_iter(world.world_ptr(), query);
+        it.flags |= sys::EcsIterIsInstanced;
+        while sys::ecs_query_next(&mut it) {
+            let Some(table) = NonNull::new(it.table) else {
+                continue;
+            };
+            if seen.insert(it.table) {
+                tables.push(MatchedTable {
+                    table: Table::new(world, table),
+                    count: sys::ecs_table_count(it.table) as usize,
+                });
+            }
+        }
+    }
+    tables
+}
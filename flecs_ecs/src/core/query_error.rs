@@ -0,0 +1,332 @@
+//! Fallible query creation with structured errors.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr};
+use std::sync::OnceLock;
+
+use crate::core::*;
+use crate::sys;
+
+/// Error returned when a query can't be created.
+///
+/// Returned by [`World::try_query_from_expr()`] and [`QueryBuilder::try_build()`] for syntax
+/// errors, unknown identifiers, invalid traversal and queries with too many terms.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryParseError {
+    /// The byte offset in the query expression at which the error occurred, `0` for queries
+    /// that were not created from an expression.
+    pub position: usize,
+    /// The token at which the error occurred, such as an unresolved identifier. Empty when the
+    /// error occurred at the end of the expression.
+    pub token: String,
+    /// The index of the invalid term, when the error is caused by a single term.
+    pub term: Option<usize>,
+    /// The error message.
+    pub message: String,
+}
+
+impl QueryParseError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            position: 0,
+            token: String::new(),
+            term: None,
+            message: message.into(),
+        }
+    }
+
+    /// Creates the error from a message logged by flecs.
+    ///
+    /// Syntax errors are logged as `"<line>: <message>\n<expression line>\n<spaces>^"`.
+    /// Errors in terms are logged as `"<message>\n[expr: <expression>\n]<terms>"`, with one
+    /// term per line and the invalid term marked with `" > "`.
+    fn from_log(log: &str, expr: Option<&str>) -> Self {
+        let (first, rest) = log.split_once('\n').unwrap_or((log, ""));
+        if let Some((line, message)) = first.split_once(": ") {
+            if let Ok(line) = line.parse::<usize>() {
+                let mut error = Self::new(message);
+                let column = rest.lines().nth(1).and_then(|marker| marker.find('^'));
+                if let (Some(column), Some(expr)) = (column, expr) {
+                    let line_start: usize = expr
+                        .split_inclusive('\n')
+                        .take(line.saturating_sub(1))
+                        .map(str::len)
+                        .sum();
+                    let (position, token) = token_at(expr, line_start + column);
+                    error.position = position;
+                    error.token = token.to_string();
+                }
+                return error;
+            }
+        }
+
+        let mut error = Self::new(first);
+        error.token = first
+            .split('\'')
+            .nth(1)
+            .filter(|_| first.matches('\'').count() >= 2)
+            .unwrap_or_default()
+            .to_string();
+
+        // the expression is logged before the terms and may span multiple lines
+        let terms = match expr {
+            Some(expr) => rest
+                .strip_prefix("expr: ")
+                .and_then(|rest| rest.strip_prefix(expr))
+                .unwrap_or(rest),
+            None => rest,
+        };
+        error.term = terms
+            .lines()
+            .filter(|line| line.starts_with("   ") || line.starts_with(" > "))
+            .position(|line| line.starts_with(" > "));
+
+        if let Some(expr) = expr {
+            error.position = find_token(expr, &error.token).unwrap_or(0);
+        }
+        error
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '$' | '*')
+}
+
+/// Returns the start and text of the token at `position`: the identifier that contains
+/// `position`, or the single character at `position` when it isn't part of an identifier.
+fn token_at(expr: &str, position: usize) -> (usize, &str) {
+    let mut position = position.min(expr.len());
+    while !expr.is_char_boundary(position) {
+        position -= 1;
+    }
+
+    let Some(c) = expr[position..].chars().next() else {
+        return (expr.len(), "");
+    };
+    if c.is_whitespace() {
+        return (position, "");
+    }
+    if !is_ident_char(c) {
+        return (position, &expr[position..position + c.len_utf8()]);
+    }
+
+    let start = expr[..position]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_ident_char(c))
+        .last()
+        .map_or(position, |(index, _)| index);
+    let end = expr[position..]
+        .char_indices()
+        .find(|&(_, c)| !is_ident_char(c))
+        .map_or(expr.len(), |(index, _)| position + index);
+    (start, &expr[start..end])
+}
+
+/// Returns the offset of the first occurrence of `token` in `expr` as a whole identifier.
+fn find_token(expr: &str, token: &str) -> Option<usize> {
+    if token.is_empty() {
+        return None;
+    }
+    expr.match_indices(token)
+        .map(|(index, _)| index)
+        .find(|&index| {
+            let before = expr[..index].chars().next_back();
+            let after = expr[index + token.len()..].chars().next();
+            !before.is_some_and(is_ident_char) && !after.is_some_and(is_ident_char)
+        })
+}
+
+impl std::fmt::Display for QueryParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.token.is_empty() {
+            write!(f, "{} (at {})", self.message, self.position)
+        } else {
+            write!(
+                f,
+                "{} (at {}, near `{}`)",
+                self.message, self.position, self.token
+            )
+        }
+    }
+}
+
+impl std::error::Error for QueryParseError {}
+
+thread_local! {
+    static CAPTURED_ERRORS: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+/// The log function that was installed before [`capture_errors`] installed its own, which logs
+/// the messages that aren't captured.
+static PREV_LOG: OnceLock<sys::ecs_os_api_log_t> = OnceLock::new();
+
+unsafe extern "C" fn capture_log(level: i32, file: *const c_char, line: i32, msg: *const c_char) {
+    let captured = level <= -3
+        && CAPTURED_ERRORS.with(|errors| {
+            let mut errors = errors.borrow_mut();
+            let Some(errors) = errors.as_mut() else {
+                return false;
+            };
+            if !msg.is_null() {
+                errors.push(CStr::from_ptr(msg).to_string_lossy().into_owned());
+            }
+            true
+        });
+
+    if !captured {
+        if let Some(Some(prev)) = PREV_LOG.get() {
+            prev(level, file, line, msg);
+        }
+    }
+}
+
+/// Runs `func` while collecting the errors that flecs logs on the current thread, the same way
+/// the REST addon captures script errors.
+///
+/// The log function is installed once and stays installed, as the OS API is shared by all
+/// threads. Messages are only captured on threads that are inside `capture_errors`, and are
+/// passed on to the previous log function everywhere else.
+fn capture_errors<R>(func: impl FnOnce() -> R) -> (R, Vec<String>) {
+    PREV_LOG.get_or_init(|| unsafe {
+        let prev_log = sys::ecs_os_api.log_;
+        sys::ecs_os_api.log_ = Some(capture_log);
+        prev_log
+    });
+
+    let prev_errors = CAPTURED_ERRORS.with(|errors| errors.borrow_mut().replace(Vec::new()));
+    let result = func();
+    let errors = CAPTURED_ERRORS.with(|errors| {
+        std::mem::replace(&mut *errors.borrow_mut(), prev_errors).unwrap_or_default()
+    });
+    (result, errors)
+}
+
+impl<'a, T> QueryBuilder<'a, T>
+where
+    T: QueryTuple,
+{
+    /// Builds the query, returning an error instead of panicking when the query is invalid.
+    ///
+    /// The query is validated by flecs, which reports syntax errors in the expression,
+    /// identifiers that don't resolve to an entity, invalid traversal and queries with more than
+    /// `FLECS_TERM_COUNT_MAX` terms.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    ///
+    /// let world = World::new();
+    ///
+    /// let result = world.query::<&Position>().with_name("Velocity").try_build();
+    /// let err = result.err().unwrap();
+    /// assert_eq!(err.message, "unresolved identifier 'Velocity'");
+    /// assert_eq!(err.term, Some(1));
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`World::try_query_from_expr()`]
+    pub fn try_build(&mut self) -> Result<Query<T>, QueryParseError> {
+        let strings = std::mem::take(&mut self.term_builder.str_ptrs_to_free);
+        let expr = (!self.desc.expr.is_null()).then(|| {
+            unsafe { CStr::from_ptr(self.desc.expr) }
+                .to_string_lossy()
+                .into_owned()
+        });
+
+        let result = self.try_build_desc(expr.as_deref());
+
+        for string_parts in strings {
+            unsafe {
+                String::from_raw_parts(
+                    string_parts.ptr as *mut u8,
+                    string_parts.len,
+                    string_parts.capacity,
+                );
+            }
+        }
+        result
+    }
+
+    fn try_build_desc(&mut self, expr: Option<&str>) -> Result<Query<T>, QueryParseError> {
+        let overflow = self.term_builder.overflow_term_count;
+        if overflow != 0 {
+            return Err(QueryParseError {
+                position: 0,
+                token: String::new(),
+                term: Some(sys::FLECS_TERM_COUNT_MAX as usize),
+                message: format!(
+                    "too many terms in query ({overflow}, the maximum is {})",
+                    sys::FLECS_TERM_COUNT_MAX
+                ),
+            });
+        }
+
+        let world_ptr = self.world_ptr_mut();
+        let desc = self.desc;
+        try_init_with_group_callbacks(&mut self.desc, || {
+            let (query_ptr, errors) =
+                capture_errors(|| unsafe { sys::ecs_query_init(world_ptr, &desc) });
+
+            if query_ptr.is_null() {
+                return Err(errors.first().map_or_else(
+                    || QueryParseError::new("invalid query"),
+                    |log| QueryParseError::from_log(log, expr),
+                ));
+            }
+
+            Ok(unsafe { Query::from_raw(world_ptr, query_ptr) })
+        })
+    }
+}
+
+impl World {
+    /// Creates a query from a query expression, such as one entered by a user at runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The query expression, in the flecs query language.
+    ///
+    /// # Returns
+    ///
+    /// The query, or an error with the position and token at which the expression is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    ///
+    /// let world = World::new();
+    /// world.component_named::<Position>("Position");
+    ///
+    /// let query = world.try_query_from_expr("Position").unwrap();
+    /// world.entity().set(Position { x: 1.0, y: 2.0 });
+    /// assert_eq!(query.count(), 1);
+    ///
+    /// let err = world.try_query_from_expr("Position, Velocity").err().unwrap();
+    /// assert_eq!(err.position, 10);
+    /// assert_eq!(err.token, "Velocity");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`QueryBuilder::try_build()`]
+    /// * [`QueryBuilderImpl::expr()`]
+    pub fn try_query_from_expr(&self, expr: &str) -> Result<Query<()>, QueryParseError> {
+        self.query::<()>().expr(expr).try_build()
+    }
+}
@@ -1,4 +1,5 @@
 //! Class that extends the capabilities of a [`Query`] by providing additional operations on the query's iterator.
+use std::cell::RefCell;
 use std::ffi::c_void;
 
 use crate::core::*;
@@ -10,9 +11,34 @@ where
 {
     iter: sys::ecs_iter_t,
     iter_next: unsafe extern "C" fn(*mut sys::ecs_iter_t) -> bool,
+    page: Option<(i32, i32)>,
+    // the query iterators the retrieved page iterators read from, which are reused once their
+    // iteration finished
+    page_chains: RefCell<Vec<*mut PageChain>>,
     _phantom: std::marker::PhantomData<&'a (P, T)>,
 }
 
+/// A query iterator that a page iterator reads from, which must not move while it's iterated.
+#[repr(C)]
+struct PageChain {
+    iter: sys::ecs_iter_t,
+    fini: sys::ecs_iter_fini_action_t,
+    finished: bool,
+}
+
+/// Finishes the query iterator of a page iterator, after which it can be reused. Flecs finishes
+/// the query iterator when it's depleted, when the page is complete and when the page iterator
+/// is finished early.
+unsafe extern "C" fn page_chain_fini(it: *mut sys::ecs_iter_t) {
+    let chain = it as *mut PageChain;
+    unsafe {
+        if let Some(fini) = (*chain).fini {
+            fini(it);
+        }
+        (*chain).finished = true;
+    }
+}
+
 impl<'a, P, T> QueryIter<'a, P, T>
 where
     T: QueryTuple,
@@ -24,10 +50,67 @@ where
         Self {
             iter,
             iter_next,
+            page: None,
+            page_chains: RefCell::new(Vec::new()),
             _phantom: std::marker::PhantomData,
         }
     }
 
+    /// Limit the results to a page of the matched entities.
+    ///
+    /// Skips the first `offset` entities and returns at most `limit` entities after that. The
+    /// page is computed over the results in iteration order, so it is consistent for cached,
+    /// uncached and sorted queries as long as the matched entities don't change.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset`: the number of entities to skip
+    /// * `limit`: the maximum number of entities to return, `0` for no limit
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    ///
+    /// let world = World::new();
+    ///
+    /// for i in 0..250 {
+    ///     world.entity().set(Position { x: i as f32, y: 0.0 });
+    /// }
+    ///
+    /// let query = world.new_query::<&Position>();
+    /// assert_eq!(query.count(), 250);
+    ///
+    /// assert_eq!(query.iterable().page(100, 100).count(), 100);
+    ///
+    /// let mut xs = Vec::new();
+    /// query.iterable().page(100, 100).each(|p| xs.push(p.x));
+    /// assert_eq!(xs.len(), 100);
+    /// assert_eq!(xs.first(), Some(&100.0));
+    /// assert_eq!(xs.last(), Some(&199.0));
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`QueryAPI::count()`]
+    /// * C++ API: `iter_iterable::page`
+    #[doc(alias = "iter_iterable::page")]
+    pub fn page(&mut self, offset: i32, limit: i32) -> &mut Self {
+        ecs_assert!(
+            offset >= 0 && limit >= 0,
+            FlecsErrorCode::InvalidParameter,
+            "page offset and limit must not be negative"
+        );
+        self.page = Some((offset, limit));
+        self
+    }
+
     /// Limit results to tables with specified group id (grouped queries only)
     ///
     /// # Arguments
@@ -63,7 +146,7 @@ where
     ///
     /// # Arguments
     ///
-    /// * `var_id`: the variable id to set
+    /// * `var`: the variable to set, a [`QueryVar`] or variable index
     ///
     /// * `value`: the value to set
     ///
@@ -71,7 +154,8 @@ where
     ///
     /// * C++ API: `iter_iterable::set_var`
     #[doc(alias = "iter_iterable::set_var")]
-    pub fn set_var(&mut self, var_id: i32, value: impl Into<Entity>) -> &mut Self {
+    pub fn set_var(&mut self, var: impl IntoQueryVar, value: impl Into<Entity>) -> &mut Self {
+        let var_id = var.var_index(self.iter.query);
         ecs_assert!(var_id != -1, FlecsErrorCode::InvalidParameter, 0);
         unsafe { sys::ecs_iter_set_var(&mut self.iter, var_id, *value.into()) };
         self
@@ -81,7 +165,7 @@ where
     ///
     /// # Arguments
     ///
-    /// * `var_id`: the variable id to set
+    /// * `var`: the variable to set, a [`QueryVar`] or variable index
     ///
     /// * `range`: the range to set
     ///
@@ -89,7 +173,12 @@ where
     ///
     /// * C++ API: `iter_iterable::set_var`
     #[doc(alias = "iter_iterable::set_var")]
-    pub fn set_var_table(&mut self, var_id: i32, table: impl IntoTableRange) -> &mut Self {
+    pub fn set_var_table(
+        &mut self,
+        var: impl IntoQueryVar,
+        table: impl IntoTableRange,
+    ) -> &mut Self {
+        let var_id = var.var_index(self.iter.query);
         ecs_assert!(var_id != -1, FlecsErrorCode::InvalidParameter, 0);
         unsafe { sys::ecs_iter_set_var_as_range(&mut self.iter, var_id, &table.range_raw()) };
         self
@@ -146,13 +235,53 @@ where
     }
 }
 
+impl<P, T> Drop for QueryIter<'_, P, T>
+where
+    T: QueryTuple,
+{
+    fn drop(&mut self) {
+        for chain in self.page_chains.get_mut().drain(..) {
+            drop(unsafe { Box::from_raw(chain) });
+        }
+    }
+}
+
 #[doc(hidden)]
 impl<'a, P, T> IterOperations for QueryIter<'a, P, T>
 where
     T: QueryTuple,
 {
     fn retrieve_iter(&self) -> sys::ecs_iter_t {
-        self.iter
+        match self.page {
+            Some((offset, limit)) => {
+                let mut chains = self.page_chains.borrow_mut();
+                let finished = chains
+                    .iter()
+                    .copied()
+                    .find(|&chain| unsafe { (*chain).finished });
+                let chain = finished.unwrap_or_else(|| {
+                    let chain = Box::into_raw(Box::new(PageChain {
+                        iter: self.iter,
+                        fini: None,
+                        finished: true,
+                    }));
+                    chains.push(chain);
+                    chain
+                });
+                unsafe {
+                    *chain = PageChain {
+                        iter: sys::ecs_iter_t {
+                            fini: Some(page_chain_fini),
+                            ..self.iter
+                        },
+                        fini: self.iter.fini,
+                        finished: false,
+                    };
+                    sys::ecs_page_iter(&(*chain).iter, offset, limit)
+                }
+            }
+            None => self.iter,
+        }
     }
 
     fn retrieve_iter_stage<'w>(&self, _stage: impl WorldProvider<'w>) -> sys::ecs_iter_t {
@@ -160,7 +289,7 @@ where
     }
 
     fn iter_next(&self, iter: &mut sys::ecs_iter_t) -> bool {
-        unsafe { (self.iter_next)(iter) }
+        unsafe { (self.iter_next_func())(iter) }
     }
 
     fn query_ptr(&self) -> *const sys::ecs_query_t {
@@ -168,7 +297,11 @@ where
     }
 
     fn iter_next_func(&self) -> unsafe extern "C" fn(*mut sys::ecs_iter_t) -> bool {
-        self.iter_next
+        if self.page.is_some() {
+            sys::ecs_page_next
+        } else {
+            self.iter_next
+        }
     }
 }
 
@@ -194,4 +327,4 @@ where
     }
 }
 
-// TODO : worker_iterable and page_iterable not implemented yet
+// TODO : worker_iterable not implemented yet
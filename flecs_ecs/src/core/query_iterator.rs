@@ -0,0 +1,240 @@
+//! Rust [`Iterator`]s over the results of a query.
+
+use std::cell::RefCell;
+use std::ptr::NonNull;
+
+use crate::core::*;
+use crate::sys;
+
+/// Returns whether the query has a mutable field that isn't matched on the iterated entity,
+/// such as a singleton, which is the same component for every result.
+pub(crate) fn has_mutable_shared_field<T: QueryTuple>(query: *const sys::ecs_query_t) -> bool {
+    let query = unsafe { &*query };
+    query.terms[..query.term_count as usize]
+        .iter()
+        .filter(|term| term.field_index < T::COUNT as i16)
+        .any(|term| {
+            let is_this = term.src.id & sys::EcsIsVariable != 0
+                && term.src.id & !(sys::EcsTermRefFlags as u64) == ECS_THIS
+                && term.src.id & sys::EcsUp == 0;
+            let is_write = term.inout == InOutKind::InOut as i16
+                || term.inout == InOutKind::Out as i16
+                || term.inout == InOutKind::Default as i16;
+            !is_this && is_write
+        })
+}
+
+/// The tables locked by an iteration, which are unlocked when the iteration scope ends.
+///
+/// The references handed out by the iterators live until the end of the scope, so tables stay
+/// locked after the iterator moved on to the next result, or was dropped.
+pub(crate) struct TableLocks {
+    world: *mut sys::ecs_world_t,
+    tables: RefCell<Vec<*mut sys::ecs_table_t>>,
+}
+
+impl TableLocks {
+    pub(crate) fn new(world: *mut sys::ecs_world_t) -> Self {
+        Self {
+            world,
+            tables: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn lock(&self, table: *mut sys::ecs_table_t) {
+        unsafe { sys::ecs_table_lock(self.world, table) };
+        self.tables.borrow_mut().push(table);
+    }
+}
+
+impl Drop for TableLocks {
+    fn drop(&mut self) {
+        for table in self.tables.get_mut().drain(..) {
+            unsafe { sys::ecs_table_unlock(self.world, table) };
+        }
+    }
+}
+
+/// The C iterator shared by [`QueryEntityIter`] and [`QueryChunkIter`].
+///
+/// The C iterator is finalized when it is dropped before the last result.
+struct RawQueryIter<'i, T: QueryTuple> {
+    iter: sys::ecs_iter_t,
+    iter_next: unsafe extern "C" fn(*mut sys::ecs_iter_t) -> bool,
+    components: Option<T::Pointers>,
+    locks: &'i TableLocks,
+    done: bool,
+}
+
+impl<'i, T: QueryTuple> RawQueryIter<'i, T> {
+    fn new(
+        mut iter: sys::ecs_iter_t,
+        iter_next: unsafe extern "C" fn(*mut sys::ecs_iter_t) -> bool,
+        locks: &'i TableLocks,
+    ) -> Self {
+        iter.flags |= sys::EcsIterIsInstanced;
+        iter.flags |= sys::EcsIterCppEach;
+        Self {
+            iter,
+            iter_next,
+            components: None,
+            locks,
+            done: false,
+        }
+    }
+
+    /// Advances to the next result.
+    fn next_result(&mut self) -> bool {
+        self.components = None;
+        if self.done {
+            return false;
+        }
+
+        if unsafe { (self.iter_next)(&mut self.iter) } {
+            self.locks.lock(self.iter.table);
+            self.components = Some(T::create_ptrs(&self.iter));
+            true
+        } else {
+            // the iterator is finalized by the C API when it returns false
+            self.done = true;
+            false
+        }
+    }
+
+    /// Returns the number of rows of the current result.
+    fn count(&self) -> usize {
+        if self.iter.count == 0 && self.iter.table.is_null() {
+            1
+        } else {
+            self.iter.count as usize
+        }
+    }
+
+    fn world(&self) -> WorldRef<'i> {
+        unsafe { WorldRef::from_ptr(self.iter.world) }
+    }
+}
+
+impl<T: QueryTuple> Drop for RawQueryIter<'_, T> {
+    fn drop(&mut self) {
+        if !self.done {
+            unsafe { sys::ecs_iter_fini(&mut self.iter) };
+        }
+    }
+}
+
+/// An [`Iterator`] over the entities that match a query and their components.
+///
+/// Passed to the callback of [`QueryAPI::iter()`]. Yields the entity together with the component
+/// tuple of the query, so it can be used with `for` loops and iterator adapters. The references
+/// that are returned can't leave the callback, and the tables they point into stay locked until
+/// the callback returns.
+pub struct QueryEntityIter<'i, T: QueryTuple> {
+    raw: RawQueryIter<'i, T>,
+    row: usize,
+}
+
+impl<'i, T: QueryTuple> Iterator for QueryEntityIter<'i, T> {
+    type Item = (EntityView<'i>, T::TupleType<'i>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(components) = &self.raw.components {
+                if self.row < self.raw.iter.count as usize {
+                    let row = self.row;
+                    self.row += 1;
+                    let entity = unsafe { *self.raw.iter.entities.add(row) };
+                    // rows are handed out once, and mutable fields are matched on the row
+                    let tuple = unsafe { components.get_tuple_unbound(row) };
+                    return Some((EntityView::new_from(self.raw.world(), entity), tuple));
+                }
+            }
+
+            if !self.raw.next_result() {
+                return None;
+            }
+
+            ecs_assert!(
+                self.raw.iter.count > 0,
+                FlecsErrorCode::InvalidOperation,
+                "no entities returned, use chunks() for queries that don't match entities",
+            );
+            self.row = 0;
+        }
+    }
+}
+
+/// A chunk of query results: the entities of a table together with their components as slices.
+///
+/// Fields that are not matched on the iterated entities (such as singletons) are slices with one
+/// element. For queries that don't match entities `entities` is empty.
+pub struct QueryChunk<'i, T: QueryTuple> {
+    /// The entities in the chunk.
+    pub entities: &'i [Entity],
+    /// The components of the entities, as a tuple of slices.
+    pub components: T::TupleSliceType<'i>,
+    /// The table of the chunk, if any.
+    pub table: Option<Table<'i>>,
+}
+
+/// An [`Iterator`] over the results of a query, one chunk per matched table.
+///
+/// Passed to the callback of [`QueryAPI::chunks()`]. Chunks give access to the components of a
+/// table as contiguous slices, which is useful for processing that benefits from SIMD or cache
+/// locality. Like with [`QueryEntityIter`], the chunks can't leave the callback.
+pub struct QueryChunkIter<'i, T: QueryTuple> {
+    raw: RawQueryIter<'i, T>,
+}
+
+impl<'i, T: QueryTuple> Iterator for QueryChunkIter<'i, T> {
+    type Item = QueryChunk<'i, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.raw.next_result() {
+            return None;
+        }
+
+        let iter = &self.raw.iter;
+        let entities = if iter.entities.is_null() {
+            &[][..]
+        } else {
+            unsafe {
+                std::slice::from_raw_parts(iter.entities as *const Entity, iter.count as usize)
+            }
+        };
+        let components = self.raw.components.as_ref()?;
+        let components = unsafe { components.get_slice_unbound(self.raw.count()) };
+        let table = NonNull::new(iter.table).map(|table| Table::new(self.raw.world(), table));
+
+        Some(QueryChunk {
+            entities,
+            components,
+            table,
+        })
+    }
+}
+
+impl<'i, T: QueryTuple> QueryEntityIter<'i, T> {
+    pub(crate) fn new(
+        iter: sys::ecs_iter_t,
+        iter_next: unsafe extern "C" fn(*mut sys::ecs_iter_t) -> bool,
+        locks: &'i TableLocks,
+    ) -> Self {
+        Self {
+            raw: RawQueryIter::new(iter, iter_next, locks),
+            row: 0,
+        }
+    }
+}
+
+impl<'i, T: QueryTuple> QueryChunkIter<'i, T> {
+    pub(crate) fn new(
+        iter: sys::ecs_iter_t,
+        iter_next: unsafe extern "C" fn(*mut sys::ecs_iter_t) -> bool,
+        locks: &'i TableLocks,
+    ) -> Self {
+        Self {
+            raw: RawQueryIter::new(iter, iter_next, locks),
+        }
+    }
+}
@@ -0,0 +1,263 @@
+//! Parallel iteration of query results with worker iterators.
+
+use crate::core::*;
+use crate::sys;
+
+/// Pointer that is handed to the worker threads of a parallel iteration.
+#[derive(Clone, Copy)]
+struct WorkerPtr<T>(*mut T);
+
+// SAFETY: the world is in readonly mode while the workers run, and every worker has its own stage.
+unsafe impl<T> Send for WorkerPtr<T> {}
+unsafe impl<T> Sync for WorkerPtr<T> {}
+
+impl<T> WorkerPtr<T> {
+    // accessed through a method, so closures capture the (Send) wrapper and not the pointer
+    fn get(self) -> *mut T {
+        self.0
+    }
+}
+
+/// Leaves readonly mode and merges the commands of the worker stages, also when a worker panicked.
+struct ParallelScope {
+    world: *mut sys::ecs_world_t,
+    stages: Vec<WorkerPtr<sys::ecs_world_t>>,
+}
+
+impl Drop for ParallelScope {
+    fn drop(&mut self) {
+        unsafe {
+            sys::ecs_readonly_end(self.world);
+            for stage in self.stages.drain(..) {
+                sys::ecs_merge(stage.get());
+                sys::ecs_stage_free(stage.get());
+            }
+        }
+    }
+}
+
+/// Returns the number of threads used for parallel iteration.
+#[cfg(feature = "rayon")]
+fn worker_count(_world: *mut sys::ecs_world_t) -> usize {
+    rayon::current_num_threads()
+}
+
+/// Returns the number of threads used for parallel iteration.
+#[cfg(not(feature = "rayon"))]
+fn worker_count(world: *mut sys::ecs_world_t) -> usize {
+    let stage_count = unsafe { sys::ecs_get_stage_count(world) } as usize;
+    if stage_count > 1 {
+        stage_count
+    } else {
+        std::thread::available_parallelism().map_or(1, |count| count.get())
+    }
+}
+
+/// Runs `worker` for every worker index.
+#[cfg(feature = "rayon")]
+fn run_workers(count: usize, worker: impl Fn(usize) + Sync) {
+    let worker = &worker;
+    rayon::scope(|scope| {
+        for index in 0..count {
+            scope.spawn(move |_| worker(index));
+        }
+    });
+}
+
+/// Runs `worker` for every worker index.
+#[cfg(not(feature = "rayon"))]
+fn run_workers(count: usize, worker: impl Fn(usize) + Sync) {
+    let worker = &worker;
+    std::thread::scope(|scope| {
+        for index in 1..count {
+            scope.spawn(move || worker(index));
+        }
+        worker(0);
+    });
+}
+
+impl<T> Query<T>
+where
+    T: QueryTuple,
+{
+    /// Splits the results of the query over worker threads with `ecs_worker_iter`, and invokes
+    /// `func` with the worker iterator of each result.
+    fn par_iter(&self, func: impl Fn(WorldRef, &sys::ecs_iter_t) + Sync) {
+        let world = self.world_ptr_mut();
+        let query = self.query.as_ptr();
+
+        ecs_assert!(
+            unsafe { !sys::ecs_stage_is_readonly(world) && !sys::ecs_is_deferred(world) },
+            FlecsErrorCode::InvalidOperation,
+            "parallel iteration requires that the world is not readonly or deferred"
+        );
+
+        assert!(
+            !has_mutable_shared_field::<T>(query),
+            "parallel iteration requires that fields which are not matched on the iterated entity are immutable, as they are shared between threads"
+        );
+
+        let count = worker_count(world).max(1);
+        let stages: Vec<_> = (0..count)
+            .map(|_| WorkerPtr(unsafe { sys::ecs_stage_new(world) }))
+            .collect();
+
+        unsafe { sys::ecs_readonly_begin(world, true) };
+        let scope = ParallelScope {
+            world,
+            stages: stages.clone(),
+        };
+
+        let query = WorkerPtr(query);
+        run_workers(count, |index| unsafe {
+            let stage = stages[index].get();
+            let mut iter = sys::ecs_query_iter(stage, query.get());
+            iter.flags |= sys::EcsIterIsInstanced;
+            iter.flags |= sys::EcsIterCppEach;
+
+            let mut worker_iter = sys::ecs_worker_iter(&iter, index as i32, count as i32);
+            while sys::ecs_worker_next(&mut worker_iter) {
+                func(WorldRef::from_ptr(stage), &worker_iter);
+            }
+        });
+
+        drop(scope);
+    }
+
+    /// Parallel each iterator.
+    ///
+    /// Works like [`QueryAPI::each()`], but splits the matched entities of every table over
+    /// multiple threads with a worker iterator. Each entity is visited by exactly one thread, so
+    /// mutable access to components of the iterated entities is sound. Fields that are shared
+    /// between entities, such as singletons or components of parents, must be accessed immutably.
+    ///
+    /// With the `rayon` feature the threads of the global rayon pool are used. Otherwise scoped
+    /// threads are spawned, one per world stage (see [`World::set_threads()`]) or one per
+    /// available CPU.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query has mutable fields that are not matched on the iterated entity.
+    /// The world must not be readonly or deferred, which means this can't be used in systems.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    ///
+    /// #[derive(Component)]
+    /// struct Velocity {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    ///
+    /// let world = World::new();
+    ///
+    /// for _ in 0..1000 {
+    ///     world
+    ///         .entity()
+    ///         .set(Position { x: 0.0, y: 0.0 })
+    ///         .set(Velocity { x: 1.0, y: 2.0 });
+    /// }
+    ///
+    /// let query = world.new_query::<(&mut Position, &Velocity)>();
+    /// query.par_each(|(p, v)| {
+    ///     p.x += v.x;
+    ///     p.y += v.y;
+    /// });
+    ///
+    /// query.each(|(p, _)| assert_eq!(p.x, 1.0));
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`Query::par_each_entity()`]
+    /// * C++ API: `iterable::worker_iter`
+    #[doc(alias = "iterable::worker_iter")]
+    pub fn par_each(&self, func: impl Fn(T::TupleType<'_>) + Send + Sync)
+    where
+        for<'x> T::TupleType<'x>: Send,
+    {
+        self.par_iter(|_, iter| {
+            let mut components_data = T::create_ptrs(iter);
+            let iter_count = if iter.count == 0 && iter.table.is_null() {
+                1_usize
+            } else {
+                iter.count as usize
+            };
+
+            for i in 0..iter_count {
+                func(components_data.get_tuple(i));
+            }
+        });
+    }
+
+    /// Parallel each iterator that also provides the entity.
+    ///
+    /// The entity is bound to the stage of the worker thread, so operations on it, such as
+    /// adding or removing components, are deferred and applied after all threads are done.
+    /// Components can't be registered while the threads run, so components that are added in
+    /// `func` must be registered beforehand.
+    ///
+    /// # Panics
+    ///
+    /// See [`Query::par_each()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Health(i32);
+    ///
+    /// #[derive(Component)]
+    /// struct Dead;
+    ///
+    /// let world = World::new();
+    /// world.component::<Dead>();
+    ///
+    /// for i in 0..100 {
+    ///     world.entity().set(Health(i % 2));
+    /// }
+    ///
+    /// world.new_query::<&Health>().par_each_entity(|e, health| {
+    ///     if health.0 == 0 {
+    ///         e.add::<Dead>();
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(world.count::<Dead>(), 50);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`Query::par_each()`]
+    /// * C++ API: `iterable::worker_iter`
+    #[doc(alias = "iterable::worker_iter")]
+    pub fn par_each_entity(&self, func: impl Fn(EntityView, T::TupleType<'_>) + Send + Sync)
+    where
+        for<'x> T::TupleType<'x>: Send,
+    {
+        self.par_iter(|stage, iter| {
+            let mut components_data = T::create_ptrs(iter);
+
+            ecs_assert!(
+                iter.count > 0,
+                FlecsErrorCode::InvalidOperation,
+                "no entities returned, use par_each() without flecs::entity argument",
+            );
+
+            for i in 0..iter.count as usize {
+                let entity = EntityView::new_from(stage, unsafe { *iter.entities.add(i) });
+                func(entity, components_data.get_tuple(i));
+            }
+        });
+    }
+}
@@ -12,6 +12,7 @@ pub struct ArrayElement {
 pub struct ComponentsData<T: QueryTuple, const LEN: usize> {
     pub array_components: [*mut u8; LEN],
     pub is_ref_array_components: [bool; LEN],
+    pub variant_array_components: [u8; LEN],
     pub is_any_array_a_ref: bool,
     _marker: PhantomData<T>,
 }
@@ -22,22 +23,42 @@ pub trait ComponentPointers<T: QueryTuple> {
     fn get_tuple(&mut self, index: usize) -> T::TupleType<'_>;
 
     fn get_slice(&mut self, count: usize) -> T::TupleSliceType<'_>;
+
+    /// Like [`ComponentPointers::get_tuple()`], but the lifetime of the references is chosen
+    /// by the caller.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the component storage outlives `'w`, and that no other
+    /// mutable references to the same components exist.
+    unsafe fn get_tuple_unbound<'w>(&self, index: usize) -> T::TupleType<'w>;
+
+    /// Like [`ComponentPointers::get_slice()`], but the lifetime of the slices is chosen by
+    /// the caller.
+    ///
+    /// # Safety
+    ///
+    /// See [`ComponentPointers::get_tuple_unbound()`].
+    unsafe fn get_slice_unbound<'w>(&self, count: usize) -> T::TupleSliceType<'w>;
 }
 
 impl<T: QueryTuple, const LEN: usize> ComponentPointers<T> for ComponentsData<T, LEN> {
     fn new(iter: &sys::ecs_iter_t) -> Self {
         let mut array_components = [std::ptr::null::<u8>() as *mut u8; LEN];
         let mut is_ref_array_components = [false; LEN];
+        let mut variant_array_components = [0; LEN];
 
         let is_any_array_a_ref = T::populate_array_ptrs(
             iter,
             &mut array_components[..],
             &mut is_ref_array_components[..],
+            &mut variant_array_components[..],
         );
 
         Self {
             array_components,
             is_ref_array_components,
+            variant_array_components,
             is_any_array_a_ref,
             _marker: PhantomData::<T>,
         }
@@ -48,10 +69,15 @@ impl<T: QueryTuple, const LEN: usize> ComponentPointers<T> for ComponentsData<T,
             T::create_tuple_with_ref(
                 &self.array_components[..],
                 &self.is_ref_array_components[..],
+                &self.variant_array_components[..],
                 index,
             )
         } else {
-            T::create_tuple(&self.array_components[..], index)
+            T::create_tuple(
+                &self.array_components[..],
+                &self.variant_array_components[..],
+                index,
+            )
         }
     }
 
@@ -60,10 +86,46 @@ impl<T: QueryTuple, const LEN: usize> ComponentPointers<T> for ComponentsData<T,
             T::create_tuple_slices_with_ref(
                 &self.array_components[..],
                 &self.is_ref_array_components[..],
+                &self.variant_array_components[..],
+                count,
+            )
+        } else {
+            T::create_tuple_slices(
+                &self.array_components[..],
+                &self.variant_array_components[..],
+                count,
+            )
+        }
+    }
+
+    unsafe fn get_tuple_unbound<'w>(&self, index: usize) -> T::TupleType<'w> {
+        // the returned references point into the component storage, not into the arrays
+        let array_components = std::slice::from_raw_parts(self.array_components.as_ptr(), LEN);
+        let variants = &self.variant_array_components[..];
+        if self.is_any_array_a_ref {
+            T::create_tuple_with_ref(
+                array_components,
+                &self.is_ref_array_components[..],
+                variants,
+                index,
+            )
+        } else {
+            T::create_tuple(array_components, variants, index)
+        }
+    }
+
+    unsafe fn get_slice_unbound<'w>(&self, count: usize) -> T::TupleSliceType<'w> {
+        let array_components = std::slice::from_raw_parts(self.array_components.as_ptr(), LEN);
+        let variants = &self.variant_array_components[..];
+        if self.is_any_array_a_ref {
+            T::create_tuple_slices_with_ref(
+                array_components,
+                &self.is_ref_array_components[..],
+                variants,
                 count,
             )
         } else {
-            T::create_tuple_slices(&self.array_components[..], count)
+            T::create_tuple_slices(array_components, variants, count)
         }
     }
 }
@@ -77,25 +139,96 @@ pub trait IterableTypeOperation {
     type OnlyType: ComponentOrPairId;
     type OnlyPairType: ComponentId;
     const ONE: i32 = 1;
+    /// The number of terms the field adds to the query, more than one for [`Either`] fields.
+    const TERM_COUNT: i32 = 1;
+    /// The number of components the field can match, more than one for [`Either`] fields.
+    const VARIANT_COUNT: u8 = 1;
 
     fn populate_term(term: &mut sys::ecs_term_t);
-    fn create_tuple_data<'a>(array_components_data: *mut u8, index: usize) -> Self::ActualType<'a>;
+
+    /// Adds the terms of the field to the query.
+    fn populate_terms<'a>(query: &mut impl QueryBuilderImpl<'a>) {
+        let id = <Self::OnlyType as ComponentOrPairId>::get_id(query.world());
+        assert_no_union_data(query, id);
+        query.with_id(id);
+        let term = query.current_term_mut();
+        Self::populate_term(term);
+    }
+
+    /// Writes the terms of the field to `terms`, starting at `index`.
+    fn register_terms_at(world: WorldRef, terms: &mut [sys::ecs_term_t], index: &mut usize) {
+        terms[*index].id = <Self::OnlyType as ComponentOrPairId>::get_id(world);
+        Self::populate_term(&mut terms[*index]);
+        *index += 1;
+    }
+
+    /// Returns which component of the field matched the (component) id `id`.
+    fn variant_of(world: WorldRef, id: sys::ecs_id_t) -> Option<u8> {
+        let pattern = <Self::OnlyType as ComponentOrPairId>::get_id(world);
+        unsafe { sys::ecs_id_match(id, pattern) }.then_some(0)
+    }
+
+    /// Returns the data of the field at `index` in the current result, and which component of
+    /// the field was matched.
+    fn field_data(it: &sys::ecs_iter_t, index: i32) -> (*mut u8, u8) {
+        let data = unsafe { ecs_field::<Self::OnlyPairType>(it, index) as *mut u8 };
+        (data, 0)
+    }
+
+    fn create_tuple_data<'a>(
+        array_components_data: *mut u8,
+        variant: u8,
+        index: usize,
+    ) -> Self::ActualType<'a>;
     fn create_tuple_with_ref_data<'a>(
         array_components_data: *mut u8,
         is_ref: bool,
+        variant: u8,
         index: usize,
     ) -> Self::ActualType<'a>;
     fn create_tuple_slice_data<'a>(
         array_components_data: *mut u8,
+        variant: u8,
         count: usize,
     ) -> Self::SliceType<'a>;
     fn create_tuple_slices_with_ref_data<'a>(
         array_components_data: *mut u8,
         is_ref_array_components: bool,
+        variant: u8,
         count: usize,
     ) -> Self::SliceType<'a>;
 }
 
+/// Asserts that `id` is not a union relationship with data, which must be added with `with`.
+#[allow(unused_variables)]
+fn assert_no_union_data<'a>(query: &impl QueryBuilderImpl<'a>, id: sys::ecs_id_t) {
+    ecs_assert!(
+        {
+            if (id & (sys::ECS_ID_FLAGS_MASK as u64)) == 0 {
+                let ti = unsafe { sys::ecs_get_type_info(query.world_ptr(), id) };
+                if !ti.is_null() {
+                    // Union relationships always return a value of type
+                    // flecs::entity_t which holds the target id of the
+                    // union relationship.
+                    // If a union component with a non-zero size (like an
+                    // enum) is added to the query signature, the each/iter
+                    // functions would accept a parameter of the component
+                    // type instead of flecs::entity_t, which would cause
+                    // an assert.
+                    (unsafe { (*ti).size == 0 }
+                        || !unsafe { sys::ecs_has_id(query.world_ptr(), id, *flecs::Union) })
+                } else {
+                    true
+                }
+            } else {
+                true
+            }
+        },
+        FlecsErrorCode::InvalidParameter,
+        "use `with` method to add union relationship"
+    );
+}
+
 impl<T> IterableTypeOperation for &T
 where
     T: ComponentOrPairId,
@@ -110,7 +243,11 @@ where
         term.inout = InOutKind::In as i16;
     }
 
-    fn create_tuple_data<'a>(array_components_data: *mut u8, index: usize) -> Self::ActualType<'a> {
+    fn create_tuple_data<'a>(
+        array_components_data: *mut u8,
+        _variant: u8,
+        index: usize,
+    ) -> Self::ActualType<'a> {
         let data_ptr = array_components_data as Self::CastType;
         unsafe { &*data_ptr.add(index) }
     }
@@ -118,6 +255,7 @@ where
     fn create_tuple_with_ref_data<'a>(
         array_components_data: *mut u8,
         is_ref: bool,
+        _variant: u8,
         index: usize,
     ) -> Self::ActualType<'a> {
         let data_ptr = array_components_data as Self::CastType;
@@ -132,6 +270,7 @@ where
 
     fn create_tuple_slice_data<'a>(
         array_components_data: *mut u8,
+        _variant: u8,
         count: usize,
     ) -> Self::SliceType<'a> {
         let data_ptr = array_components_data as Self::CastType;
@@ -141,6 +280,7 @@ where
     fn create_tuple_slices_with_ref_data<'a>(
         array_components_data: *mut u8,
         is_ref_array_components: bool,
+        _variant: u8,
         count: usize,
     ) -> Self::SliceType<'a> {
         let data_ptr = array_components_data as Self::CastType;
@@ -168,7 +308,11 @@ where
         term.inout = InOutKind::InOut as i16;
     }
 
-    fn create_tuple_data<'a>(array_components_data: *mut u8, index: usize) -> Self::ActualType<'a> {
+    fn create_tuple_data<'a>(
+        array_components_data: *mut u8,
+        _variant: u8,
+        index: usize,
+    ) -> Self::ActualType<'a> {
         let data_ptr = array_components_data as Self::CastType;
         unsafe { &mut *data_ptr.add(index) }
     }
@@ -176,6 +320,7 @@ where
     fn create_tuple_with_ref_data<'a>(
         array_components_data: *mut u8,
         is_ref: bool,
+        _variant: u8,
         index: usize,
     ) -> Self::ActualType<'a> {
         let data_ptr = array_components_data as Self::CastType;
@@ -190,6 +335,7 @@ where
 
     fn create_tuple_slice_data<'a>(
         array_components_data: *mut u8,
+        _variant: u8,
         count: usize,
     ) -> Self::SliceType<'a> {
         let data_ptr = array_components_data as Self::CastType;
@@ -199,6 +345,7 @@ where
     fn create_tuple_slices_with_ref_data<'a>(
         array_components_data: *mut u8,
         is_ref_array_components: bool,
+        _variant: u8,
         count: usize,
     ) -> Self::SliceType<'a> {
         let data_ptr = array_components_data as Self::CastType;
@@ -227,7 +374,11 @@ where
         term.oper = OperKind::Optional as i16;
     }
 
-    fn create_tuple_data<'a>(array_components_data: *mut u8, index: usize) -> Self::ActualType<'a> {
+    fn create_tuple_data<'a>(
+        array_components_data: *mut u8,
+        _variant: u8,
+        index: usize,
+    ) -> Self::ActualType<'a> {
         let data_ptr = array_components_data as Self::CastType;
         if data_ptr.is_null() {
             None
@@ -239,6 +390,7 @@ where
     fn create_tuple_with_ref_data<'a>(
         array_components_data: *mut u8,
         is_ref: bool,
+        _variant: u8,
         index: usize,
     ) -> Self::ActualType<'a> {
         let data_ptr = array_components_data as Self::CastType;
@@ -253,6 +405,7 @@ where
 
     fn create_tuple_slice_data<'a>(
         array_components_data: *mut u8,
+        _variant: u8,
         count: usize,
     ) -> Self::SliceType<'a> {
         let data_ptr = array_components_data as Self::CastType;
@@ -266,6 +419,7 @@ where
     fn create_tuple_slices_with_ref_data<'a>(
         array_components_data: *mut u8,
         is_ref_array_components: bool,
+        _variant: u8,
         count: usize,
     ) -> Self::SliceType<'a> {
         let data_ptr = array_components_data as Self::CastType;
@@ -294,7 +448,11 @@ where
         term.oper = OperKind::Optional as i16;
     }
 
-    fn create_tuple_data<'a>(array_components_data: *mut u8, index: usize) -> Self::ActualType<'a> {
+    fn create_tuple_data<'a>(
+        array_components_data: *mut u8,
+        _variant: u8,
+        index: usize,
+    ) -> Self::ActualType<'a> {
         let data_ptr = array_components_data as Self::CastType;
         if data_ptr.is_null() {
             None
@@ -306,6 +464,7 @@ where
     fn create_tuple_with_ref_data<'a>(
         array_components_data: *mut u8,
         is_ref: bool,
+        _variant: u8,
         index: usize,
     ) -> Self::ActualType<'a> {
         let data_ptr = array_components_data as Self::CastType;
@@ -320,6 +479,7 @@ where
 
     fn create_tuple_slice_data<'a>(
         array_components_data: *mut u8,
+        _variant: u8,
         count: usize,
     ) -> Self::SliceType<'a> {
         let data_ptr = array_components_data as Self::CastType;
@@ -333,6 +493,7 @@ where
     fn create_tuple_slices_with_ref_data<'a>(
         array_components_data: *mut u8,
         is_ref_array_components: bool,
+        _variant: u8,
         count: usize,
     ) -> Self::SliceType<'a> {
         let data_ptr = array_components_data as Self::CastType;
@@ -351,6 +512,9 @@ pub trait QueryTuple: Sized {
     type TupleType<'a>;
     type TupleSliceType<'a>;
     const COUNT: i32;
+    /// The number of terms of the tuple, which is larger than [`QueryTuple::COUNT`] when the
+    /// tuple contains [`Either`] fields.
+    const TERM_COUNT: i32;
 
     fn create_ptrs(iter: &sys::ecs_iter_t) -> Self::Pointers {
         Self::Pointers::new(iter)
@@ -372,21 +536,32 @@ pub trait QueryTuple: Sized {
         it: &sys::ecs_iter_t,
         components: &mut [*mut u8],
         is_ref: &mut [bool],
+        variants: &mut [u8],
     ) -> bool;
 
-    fn create_tuple(array_components: &[*mut u8], index: usize) -> Self::TupleType<'_>;
+    fn create_tuple<'a>(
+        array_components: &'a [*mut u8],
+        variants: &[u8],
+        index: usize,
+    ) -> Self::TupleType<'a>;
 
     fn create_tuple_with_ref<'a>(
         array_components: &'a [*mut u8],
         is_ref_array_components: &[bool],
+        variants: &[u8],
         index: usize,
     ) -> Self::TupleType<'a>;
 
-    fn create_tuple_slices(array_components: &[*mut u8], count: usize) -> Self::TupleSliceType<'_>;
+    fn create_tuple_slices<'a>(
+        array_components: &'a [*mut u8],
+        variants: &[u8],
+        count: usize,
+    ) -> Self::TupleSliceType<'a>;
 
     fn create_tuple_slices_with_ref<'a>(
         array_components: &'a [*mut u8],
         is_ref_array_components: &[bool],
+        variants: &[u8],
         count: usize,
     ) -> Self::TupleSliceType<'a>;
 }
@@ -395,41 +570,18 @@ pub trait QueryTuple: Sized {
 // The higher sized tuples are done by a macro towards the bottom of this file.
 /////////////////////
 
-#[rustfmt::skip]
 impl<A> QueryTuple for A
 where
     A: IterableTypeOperation,
-{ 
+{
     type Pointers = ComponentsData<A, 1>;
     type TupleType<'w> = A::ActualType<'w>;
     type TupleSliceType<'w> = A::SliceType<'w>;
-    const COUNT : i32 = 1;
+    const COUNT: i32 = 1;
+    const TERM_COUNT: i32 = A::TERM_COUNT;
 
     fn populate<'a>(query: &mut impl QueryBuilderImpl<'a>) {
-        let id = <A::OnlyType as ComponentOrPairId>::get_id(query.world());
-
-        ecs_assert!(
-        {
-            if (id & (sys::ECS_ID_FLAGS_MASK as u64)) == 0 {
-                let ti =  unsafe { sys::ecs_get_type_info(query.world_ptr(), id) };
-                if !ti.is_null() {
-                    // Union relationships always return a value of type
-                    // flecs::entity_t which holds the target id of the 
-                    // union relationship.
-                    // If a union component with a non-zero size (like an 
-                    // enum) is added to the query signature, the each/iter
-                    // functions would accept a parameter of the component
-                    // type instead of flecs::entity_t, which would cause
-                    // an assert.
-                    (unsafe { (*ti).size == 0 } || !unsafe { sys::ecs_has_id(query.world_ptr(), id, *flecs::Union)})
-                } else { true }
-            } else { true }
-        }, FlecsErrorCode::InvalidParameter, "use `with` method to add union relationship");
-        
-        query.with_id(id);
-        let term = query.current_term_mut();
-        A::populate_term(term);
-
+        A::populate_terms(query);
     }
 
     #[allow(clippy::not_unsafe_ptr_arg_deref)]
@@ -439,18 +591,16 @@ where
         index: &mut usize,
     ) {
         let world = unsafe { WorldRef::from_ptr(world) };
-        terms[*index].id = <A::OnlyType as ComponentOrPairId>::get_id(world);
-        A::populate_term(&mut terms[*index]);
-        *index += 1;
+        A::register_terms_at(world, terms, index);
     }
 
     fn populate_array_ptrs(
         it: &sys::ecs_iter_t,
         components: &mut [*mut u8],
         is_ref: &mut [bool],
+        variants: &mut [u8],
     ) -> bool {
-        components[0] =
-            unsafe { ecs_field::<A::OnlyPairType>(it, 0) as *mut u8 };
+        (components[0], variants[0]) = A::field_data(it, 0);
         is_ref[0] = if !it.sources.is_null() {
             unsafe { *it.sources.add(0) != 0 }
         } else {
@@ -459,9 +609,12 @@ where
         is_ref[0]
     }
 
-    fn create_tuple(array_components: &[*mut u8], index: usize) -> Self::TupleType<'_> {
-        A::create_tuple_data(array_components[0], index)
-
+    fn create_tuple<'a>(
+        array_components: &'a [*mut u8],
+        variants: &[u8],
+        index: usize,
+    ) -> Self::TupleType<'a> {
+        A::create_tuple_data(array_components[0], variants[0], index)
     }
 
     // TODO since it's only one component, we don't need to check if it's a ref array or not, we can just return the first element of the array
@@ -469,26 +622,35 @@ where
     fn create_tuple_with_ref<'a>(
         array_components: &'a [*mut u8],
         is_ref_array_components: &[bool],
-        index: usize
+        variants: &[u8],
+        index: usize,
     ) -> Self::TupleType<'a> {
-        A::create_tuple_with_ref_data(array_components[0], is_ref_array_components[0], index)
+        A::create_tuple_with_ref_data(
+            array_components[0],
+            is_ref_array_components[0],
+            variants[0],
+            index,
+        )
     }
 
-    fn create_tuple_slices(
-        array_components: &[*mut u8],
+    fn create_tuple_slices<'a>(
+        array_components: &'a [*mut u8],
+        variants: &[u8],
         count: usize,
-    ) -> Self::TupleSliceType<'_> {
-        A::create_tuple_slice_data(array_components[0], count)
+    ) -> Self::TupleSliceType<'a> {
+        A::create_tuple_slice_data(array_components[0], variants[0], count)
     }
 
     fn create_tuple_slices_with_ref<'a>(
         array_components: &'a [*mut u8],
         is_ref_array_components: &[bool],
+        variants: &[u8],
         count: usize,
     ) -> Self::TupleSliceType<'a> {
         A::create_tuple_slices_with_ref_data(
             array_components[0],
             is_ref_array_components[0],
+            variants[0],
             count,
         )
     }
@@ -640,36 +802,11 @@ macro_rules! impl_iterable {
             )*);
             type Pointers = ComponentsData<Self, { tuple_count!($($t),*) }>;
             const COUNT : i32 = tuple_count!($($t),*);
+            const TERM_COUNT : i32 = 0 $( + $t::TERM_COUNT )*;
 
+            #[allow(unused)]
             fn populate<'a>(query: &mut impl QueryBuilderImpl<'a>) {
-                let _world = query.world();
-
-                $(
-                    let id = <$t::OnlyType as ComponentOrPairId>::get_id(_world);
-
-                    ecs_assert!(
-                    {
-                        if (id & (sys::ECS_ID_FLAGS_MASK as u64)) == 0 {
-                            let ti =  unsafe { sys::ecs_get_type_info(query.world_ptr(), id) };
-                            if !ti.is_null() {
-                                // Union relationships always return a value of type
-                                // flecs::entity_t which holds the target id of the
-                                // union relationship.
-                                // If a union component with a non-zero size (like an
-                                // enum) is added to the query signature, the each/iter
-                                // functions would accept a parameter of the component
-                                // type instead of flecs::entity_t, which would cause
-                                // an assert.
-                                (unsafe { (*ti).size == 0 } || !unsafe { sys::ecs_has_id(query.world_ptr(), id, *flecs::Union)})
-                            } else { true }
-                        } else { true }
-                    }, FlecsErrorCode::InvalidParameter, "use `with` method to add union relationship");
-
-                    query.with_id(id);
-                    let term = query.current_term_mut();
-                    $t::populate_term(term);
-
-                )*
+                $( $t::populate_terms(query); )*
             }
 
             #[allow(unused)]
@@ -682,12 +819,13 @@ macro_rules! impl_iterable {
                 it: &sys::ecs_iter_t,
                 components: &mut [*mut u8],
                 is_ref: &mut [bool],
+                variants: &mut [u8],
             ) -> bool {
                 let mut index = 0;
                 let mut any_ref = false;
                 $(
-                    components[index as usize] =
-                    unsafe { ecs_field::<$t::OnlyPairType>(it, index) as *mut u8 };
+                    (components[index as usize], variants[index as usize]) =
+                        $t::field_data(it, index);
                     is_ref[index as usize] = if !it.sources.is_null() {
                         unsafe { *it.sources.add(index as usize) != 0 }
                     } else {
@@ -700,32 +838,33 @@ macro_rules! impl_iterable {
             }
 
             #[allow(unused, clippy::unused_unit)]
-            fn create_tuple(array_components: &[*mut u8], index: usize) -> Self::TupleType<'_> {
+            fn create_tuple<'a>(array_components: &'a [*mut u8], variants: &[u8], index: usize) -> Self::TupleType<'a> {
                 let mut column: isize = -1;
                 ($({
                     column += 1;
-                    $t::create_tuple_data(array_components[column as usize], index)
+                    $t::create_tuple_data(array_components[column as usize], variants[column as usize], index)
                 },)*)
             }
 
             #[allow(unused, clippy::unused_unit)]
-            fn create_tuple_with_ref<'a>(array_components: &'a [*mut u8], is_ref_array_components: &[bool], index: usize) -> Self::TupleType<'a> {
+            fn create_tuple_with_ref<'a>(array_components: &'a [*mut u8], is_ref_array_components: &[bool], variants: &[u8], index: usize) -> Self::TupleType<'a> {
                 let mut column: isize = -1;
                 ($({
                     column += 1;
-                    $t::create_tuple_with_ref_data(array_components[column as usize], is_ref_array_components[column as usize], index)
+                    $t::create_tuple_with_ref_data(array_components[column as usize], is_ref_array_components[column as usize], variants[column as usize], index)
                 },)*)
             }
 
             #[allow(unused, clippy::unused_unit)]
-            fn create_tuple_slices(
-                array_components: &[*mut u8],
+            fn create_tuple_slices<'a>(
+                array_components: &'a [*mut u8],
+                variants: &[u8],
                 count: usize,
-            ) -> Self::TupleSliceType<'_> {
+            ) -> Self::TupleSliceType<'a> {
                 let mut column: isize = -1;
                 ($({
                     column += 1;
-                    $t::create_tuple_slice_data(array_components[column as usize], count)
+                    $t::create_tuple_slice_data(array_components[column as usize], variants[column as usize], count)
                 },)*)
             }
 
@@ -733,12 +872,13 @@ macro_rules! impl_iterable {
             fn create_tuple_slices_with_ref<'a>(
                 array_components: &'a [*mut u8],
                 is_ref_array_components: &[bool],
+                variants: &[u8],
                 count: usize,
             ) -> Self::TupleSliceType<'a> {
                 let mut column: isize = -1;
                 ($({
                     column += 1;
-                    $t::create_tuple_slices_with_ref_data(array_components[column as usize], is_ref_array_components[column as usize], count)
+                    $t::create_tuple_slices_with_ref_data(array_components[column as usize], is_ref_array_components[column as usize], variants[column as usize], count)
                 },)*)
             }
         }
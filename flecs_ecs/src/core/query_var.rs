@@ -0,0 +1,184 @@
+//! Typed handles for query variables.
+
+use crate::core::*;
+use crate::sys;
+
+/// A handle to a variable of a query, such as `$planet`.
+///
+/// Handles are returned by [`QueryBuilder::build_with_vars()`] for the variables declared with
+/// [`QueryBuilder::with_var()`], or looked up with [`QueryAPI::query_var()`]. They can be passed
+/// anywhere a variable index is accepted, such as [`QueryIter::set_var()`] and [`TableIter::var()`],
+/// and only resolve for the query they were created for.
+///
+/// # Example
+///
+/// ```
+/// use flecs_ecs::prelude::*;
+///
+/// #[derive(Component)]
+/// struct SpaceShip;
+///
+/// #[derive(Component)]
+/// struct Planet;
+///
+/// #[derive(Component)]
+/// struct DockedTo;
+///
+/// let world = World::new();
+///
+/// let earth = world.entity().add::<Planet>();
+/// let mars = world.entity().add::<Planet>();
+/// let ship = world.entity().add::<SpaceShip>().add_first::<DockedTo>(earth);
+/// world.entity().add::<SpaceShip>().add_first::<DockedTo>(mars);
+///
+/// let (query, [planet]) = world
+///     .query::<&SpaceShip>()
+///     .with::<DockedTo>()
+///     .set_second_name("$planet")
+///     .with::<Planet>()
+///     .set_src_name("$planet")
+///     .with_var("$planet")
+///     .build_with_vars()
+///     .unwrap();
+///
+/// let mut count = 0;
+/// query.iterable().set_var(planet, earth).run(|mut it| {
+///     while it.next() {
+///         assert_eq!(it.var(planet), earth);
+///         assert_eq!(it.entity(0), ship);
+///         count += it.count();
+///     }
+/// });
+/// assert_eq!(count, 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QueryVar {
+    index: i32,
+    query: usize,
+}
+
+impl QueryVar {
+    /// Looks up the variable `name` in `query`. A leading `$` is ignored.
+    pub(crate) fn find(query: *const sys::ecs_query_t, name: &str) -> Option<Self> {
+        let name = compact_str::format_compact!("{}\0", name.trim_start_matches('$'));
+        let index = unsafe { sys::ecs_query_find_var(query, name.as_ptr() as *const _) };
+        (index != -1).then_some(Self {
+            index,
+            query: query as usize,
+        })
+    }
+
+    /// Returns the index of the variable in the query.
+    pub fn index(&self) -> i32 {
+        self.index
+    }
+}
+
+/// Types that identify a query variable: a [`QueryVar`] handle or a raw `i32` variable index.
+pub trait IntoQueryVar {
+    #[doc(hidden)]
+    fn var_index(&self, query: *const sys::ecs_query_t) -> i32;
+}
+
+impl IntoQueryVar for i32 {
+    #[inline]
+    fn var_index(&self, _query: *const sys::ecs_query_t) -> i32 {
+        *self
+    }
+}
+
+impl IntoQueryVar for QueryVar {
+    #[inline]
+    fn var_index(&self, query: *const sys::ecs_query_t) -> i32 {
+        ecs_assert!(
+            self.query == query as usize,
+            FlecsErrorCode::InvalidParameter,
+            "query variable belongs to a different query"
+        );
+        self.index
+    }
+}
+
+/// Error returned by [`QueryBuilder::build_with_vars()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryVarError {
+    /// A variable declared with [`QueryBuilder::with_var()`] is not used by the query.
+    Unknown {
+        /// The name of the variable.
+        name: String,
+    },
+    /// The number of requested handles differs from the number of declared variables.
+    CountMismatch {
+        /// The number of variables declared with [`QueryBuilder::with_var()`].
+        declared: usize,
+        /// The number of handles requested.
+        requested: usize,
+    },
+}
+
+impl std::fmt::Display for QueryVarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryVarError::Unknown { name } => {
+                write!(f, "query has no variable named `${name}`")
+            }
+            QueryVarError::CountMismatch {
+                declared,
+                requested,
+            } => write!(
+                f,
+                "{declared} query variables were declared, but {requested} were requested"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for QueryVarError {}
+
+impl<'a, T> QueryBuilder<'a, T>
+where
+    T: QueryTuple,
+{
+    /// Declares a variable for which [`QueryBuilder::build_with_vars()`] returns a [`QueryVar`].
+    ///
+    /// The variable must be used by the terms of the query, for example with `set_src_name`
+    /// or `set_second_name`. A leading `$` is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the variable.
+    pub fn with_var(&mut self, name: &str) -> &mut Self {
+        self.vars.push(name.trim_start_matches('$').to_string());
+        self
+    }
+
+    /// Builds the query and returns the handles of the variables declared with
+    /// [`QueryBuilder::with_var()`], in the order they were declared.
+    ///
+    /// # Returns
+    ///
+    /// An error when a declared variable isn't used by the query, or when `N` differs from the
+    /// number of declared variables.
+    pub fn build_with_vars<const N: usize>(
+        &mut self,
+    ) -> Result<(Query<T>, [QueryVar; N]), QueryVarError> {
+        let names = std::mem::take(&mut self.vars);
+        if names.len() != N {
+            return Err(QueryVarError::CountMismatch {
+                declared: names.len(),
+                requested: N,
+            });
+        }
+
+        let query = self.build();
+        let query_ptr = query.query_ptr();
+        let mut vars = [QueryVar {
+            index: -1,
+            query: query_ptr as usize,
+        }; N];
+        for (var, name) in vars.iter_mut().zip(names) {
+            *var = QueryVar::find(query_ptr, &name).ok_or(QueryVarError::Unknown { name })?;
+        }
+        Ok((query, vars))
+    }
+}
@@ -0,0 +1,358 @@
+//! Opt-in per-row change detection for components.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::core::*;
+use crate::sys;
+
+/// Mask of the row in [`sys::ecs_record_t::row`], the upper bits are flags.
+const ROW_MASK: u32 = 0x0FFF_FFFF;
+
+/// The change ticks of the components for which row changes are tracked, and the position of
+/// the queries that iterate them with [`TableIter::each_changed()`].
+///
+/// Ticks are stored per entity, so they survive entities moving between tables, and are
+/// written from the Rust write paths: `set`, `modified` and mutable `get`.
+#[derive(Default)]
+pub(crate) struct RowChanges {
+    /// A bit per hash of the tracked ids, so that writes of other ids don't take the lock.
+    tracked: [AtomicU64; 4],
+    state: Mutex<RowChangeState>,
+}
+
+#[derive(Default)]
+struct RowChangeState {
+    tick: u64,
+    components: HashMap<u64, TrackedComponent>,
+    cursors: HashMap<(CursorKey, u64), QueryCursor>,
+}
+
+/// The last change tick of every written entity, plus the same ticks ordered by tick so the
+/// entities changed after a tick can be found without visiting all entities.
+#[derive(Default)]
+struct TrackedComponent {
+    ticks: HashMap<u64, u64>,
+    log: BTreeMap<u64, u64>,
+}
+
+/// The query a cursor belongs to: the entity of the query, or the query itself for queries
+/// without an entity.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum CursorKey {
+    Entity(u64),
+    Query(usize),
+}
+
+impl CursorKey {
+    fn new(query: *const sys::ecs_query_t) -> Self {
+        match unsafe { (*query).entity } {
+            0 => CursorKey::Query(query as usize),
+            entity => CursorKey::Entity(entity),
+        }
+    }
+}
+
+/// Where a query is in processing the changes of a component.
+#[derive(Default)]
+struct QueryCursor {
+    /// The changes up to this tick are processed.
+    since: u64,
+    /// The entities that were passed to `each_changed` after `since`, with their change tick.
+    delivered: HashMap<u64, u64>,
+    /// The iteration of the query that is in progress.
+    run: Option<QueryRun>,
+}
+
+/// The changed rows of an iteration of a query, per table.
+#[derive(Default)]
+struct QueryRun {
+    rows: HashMap<usize, Vec<ChangedRow>>,
+    /// The results that were processed, by table, offset and frame offset.
+    results: HashSet<(usize, i32, i32)>,
+}
+
+struct ChangedRow {
+    row: usize,
+    entity: u64,
+    tick: u64,
+}
+
+fn tracked_bit(id: u64) -> (usize, u64) {
+    let hash = (id ^ (id >> 32)).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 56;
+    ((hash >> 6) as usize, 1 << (hash & 63))
+}
+
+impl RowChanges {
+    fn state(&self) -> std::sync::MutexGuard<'_, RowChangeState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn may_track(&self, id: u64) -> bool {
+        let (word, bit) = tracked_bit(id);
+        self.tracked[word].load(Ordering::Relaxed) & bit != 0
+    }
+}
+
+fn row_changes<'a>(world: *const sys::ecs_world_t) -> Option<&'a RowChanges> {
+    let ctx = unsafe { sys::ecs_get_binding_ctx(world) } as *const WorldCtx;
+    (!ctx.is_null()).then(|| unsafe { &(*ctx).row_changes })
+}
+
+/// Records that `id` was written on `entity`, if row changes are tracked for `id`.
+pub(crate) fn mark_row_changed(world: *const sys::ecs_world_t, entity: u64, id: u64) {
+    let Some(row_changes) = row_changes(world) else {
+        return;
+    };
+    if !row_changes.may_track(id) {
+        return;
+    }
+
+    let mut state = row_changes.state();
+    let state = &mut *state;
+    if let Some(component) = state.components.get_mut(&id) {
+        state.tick += 1;
+        if let Some(previous) = component.ticks.insert(entity, state.tick) {
+            component.log.remove(&previous);
+        }
+        component.log.insert(state.tick, entity);
+    }
+}
+
+/// Collects the changed rows of `component` that `cursor` didn't process yet, and drops the
+/// changes that are processed or belong to deleted entities.
+fn start_run(
+    world: *const sys::ecs_world_t,
+    component: &mut TrackedComponent,
+    cursor: &mut QueryCursor,
+) -> QueryRun {
+    let mut run = QueryRun::default();
+    let mut removed = Vec::new();
+    let mut processed = true;
+    for (&tick, &entity) in component.log.range(cursor.since + 1..) {
+        let delivered = cursor.delivered.get(&entity) == Some(&tick);
+        if processed && delivered {
+            // a prefix of changes that were all processed, so the cursor can advance
+            cursor.since = tick;
+            cursor.delivered.remove(&entity);
+            continue;
+        }
+        processed = false;
+        if delivered {
+            continue;
+        }
+
+        let record = unsafe { sys::ecs_record_find(world, entity) };
+        if record.is_null() || unsafe { (*record).table.is_null() } {
+            removed.push((tick, entity));
+            continue;
+        }
+        let (table, row) = unsafe { ((*record).table, (*record).row & ROW_MASK) };
+        run.rows
+            .entry(table as usize)
+            .or_default()
+            .push(ChangedRow {
+                row: row as usize,
+                entity,
+                tick,
+            });
+    }
+    for (tick, entity) in removed {
+        component.log.remove(&tick);
+        component.ticks.remove(&entity);
+        cursor.delivered.remove(&entity);
+    }
+    for rows in run.rows.values_mut() {
+        rows.sort_unstable_by_key(|row| row.row);
+    }
+    run
+}
+
+/// Invokes `func` with the rows of the current result of `iter` in which `id` changed since the
+/// query last processed them.
+pub(crate) fn each_changed_row(iter: &sys::ecs_iter_t, id: u64, mut func: impl FnMut(usize)) {
+    let world = iter.real_world;
+    let row_changes = row_changes(world).expect("world has no binding context");
+
+    let rows = {
+        let mut state = row_changes.state();
+        let state = &mut *state;
+        let Some(component) = state.components.get_mut(&id) else {
+            panic!(
+                "row changes are not tracked for id {}, enable them with `World::track_row_changes`",
+                id
+            );
+        };
+
+        let key = (CursorKey::new(iter.query), id);
+        if !state.cursors.contains_key(&key) {
+            // queries with an entity can be deleted without going through `Query`, so their
+            // cursors are dropped when a new cursor is created
+            state
+                .cursors
+                .retain(|&(cursor_key, _), _| match cursor_key {
+                    CursorKey::Entity(entity) => unsafe { sys::ecs_is_alive(world, entity) },
+                    CursorKey::Query(_) => true,
+                });
+        }
+        let cursor = state.cursors.entry(key).or_default();
+
+        // a result that was already processed means the previous iteration stopped early, in
+        // which case the rows it didn't process are reported again
+        let result = (iter.table as usize, iter.offset, iter.frame_offset);
+        if cursor
+            .run
+            .as_ref()
+            .is_some_and(|run| run.results.contains(&result))
+        {
+            cursor.run = None;
+        }
+        let run = match cursor.run.take() {
+            Some(run) => run,
+            None => start_run(world, component, cursor),
+        };
+        let run = cursor.run.insert(run);
+        run.results.insert(result);
+
+        let offset = iter.offset as usize;
+        let count = iter.count as usize;
+        let mut rows = Vec::new();
+        for changed in run.rows.get(&(iter.table as usize)).into_iter().flatten() {
+            if changed.row >= offset && changed.row < offset + count {
+                rows.push(changed.row - offset);
+                cursor.delivered.insert(changed.entity, changed.tick);
+            }
+        }
+        rows
+    };
+
+    for row in rows {
+        func(row);
+    }
+}
+
+/// Advances the cursors of the query of `iter` past the iteration that just finished.
+///
+/// Changes that the iteration didn't report belong to entities the query doesn't match, and
+/// writes made while the query ran, for example by the query itself, are not reported to the
+/// next iteration.
+pub(crate) fn finish_row_change_run(iter: &sys::ecs_iter_t) {
+    if iter.query.is_null() {
+        return;
+    }
+    let Some(row_changes) = row_changes(iter.real_world) else {
+        return;
+    };
+    if row_changes
+        .tracked
+        .iter()
+        .all(|word| word.load(Ordering::Relaxed) == 0)
+    {
+        return;
+    }
+
+    let key = CursorKey::new(iter.query);
+    let mut state = row_changes.state();
+    let state = &mut *state;
+    for (&(cursor_key, _), cursor) in &mut state.cursors {
+        if cursor_key == key && cursor.run.take().is_some() {
+            cursor.since = state.tick;
+            cursor.delivered.clear();
+        }
+    }
+}
+
+/// Drops the cursors of a query that is deleted.
+pub(crate) fn forget_row_change_cursors(query: *const sys::ecs_query_t) {
+    let world = unsafe { (*query).real_world };
+    let Some(row_changes) = row_changes(world) else {
+        return;
+    };
+    let key = CursorKey::new(query);
+    row_changes
+        .state()
+        .cursors
+        .retain(|&(cursor_key, _), _| cursor_key != key);
+}
+
+impl World {
+    /// Enables per-row change tracking for the component or pair `T`.
+    ///
+    /// Table-level change detection with [`TableIter::is_changed()`] reports a table as changed
+    /// when any of its rows changed. With row tracking enabled, every write of `T` through
+    /// [`EntityView::set()`], [`EntityView::modified()`] or a mutable [`EntityView::get()`]
+    /// records a change tick for the entity, and [`TableIter::each_changed()`] visits only the
+    /// rows that changed since the query last ran.
+    ///
+    /// Writes that bypass these paths, such as mutable query fields, are not recorded. Call
+    /// [`EntityView::modified()`] to record them.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `T` - The component or pair to track.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    ///
+    /// let world = World::new();
+    /// world.track_row_changes::<Position>();
+    ///
+    /// let entities: Vec<_> = (0..5)
+    ///     .map(|i| world.entity().set(Position { x: i as f32, y: 0.0 }))
+    ///     .collect();
+    ///
+    /// let query = world.new_query::<&Position>();
+    /// let mut changed = Vec::new();
+    /// let mut collect_changed = |changed: &mut Vec<Entity>| {
+    ///     query.run(|mut it| {
+    ///         while it.next() {
+    ///             it.each_changed::<Position>(|row| changed.push(it.entity(row).id()));
+    ///         }
+    ///     });
+    /// };
+    ///
+    /// // the first run reports all entities that were written
+    /// collect_changed(&mut changed);
+    /// assert_eq!(changed.len(), 5);
+    ///
+    /// changed.clear();
+    /// entities[3].set(Position { x: 10.0, y: 0.0 });
+    /// collect_changed(&mut changed);
+    /// assert_eq!(changed, [entities[3].id()]);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`TableIter::each_changed()`]
+    /// * [`World::is_row_change_tracked()`]
+    pub fn track_row_changes<T: ComponentOrPairId>(&self) {
+        let id = T::get_id(self);
+        let row_changes = &self.world_ctx().row_changes;
+        row_changes.state().components.entry(id).or_default();
+        let (word, bit) = tracked_bit(id);
+        row_changes.tracked[word].fetch_or(bit, Ordering::Relaxed);
+    }
+
+    /// Returns whether per-row change tracking is enabled for the component or pair `T`.
+    ///
+    /// # See also
+    ///
+    /// * [`World::track_row_changes()`]
+    pub fn is_row_change_tracked<T: ComponentOrPairId>(&self) -> bool {
+        let id = T::get_id(self);
+        self.world_ctx()
+            .row_changes
+            .state()
+            .components
+            .contains_key(&id)
+    }
+}
@@ -203,13 +203,14 @@ where
     ///
     /// # Arguments
     ///
-    /// * `var_id` - The variable id
+    /// * `var` - The variable, a [`QueryVar`] or variable index
     ///
     /// # See also
     ///
     /// * C++ API: `iter::get_var`
     #[doc(alias = "iter::get_var")]
-    pub fn get_var(&self, var_id: i32) -> EntityView<'a> {
+    pub fn get_var(&self, var: impl IntoQueryVar) -> EntityView<'a> {
+        let var_id = var.var_index(self.iter.query);
         ecs_assert!(var_id != -1, FlecsErrorCode::InvalidParameter, 0);
         let var =
             unsafe { sys::ecs_iter_get_var(self.iter as *const _ as *mut sys::ecs_iter_t, var_id) };
@@ -217,6 +218,19 @@ where
         EntityView::new_from(world, var)
     }
 
+    /// Get the value of a query variable.
+    ///
+    /// # Arguments
+    ///
+    /// * `var` - The variable handle
+    ///
+    /// # See also
+    ///
+    /// * [`QueryBuilder::build_with_vars()`]
+    pub fn var(&self, var: QueryVar) -> EntityView<'a> {
+        self.get_var(var)
+    }
+
     /// Get the variable of the iterator by name
     ///
     /// # Arguments
@@ -268,6 +282,48 @@ where
         self.iter.ctx
     }
 
+    /// Access the typed context of the system or observer, or of the query when the iterator
+    /// doesn't belong to a system or observer.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `C` - The type of the context.
+    ///
+    /// # Panics
+    ///
+    /// Panics when no context was set, or when the context is not of type `C`. The context must
+    /// have been set with one of the `set_context_typed()` functions, a context set with
+    /// `set_context()` can only be accessed with [`TableIter::context_ptr()`].
+    ///
+    /// The context is shared by the workers of multithreaded systems, so it can only be accessed
+    /// immutably. Use atomics or locks to update it from a system.
+    ///
+    /// # See also
+    ///
+    /// * [`SystemAPI::set_context_typed()`]
+    /// * [`QueryBuilder::set_context_typed()`]
+    pub fn context_typed<C: Send + Sync + 'static>(&self) -> &C {
+        let mut ctx = std::ptr::null_mut();
+        if self.iter.system != 0 {
+            let world = self.iter.real_world;
+            let observer = unsafe { sys::ecs_observer_get(world, self.iter.system) };
+            if !observer.is_null() {
+                if is_typed_context(unsafe { (*observer).ctx_free }) {
+                    ctx = unsafe { (*observer).ctx };
+                }
+            } else {
+                let system = unsafe { sys::ecs_system_get(world, self.iter.system) };
+                if !system.is_null() && is_typed_context(unsafe { (*system).ctx_free }) {
+                    ctx = unsafe { (*system).ctx };
+                }
+            }
+        }
+        if ctx.is_null() && !self.iter.query.is_null() {
+            ctx = unsafe { typed_query_context(self.iter.query) };
+        }
+        unsafe { typed_context(ctx) }
+    }
+
     /// Access param.
     /// param contains the pointer passed to the param argument of `system::run`
     ///
@@ -731,6 +787,38 @@ where
         unsafe { sys::ecs_iter_changed(self.iter) }
     }
 
+    /// Invokes `func` with the rows of the current result in which the component or pair `T`
+    /// changed since this query last ran.
+    ///
+    /// Requires per-row change tracking for `T`, which is enabled with
+    /// [`World::track_row_changes()`]. The first run of a query visits all rows that were
+    /// written since tracking was enabled.
+    ///
+    /// A run of the query finishes when [`TableIter::next()`] returns false. Writes made while
+    /// the query runs, for example by `func` itself, are not reported to the next run. When the
+    /// iteration stops early, the next run reports the rows that weren't passed to `func` yet.
+    ///
+    /// Call this at most once per component for every result: a new run of the query is
+    /// detected when a result is processed again.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - Invoked with the row of every changed entity, which can be used to index
+    ///   the fields and entities of the current result.
+    ///
+    /// # Panics
+    ///
+    /// Panics when row changes are not tracked for `T`.
+    ///
+    /// # See also
+    ///
+    /// * [`World::track_row_changes()`]
+    /// * [`TableIter::is_changed()`]
+    pub fn each_changed<T: ComponentOrPairId>(&self, func: impl FnMut(usize)) {
+        let id = T::get_id(self.real_world());
+        each_changed_row(self.iter, id, func);
+    }
+
     /// Skip current table.
     /// This indicates to the query that the data in the current table is not
     /// modified. By default, iterating a table with a query will mark the
@@ -859,6 +947,8 @@ where
             unsafe {
                 sys::ecs_table_lock(self.iter.world, self.iter.table);
             };
+        } else if !result {
+            finish_row_change_run(self.iter);
         }
 
         result
@@ -893,6 +983,10 @@ where
     /// ```
     pub fn fini(self) {
         unsafe {
+            // the table of the current result is locked by `next()`
+            if self.iter.flags & sys::EcsIterIsValid != 0 && !self.iter.table.is_null() {
+                sys::ecs_table_unlock(self.iter.world, self.iter.table);
+            }
             sys::ecs_iter_fini(self.iter);
         }
     }
@@ -18,10 +18,40 @@ pub enum TermRefMode {
 /// It is not possible to modify the term using this type.
 /// To modify a term, use the `TermBuilder` interface.
 /// Useful for debugging purposes.
+#[derive(Clone, Copy)]
 pub struct TermRef<'a> {
     term: &'a sys::ecs_term_t,
 }
 
+/// The source a term is matched on, as returned by [`TermRef::src()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TermSource<'a> {
+    /// The term is matched on the iterated entities (`$this`).
+    This,
+    /// The term is matched on the entity stored in a query variable, such as `$planet`.
+    Variable(&'a str),
+    /// The term is matched on a fixed entity, such as a singleton.
+    Entity(Entity),
+    /// The term is not matched on anything and only passes its id to the iterator.
+    None,
+}
+
+/// How a term traverses relationships to find its component, as returned by
+/// [`TermRef::traversal()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TermTraversal {
+    /// The component is matched on the source itself.
+    pub self_: bool,
+    /// The component is matched by traversing `relationship` upwards.
+    pub up: bool,
+    /// Results are ordered breadth-first by the depth of `relationship`.
+    pub cascade: bool,
+    /// Together with `cascade`, results are ordered by descending depth.
+    pub desc: bool,
+    /// The traversed relationship, or `0` if the term doesn't traverse.
+    pub relationship: Entity,
+}
+
 impl<'a> TermRef<'a> {
     pub fn new(term: &'a sys::ecs_term_t) -> Self {
         Self { term }
@@ -57,6 +87,55 @@ impl<'a> TermRef<'a> {
         let id = self.term.second.id & !flecs::TermRefFlags::ID;
         Entity(id)
     }
+
+    /// Returns the index of the field of the term in the query results.
+    pub fn field_index(&self) -> i16 {
+        self.term.field_index
+    }
+
+    /// Returns the source the term is matched on.
+    pub fn src(&self) -> TermSource<'a> {
+        let src = &self.term.src;
+        let id = src.id & !flecs::TermRefFlags::ID;
+        if src.id & flecs::IsVariable::ID != 0 {
+            if id == ECS_THIS {
+                TermSource::This
+            } else if src.name.is_null() {
+                TermSource::Variable("")
+            } else {
+                let name = unsafe { std::ffi::CStr::from_ptr(src.name) };
+                TermSource::Variable(name.to_str().unwrap_or(""))
+            }
+        } else if id == 0 {
+            TermSource::None
+        } else {
+            TermSource::Entity(Entity(id))
+        }
+    }
+
+    /// Returns how the term traverses relationships to find its component.
+    pub fn traversal(&self) -> TermTraversal {
+        let flags = self.term.src.id;
+        TermTraversal {
+            self_: flags & flecs::Self_::ID != 0,
+            up: flags & flecs::Up::ID != 0,
+            cascade: flags & flecs::Cascade::ID != 0,
+            desc: flags & flecs::Desc::ID != 0,
+            relationship: Entity(self.term.trav),
+        }
+    }
+}
+
+impl std::fmt::Debug for TermRef<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TermRef")
+            .field("id", &self.id())
+            .field("inout", &self.inout())
+            .field("oper", &self.oper())
+            .field("src", &self.src())
+            .field("traversal", &self.traversal())
+            .finish()
+    }
 }
 
 #[doc(hidden)]
@@ -76,6 +155,10 @@ pub mod internals {
         pub(crate) next_term_index: i32,
         pub(crate) term_ref_mode: TermRefMode,
         pub(crate) str_ptrs_to_free: Vec<StringToFree>,
+        /// The number of terms when more than `FLECS_TERM_COUNT_MAX` terms were added, else 0.
+        pub(crate) overflow_term_count: i32,
+        /// Receives the writes to terms past `FLECS_TERM_COUNT_MAX`.
+        pub(crate) overflow_term: sys::ecs_term_t,
     }
 
     #[doc(hidden)]
@@ -100,6 +183,11 @@ pub mod internals {
 
         #[inline(always)]
         fn term_mut_at(&mut self, index: i32) -> &mut sys::ecs_term_t {
+            if index >= sys::FLECS_TERM_COUNT_MAX as i32 {
+                let term_builder = self.term_builder_mut();
+                term_builder.overflow_term_count = term_builder.overflow_term_count.max(index + 1);
+                return &mut term_builder.overflow_term;
+            }
             &mut self.query_desc_mut().terms[index as usize]
         }
 
@@ -111,7 +199,22 @@ pub mod internals {
 
         #[inline(always)]
         fn current_term(&self) -> &sys::ecs_term_t {
-            &self.query_desc().terms[self.term_builder().current_term_index as usize]
+            let index = self.term_builder().current_term_index;
+            if index >= sys::FLECS_TERM_COUNT_MAX as i32 {
+                return &self.term_builder().overflow_term;
+            }
+            &self.query_desc().terms[index as usize]
+        }
+
+        /// Panics when more terms were added than a query supports.
+        fn assert_term_count(&self) {
+            let count = self.term_builder().overflow_term_count;
+            assert!(
+                count == 0,
+                "{}: Maximum number of terms reached in query builder ({count} > {})",
+                FlecsErrorCode::InvalidParameter,
+                sys::FLECS_TERM_COUNT_MAX
+            );
         }
 
         #[inline(always)]
@@ -0,0 +1,69 @@
+//! Typed contexts, which store a Rust value as the `ctx` of systems, observers, queries and
+//! worlds.
+
+use std::any::{type_name, Any};
+use std::os::raw::c_void;
+
+use crate::sys;
+
+/// Moves `context` to the heap, returning the pointer to store as `ctx`.
+///
+/// The pointer must be freed with [`free_typed_context`].
+pub(crate) fn typed_context_into_raw<C: 'static>(context: C) -> *mut c_void {
+    let context: Box<dyn Any> = Box::new(context);
+    Box::into_raw(Box::new(context)) as *mut c_void
+}
+
+/// The `ctx_free` callback of typed contexts.
+pub(crate) unsafe extern "C" fn free_typed_context(ctx: *mut c_void) {
+    unsafe { drop(Box::from_raw(ctx as *mut Box<dyn Any>)) };
+}
+
+/// Returns whether a context with the free callback `ctx_free` is a typed context.
+///
+/// Systems and observers free their typed context with [`free_typed_context`], which tells
+/// their typed contexts apart from contexts set with `set_context()`.
+pub(crate) fn is_typed_context(ctx_free: sys::ecs_ctx_free_t) -> bool {
+    ctx_free.is_some_and(|ctx_free| {
+        std::ptr::fn_addr_eq(
+            ctx_free,
+            free_typed_context as unsafe extern "C" fn(*mut c_void),
+        )
+    })
+}
+
+/// Returns the typed context of `query`, or null when its context isn't typed.
+///
+/// The `binding_ctx` of queries with a typed context points to the context, as `ctx_free` isn't
+/// accessible once the query is created.
+///
+/// # Safety
+///
+/// `query` must point to a valid query.
+pub(crate) unsafe fn typed_query_context(query: *const sys::ecs_query_t) -> *mut c_void {
+    let query = unsafe { &*query };
+    if query.binding_ctx == query.ctx {
+        query.ctx
+    } else {
+        std::ptr::null_mut()
+    }
+}
+
+/// Returns the value of a typed context.
+///
+/// # Safety
+///
+/// `ctx` must be null or a pointer created by [`typed_context_into_raw`] that wasn't freed.
+///
+/// # Panics
+///
+/// Panics when `ctx` is null or when the context is not of type `C`.
+pub(crate) unsafe fn typed_context<'a, C: 'static>(ctx: *mut c_void) -> &'a C {
+    if ctx.is_null() {
+        panic!("no context of type {} was set", type_name::<C>());
+    }
+    let context = unsafe { &*(ctx as *const Box<dyn Any>) };
+    context
+        .downcast_ref::<C>()
+        .unwrap_or_else(|| panic!("context is not of type {}", type_name::<C>()))
+}
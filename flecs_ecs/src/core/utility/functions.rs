@@ -246,6 +246,8 @@ pub(crate) fn set_helper<T: ComponentId>(
         );
     };
 
+    mark_row_changed(world, entity, id);
+
     let mut is_new = false;
     unsafe {
         if sys::ecs_is_deferred(world) {
@@ -47,6 +47,8 @@ pub mod private {
             run_ctx_free: flecs_ecs_sys::ecs_ctx_free_t,
         ) -> &mut Self;
 
+        fn set_context_free(&mut self, ctx_free: sys::ecs_ctx_free_t) -> &mut Self;
+
         fn desc_binding_context(&self) -> *mut c_void;
 
         fn set_desc_callback(
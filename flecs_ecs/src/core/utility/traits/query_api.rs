@@ -872,6 +872,19 @@ where
         }
     }
 
+    /// Returns a handle to the variable `name` of the query. A leading `$` is ignored.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the query has no variable with that name.
+    ///
+    /// # See also
+    ///
+    /// * [`QueryBuilder::build_with_vars()`]
+    fn query_var(&self, name: &str) -> Option<QueryVar> {
+        QueryVar::find(self.query_ptr(), name)
+    }
+
     fn plan(&self) -> String {
         let query = self.query_ptr();
         let result: *mut c_char = unsafe { sys::ecs_query_plan(query as *const _) };
@@ -885,6 +898,195 @@ where
         rust_string
     }
 
+    /// Returns a structured description of the query.
+    ///
+    /// The description contains the terms of the query with their operators, inout kinds,
+    /// sources and traversal, the names of the query variables, the cache kind, and the tables
+    /// the query currently matches with their entity counts. The matched tables are collected
+    /// by iterating the query.
+    ///
+    /// # See also
+    ///
+    /// * [`QueryDescription`]
+    /// * [`QueryAPI::plan()`]
+    /// * [`QueryAPI::to_string()`]
+    fn describe(&self) -> QueryDescription<'_> {
+        let query = self.query_ptr();
+        ecs_assert!(
+            !query.is_null(),
+            FlecsErrorCode::InvalidParameter,
+            "query filter is null"
+        );
+        let world = unsafe { WorldRef::from_ptr(self.world_ptr_mut()) };
+        QueryDescription::new(world, unsafe { &*query })
+    }
+
+    /// Calls `func` with an [`Iterator`] over the matched entities and their components.
+    ///
+    /// The iterator yields `(entity, components)` pairs, where `components` is the tuple of the
+    /// query, so it can be used with `for` loops and adapters such as `map`, `filter` and `collect`.
+    /// Breaking out of the iteration early is fine, the underlying iterator is cleaned up when the
+    /// iterator is dropped.
+    ///
+    /// The references yielded by the iterator can't leave `func`. The tables of the entities stay
+    /// locked until `func` returns, so that structural changes to them, such as adding or
+    /// removing components, are caught while the references are in use.
+    ///
+    /// The query must match entities. For queries that only match singletons or fixed sources use
+    /// [`QueryAPI::chunks()`].
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The function that consumes the iterator.
+    ///
+    /// # Returns
+    ///
+    /// The value returned by `func`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the query has mutable fields that aren't matched on the iterated entities,
+    /// such as singletons, as the iterator would hand out multiple mutable references to them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    ///
+    /// #[derive(Component)]
+    /// struct Velocity {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    ///
+    /// let world = World::new();
+    ///
+    /// let e1 = world.entity().set(Position { x: 1.0, y: 2.0 });
+    /// let e2 = world
+    ///     .entity()
+    ///     .set(Position { x: 3.0, y: 4.0 })
+    ///     .set(Velocity { x: 1.0, y: 1.0 });
+    ///
+    /// let query = world.new_query::<(&mut Position, Option<&Velocity>)>();
+    ///
+    /// query.iter(|iter| {
+    ///     for (_, (pos, vel)) in iter {
+    ///         if let Some(vel) = vel {
+    ///             pos.x += vel.x;
+    ///             pos.y += vel.y;
+    ///         }
+    ///     }
+    /// });
+    ///
+    /// let moved: Vec<Entity> = query.iter(|iter| {
+    ///     iter.filter(|(_, (pos, _))| pos.x > 2.0)
+    ///         .map(|(e, _)| e.id())
+    ///         .collect()
+    /// });
+    ///
+    /// assert_eq!(moved, [e2.id()]);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`QueryAPI::each_entity()`]
+    /// * [`QueryAPI::chunks()`]
+    fn iter<R>(&self, func: impl for<'i> FnOnce(QueryEntityIter<'i, T>) -> R) -> R {
+        assert!(
+            !has_mutable_shared_field::<T>(self.query_ptr()),
+            "iter() requires that fields which are not matched on the iterated entity are immutable, as they are shared between results"
+        );
+        let locks = TableLocks::new(self.world_ptr_mut());
+        func(QueryEntityIter::new(
+            self.retrieve_iter(),
+            self.iter_next_func(),
+            &locks,
+        ))
+    }
+
+    /// Calls `func` with an [`Iterator`] over the results of the query, one [`QueryChunk`] per
+    /// matched table.
+    ///
+    /// A chunk contains the entities of the table and the components of the query as slices.
+    /// Like with [`QueryAPI::iter()`], the chunks can't leave `func`, and their tables stay locked
+    /// until `func` returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - The function that consumes the iterator.
+    ///
+    /// # Returns
+    ///
+    /// The value returned by `func`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the query has mutable fields that aren't matched on the iterated entities,
+    /// such as singletons, as the iterator would hand out multiple mutable references to them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    ///
+    /// #[derive(Component)]
+    /// struct Velocity {
+    ///     x: f32,
+    ///     y: f32,
+    /// }
+    ///
+    /// let world = World::new();
+    ///
+    /// for _ in 0..10 {
+    ///     world
+    ///         .entity()
+    ///         .set(Position { x: 0.0, y: 0.0 })
+    ///         .set(Velocity { x: 1.0, y: 2.0 });
+    /// }
+    ///
+    /// let query = world.new_query::<(&mut Position, &Velocity)>();
+    ///
+    /// query.chunks(|chunks| {
+    ///     for chunk in chunks {
+    ///         let (positions, velocities) = chunk.components;
+    ///         assert_eq!(positions.len(), chunk.entities.len());
+    ///         for (p, v) in positions.iter_mut().zip(velocities) {
+    ///             p.x += v.x;
+    ///             p.y += v.y;
+    ///         }
+    ///     }
+    /// });
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`QueryAPI::run_iter()`]
+    /// * [`QueryAPI::iter()`]
+    fn chunks<R>(&self, func: impl for<'i> FnOnce(QueryChunkIter<'i, T>) -> R) -> R {
+        assert!(
+            !has_mutable_shared_field::<T>(self.query_ptr()),
+            "chunks() requires that fields which are not matched on the iterated entity are immutable, as they are shared between results"
+        );
+        let locks = TableLocks::new(self.world_ptr_mut());
+        func(QueryChunkIter::new(
+            self.retrieve_iter(),
+            self.iter_next_func(),
+            &locks,
+        ))
+    }
+
     fn iterable(&self) -> QueryIter<P, T> {
         QueryIter::new(self.retrieve_iter(), self.iter_next_func())
     }
@@ -928,15 +1130,19 @@ where
 
     /// Return total number of entities in result.
     ///
+    /// When called on a [`QueryIter`], the group, variables and page of the iterator are taken
+    /// into account.
+    ///
     /// # Returns
     ///
     /// The total number of entities in the result
     ///
     /// # See also
     ///
+    /// * [`QueryIter::page()`]
     /// * C++ API: `iter_iterable::count`
     #[doc(alias = "iter_iterable::count")]
-    fn count(&mut self) -> i32 {
+    fn count(&self) -> i32 {
         let mut it = self.retrieve_iter();
         let mut result = 0;
         while self.iter_next(&mut it) {
@@ -945,6 +1151,24 @@ where
         result
     }
 
+    /// Limit results to a page of the matched entities.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset`: the number of entities to skip
+    /// * `limit`: the maximum number of entities to return, `0` for no limit
+    ///
+    /// # See also
+    ///
+    /// * [`QueryIter::page()`]
+    /// * C++ API: `iter_iterable::page`
+    #[doc(alias = "iter_iterable::page")]
+    fn page(&mut self, offset: i32, limit: i32) -> QueryIter<P, T> {
+        let mut iter = self.iterable();
+        iter.page(offset, limit);
+        iter
+    }
+
     /// Limit results to tables with specified group id (grouped queries only)
     ///
     /// # Arguments
@@ -981,7 +1205,7 @@ where
     ///
     /// # Arguments
     ///
-    /// * `var_id`: the variable id to set
+    /// * `var`: the variable to set, a [`QueryVar`] or variable index
     ///
     /// * `value`: the value to set
     ///
@@ -989,9 +1213,9 @@ where
     ///
     /// * C++ API: `iterable::set_var`
     #[doc(alias = "iterable::set_var")]
-    fn set_var(&mut self, var_id: i32, value: impl Into<Entity>) -> QueryIter<P, T> {
+    fn set_var(&mut self, var: impl IntoQueryVar, value: impl Into<Entity>) -> QueryIter<P, T> {
         let mut iter = self.iterable();
-        iter.set_var(var_id, value);
+        iter.set_var(var, value);
         iter
     }
 
@@ -999,7 +1223,7 @@ where
     ///
     /// # Arguments
     ///
-    /// * `var_id`: the variable id to set
+    /// * `var`: the variable to set, a [`QueryVar`] or variable index
     ///
     /// * `range`: the range to set
     ///
@@ -1007,9 +1231,13 @@ where
     ///
     /// * C++ API: `iter_iterable::set_var`
     #[doc(alias = "iter_iterable::set_var")]
-    fn set_var_table(&mut self, var_id: i32, table: impl IntoTableRange) -> QueryIter<P, T> {
+    fn set_var_table(
+        &mut self,
+        var: impl IntoQueryVar,
+        table: impl IntoTableRange,
+    ) -> QueryIter<P, T> {
         let mut iter = self.iterable();
-        iter.set_var_table(var_id, table);
+        iter.set_var_table(var, table);
         iter
     }
 
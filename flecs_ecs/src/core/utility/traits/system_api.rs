@@ -17,6 +17,68 @@ where
     #[doc(alias = "system_builder_i::ctx")]
     fn set_context(&mut self, context: *mut c_void) -> &mut Self;
 
+    /// Set a typed context, which is dropped when the system or observer is deleted.
+    ///
+    /// The context can be accessed with [`TableIter::context_typed()`] from the callbacks,
+    /// and with `context_typed()` on the built [`System`](crate::addons::system::System) or
+    /// [`Observer`].
+    ///
+    /// # Type Parameters
+    ///
+    /// * `C` - The type of the context.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The context.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// #[derive(Component)]
+    /// struct Position {
+    ///     x: f32,
+    /// }
+    ///
+    /// struct Stats {
+    ///     moved: AtomicUsize,
+    /// }
+    ///
+    /// let world = World::new();
+    ///
+    /// let system = world
+    ///     .system::<&mut Position>()
+    ///     .set_context_typed(Stats {
+    ///         moved: AtomicUsize::new(0),
+    ///     })
+    ///     .run(|mut it| {
+    ///         while it.next() {
+    ///             let stats = it.context_typed::<Stats>();
+    ///             stats.moved.fetch_add(it.count(), Ordering::Relaxed);
+    ///         }
+    ///     });
+    ///
+    /// world.entity().set(Position { x: 0.0 });
+    /// world.progress();
+    ///
+    /// let stats = system.context_typed::<Stats>();
+    /// assert_eq!(stats.moved.load(Ordering::Relaxed), 1);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`SystemAPI::set_context()`]
+    /// * C++ API: `observer_builder_i::ctx`
+    /// * C++ API: `system_builder_i::ctx`
+    #[doc(alias = "observer_builder_i::ctx")]
+    #[doc(alias = "system_builder_i::ctx")]
+    fn set_context_typed<C: 'static>(&mut self, context: C) -> &mut Self {
+        self.set_context(typed_context_into_raw(context));
+        self.set_context_free(Some(free_typed_context))
+    }
+
     fn each<Func>(&mut self, func: Func) -> <Self as builder::Builder<'a>>::BuiltType
     where
         Func: FnMut(T::TupleType<'_>) + 'static,
@@ -578,6 +640,11 @@ macro_rules! implement_reactor_api {
                 self
             }
 
+            fn set_context_free(&mut self, ctx_free: flecs_ecs_sys::ecs_ctx_free_t) -> &mut Self {
+                self.desc.ctx_free = ctx_free;
+                self
+            }
+
             fn desc_binding_context(&self) -> *mut c_void {
                 self.desc.callback_ctx
             }
@@ -602,7 +669,12 @@ macro_rules! implement_reactor_api {
             T: QueryTuple,
         {
             fn set_context(&mut self, context: *mut c_void) -> &mut Self {
+                // a raw context replaces a typed context, which isn't freed by flecs anymore
+                if $crate::core::is_typed_context(self.desc.ctx_free) {
+                    unsafe { $crate::core::free_typed_context(self.desc.ctx) };
+                }
                 self.desc.ctx = context;
+                self.desc.ctx_free = None;
                 self
             }
         }
@@ -643,6 +715,11 @@ macro_rules! implement_reactor_api {
                 self
             }
 
+            fn set_context_free(&mut self, ctx_free: flecs_ecs_sys::ecs_ctx_free_t) -> &mut Self {
+                self.desc.ctx_free = ctx_free;
+                self
+            }
+
             fn desc_binding_context(&self) -> *mut c_void {
                 self.desc.callback_ctx
             }
@@ -668,7 +745,12 @@ macro_rules! implement_reactor_api {
             P: ComponentId,
         {
             fn set_context(&mut self, context: *mut c_void) -> &mut Self {
+                // a raw context replaces a typed context, which isn't freed by flecs anymore
+                if $crate::core::is_typed_context(self.desc.ctx_free) {
+                    unsafe { $crate::core::free_typed_context(self.desc.ctx) };
+                }
                 self.desc.ctx = context;
+                self.desc.ctx_free = None;
                 self
             }
         }
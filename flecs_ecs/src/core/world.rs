@@ -323,7 +323,8 @@ impl World {
     /// Ends a frame.
     ///
     /// This operation must be called at the end of the frame, and always after
-    /// [`World::frame_begin()`].
+    /// [`World::frame_begin()`]. Pending [`Commands`] are applied when the frame ends, if the
+    /// pipeline ran during the frame.
     ///
     /// # Safety
     /// The function should only be run from the main thread.
@@ -1089,7 +1090,65 @@ impl World {
     #[doc(alias = "world::set_ctx")]
     #[allow(clippy::not_unsafe_ptr_arg_deref)] // this doesn't actually deref the pointer
     pub fn set_context(&self, ctx: *mut c_void, ctx_free: sys::ecs_ctx_free_t) {
-        unsafe { sys::ecs_set_ctx(self.raw_world.as_ptr(), ctx, ctx_free) }
+        let previous = self.context();
+        unsafe { sys::ecs_set_ctx(self.raw_world.as_ptr(), ctx, ctx_free) };
+
+        let world_ctx = self.world_ctx_mut();
+        if world_ctx.has_typed_context && previous != ctx {
+            unsafe { free_typed_context(previous) };
+        }
+        world_ctx.has_typed_context = false;
+    }
+
+    /// Set a typed world context, which can be accessed by anyone that has a reference to the
+    /// world.
+    ///
+    /// The context replaces and drops the previous typed context, and is dropped when the world
+    /// is destroyed.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `C` - The type of the context.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The world context.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flecs_ecs::prelude::*;
+    ///
+    /// struct Settings {
+    ///     gravity: f32,
+    /// }
+    ///
+    /// let world = World::new();
+    /// world.set_context_typed(Settings { gravity: 9.81 });
+    ///
+    /// assert_eq!(world.context_typed::<Settings>().gravity, 9.81);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// * [`World::context_typed()`]
+    /// * C++ API: `world::set_ctx`
+    #[doc(alias = "world::set_ctx")]
+    pub fn set_context_typed<C: 'static>(&self, context: C) {
+        let previous = self.context();
+        unsafe {
+            sys::ecs_set_ctx(
+                self.raw_world.as_ptr(),
+                typed_context_into_raw(context),
+                Some(free_typed_context),
+            );
+        }
+
+        let world_ctx = self.world_ctx_mut();
+        if world_ctx.has_typed_context {
+            unsafe { free_typed_context(previous) };
+        }
+        world_ctx.has_typed_context = true;
     }
 
     /// Get world context.
@@ -1111,6 +1170,31 @@ impl World {
         unsafe { sys::ecs_get_ctx(self.raw_world.as_ptr()) }
     }
 
+    /// Get the typed world context.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `C` - The type of the context.
+    ///
+    /// # Panics
+    ///
+    /// Panics when no context was set with [`World::set_context_typed()`], or when the context
+    /// is not of type `C`.
+    ///
+    /// # See also
+    ///
+    /// * [`World::set_context_typed()`]
+    /// * C++ API: `world::get_ctx`
+    #[doc(alias = "world::get_ctx")]
+    pub fn context_typed<C: 'static>(&self) -> &C {
+        let context = if self.world_ctx_mut().has_typed_context {
+            self.context()
+        } else {
+            std::ptr::null_mut()
+        };
+        unsafe { typed_context(context) }
+    }
+
     pub(crate) fn get_context(world: *mut sys::ecs_world_t) -> *mut WorldCtx {
         unsafe { sys::ecs_get_binding_ctx(world) as *mut WorldCtx }
     }
@@ -4400,8 +4484,12 @@ impl World {
     /// on their matching entities for the specified time since the last frame.
     ///
     /// When `delta_time` is 0, `World::progress_time()` will automatically measure the time passed
-    /// since the last frame. For applications not using time management, passing a
-    /// non-zero `delta_time` (1.0 recommended) skips automatic time measurement to avoid overhead.
+    /// since the last frame, unless the world is deterministic, in which case the fixed delta
+    /// time set with [`World::set_deterministic()`] is used. For applications not using time
+    /// management, passing a non-zero `delta_time` (1.0 recommended) skips automatic time
+    /// measurement to avoid overhead.
+    ///
+    /// Pending [`Commands`] are applied after the frame has ended.
     ///
     /// # Arguments
     ///
@@ -4419,6 +4507,8 @@ impl World {
     #[doc(alias = "world::progress")]
     #[inline(always)]
     pub fn progress_time(&self, delta_time: f32) -> bool {
+        crate::addons::pipeline::resolve_if_dirty(self.into());
+        let delta_time = crate::addons::pipeline::frame_delta_time(self.into(), delta_time);
         unsafe { sys::ecs_progress(self.raw_world.as_ptr(), delta_time) }
     }
 
@@ -4478,6 +4568,7 @@ impl World {
     #[doc(alias = "world::run_pipeline")]
     #[inline(always)]
     pub fn run_pipeline_id_time(&self, pipeline: impl Into<Entity>, delta_time: super::FTime) {
+        crate::addons::pipeline::resolve_if_dirty(self.into());
         unsafe {
             sys::ecs_run_pipeline(self.raw_world.as_ptr(), *pipeline.into(), delta_time);
         }
@@ -4514,6 +4605,7 @@ impl World {
     where
         Component: ComponentType<Struct> + ComponentId,
     {
+        crate::addons::pipeline::resolve_if_dirty(self.into());
         unsafe {
             sys::ecs_run_pipeline(self.raw_world.as_ptr(), Component::id(self), delta_time);
         }
@@ -4588,6 +4680,46 @@ impl World {
         self.info().time_scale
     }
 
+    /// Get the delta time of the last frame, including the time scale.
+    ///
+    /// # See also
+    ///
+    /// * [`World::time()`]
+    /// * C++ API: `world::delta_time`
+    #[doc(alias = "world::delta_time")]
+    #[inline(always)]
+    pub fn delta_time(&self) -> super::FTime {
+        self.info().delta_time
+    }
+
+    /// Get the time that passed in the simulation, which is the sum of the delta times of all
+    /// frames, including the time scale.
+    ///
+    /// For deterministic worlds this is a virtual clock that only advances with the fixed delta
+    /// time. It is reset by [`World::reset_clock()`].
+    ///
+    /// # See also
+    ///
+    /// * [`World::delta_time()`]
+    /// * [`World::set_deterministic()`]
+    /// * C++ API: `world::time`
+    #[doc(alias = "world::time")]
+    #[inline(always)]
+    pub fn time(&self) -> f64 {
+        self.info().world_time_total
+    }
+
+    /// Get the number of frames that ran.
+    ///
+    /// # See also
+    ///
+    /// * C++ API: `world::tick`
+    #[doc(alias = "world::tick")]
+    #[inline(always)]
+    pub fn tick(&self) -> i64 {
+        self.info().frame_count_total
+    }
+
     /// Get target frames per second (FPS).
     ///
     /// Retrieves the target FPS for the world. This value is used to calculate
@@ -4619,6 +4751,8 @@ impl World {
     /// Utilizing this feature promotes consistent system execution intervals and
     /// conserves CPU resources by avoiding more frequent system runs than necessary.
     ///
+    /// While the world is deterministic, the target fps is set when it stops being deterministic.
+    ///
     /// It's important to note that [`World::progress()`] will only introduce sleep periods
     /// when there is surplus time within a frame. This accounts for time consumed both
     /// within Flecs and in external operations.
@@ -4631,13 +4765,12 @@ impl World {
     /// # See also
     ///
     /// * [`World::get_target_fps()`]
+    /// * [`World::set_deterministic()`]
     /// * C++ API: `world::set_target_fps`
     #[doc(alias = "world::set_target_fps")]
     #[inline(always)]
     pub fn set_target_fps(&self, target_fps: super::FTime) {
-        unsafe {
-            sys::ecs_set_target_fps(self.raw_world.as_ptr(), target_fps);
-        }
+        crate::addons::pipeline::set_frame_rate(self.into(), target_fps);
     }
 
     /// Reset world clock. Reset the clock that keeps track of the total time passed in the simulation.
@@ -4661,6 +4794,8 @@ impl World {
     /// but never while running a system / pipeline. Calling [`World::set_threads()`] will also end the use
     /// of task threads setup with [`World::set_task_threads()`] and vice-versa
     ///
+    /// While the pipeline is sequential, the threads are started when it stops being sequential.
+    ///
     /// # Arguments
     ///
     /// * `threads` - The number of threads to use.
@@ -4669,13 +4804,12 @@ impl World {
     ///
     /// * [`World::set_stage_count()`]
     /// * [`World::set_task_threads()`]
+    /// * [`World::set_sequential()`]
     /// * C++ API: `world::set_threads`
     #[doc(alias = "world::set_threads")]
     #[inline(always)]
     pub fn set_threads(&self, threads: i32) {
-        unsafe {
-            sys::ecs_set_threads(self.raw_world.as_ptr(), threads);
-        }
+        crate::addons::pipeline::set_worker_threads(self.into(), threads);
     }
 
     /// Get number of configured stages. Return number of stages set by [`World::set_stage_count()`].
@@ -1,4 +1,6 @@
-use super::{FlecsArray, FlecsIdMap, World};
+use std::sync::Arc;
+
+use super::{CommandQueue, Commands, FlecsArray, FlecsIdMap, RowChanges, World};
 use crate::sys;
 
 pub(crate) struct WorldCtx {
@@ -6,6 +8,27 @@ pub(crate) struct WorldCtx {
     pub(crate) components: FlecsIdMap,
     pub(crate) components_array: FlecsArray,
     pub(crate) is_panicking: bool,
+    pub(crate) command_queues: Vec<Arc<CommandQueue>>,
+    pub(crate) handle_commands: Option<Commands>,
+    /// Whether the system that applies the command buffers at the end of every frame exists.
+    #[cfg(feature = "flecs_pipeline")]
+    pub(crate) commands_system: bool,
+    pub(crate) ordered_children_observer: bool,
+    pub(crate) row_changes: RowChanges,
+    /// Whether the context of the world was set with `World::set_context_typed()`.
+    pub(crate) has_typed_context: bool,
+    #[cfg(feature = "flecs_system")]
+    pub(crate) system_run_hooks: crate::addons::system::SystemRunHooks,
+    #[cfg(feature = "flecs_pipeline")]
+    pub(crate) system_ordering: crate::addons::pipeline::SystemOrdering,
+    #[cfg(feature = "flecs_pipeline")]
+    pub(crate) states: crate::addons::pipeline::States,
+    #[cfg(feature = "flecs_pipeline")]
+    pub(crate) deterministic: crate::addons::pipeline::Deterministic,
+    #[cfg(feature = "flecs_timer")]
+    pub(crate) timers: crate::addons::timer::Timers,
+    #[cfg(feature = "flecs_app")]
+    pub(crate) app_hooks: Option<crate::addons::app::AppHooks>,
 }
 
 impl WorldCtx {
@@ -15,6 +38,25 @@ impl WorldCtx {
             components: Default::default(),
             components_array: vec![0; 500],
             is_panicking: false,
+            command_queues: Vec::new(),
+            handle_commands: None,
+            #[cfg(feature = "flecs_pipeline")]
+            commands_system: false,
+            ordered_children_observer: false,
+            row_changes: Default::default(),
+            has_typed_context: false,
+            #[cfg(feature = "flecs_system")]
+            system_run_hooks: Default::default(),
+            #[cfg(feature = "flecs_pipeline")]
+            system_ordering: Default::default(),
+            #[cfg(feature = "flecs_pipeline")]
+            states: Default::default(),
+            #[cfg(feature = "flecs_pipeline")]
+            deterministic: Default::default(),
+            #[cfg(feature = "flecs_timer")]
+            timers: Default::default(),
+            #[cfg(feature = "flecs_app")]
+            app_hooks: None,
         }
     }
 
@@ -55,6 +97,15 @@ impl WorldCtx {
     }
 }
 
+impl Drop for WorldCtx {
+    fn drop(&mut self) {
+        // command buffers can outlive the world
+        for queue in &self.command_queues {
+            queue.detach();
+        }
+    }
+}
+
 impl World {
     pub(crate) fn world_ctx(&self) -> &WorldCtx {
         unsafe { &*(sys::ecs_get_binding_ctx(self.raw_world.as_ptr()) as *const WorldCtx) }
@@ -0,0 +1,239 @@
+#![allow(dead_code)]
+use crate::common_test::*;
+
+fn assert_send_sync_clone<T: Send + Sync + Clone + 'static>() {}
+
+#[test]
+fn commands_is_send_sync_clone() {
+    assert_send_sync_clone::<Commands>();
+}
+
+#[test]
+fn commands_spawn_insert_apply() {
+    let world = World::new();
+    let commands = world.commands();
+
+    let e = commands.spawn();
+    commands.insert(e, Position { x: 10, y: 20 });
+    commands.add::<Tag>(e);
+
+    assert!(!world.entity_from_id(e).has::<Position>());
+    assert_eq!(commands.len(), 2);
+
+    world.apply(&commands);
+
+    assert!(commands.is_empty());
+    let e = world.entity_from_id(e);
+    assert!(e.has::<Tag>());
+    e.get::<&Position>(|pos| {
+        assert_eq!(pos.x, 10);
+        assert_eq!(pos.y, 20);
+    });
+}
+
+#[test]
+fn commands_remove_despawn() {
+    let world = World::new();
+    let commands = world.commands();
+
+    let a = world.entity().set(Position { x: 1, y: 2 }).add::<Tag>();
+    let b = world.entity().add::<Tag>();
+
+    commands.remove::<Position>(a);
+    commands.despawn(b);
+    world.apply(&commands);
+
+    assert!(!a.has::<Position>());
+    assert!(a.has::<Tag>());
+    assert!(!world.is_alive(b));
+}
+
+#[test]
+fn commands_skip_dead_entity() {
+    let world = World::new();
+    let commands = world.commands();
+
+    let e = world.entity();
+    commands.insert(e, Position { x: 1, y: 2 });
+    e.destruct();
+
+    world.apply(&commands);
+
+    assert!(!world.is_alive(e));
+}
+
+#[test]
+fn commands_from_threads() {
+    let world = World::new();
+    let commands = world.commands_with_capacity(8);
+
+    let handles = (0..4)
+        .map(|i| {
+            let commands = commands.clone();
+            std::thread::spawn(move || {
+                let e = commands.spawn();
+                commands.insert(e, Position { x: i, y: i });
+                e
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let entities = handles
+        .into_iter()
+        .map(|h| h.join().unwrap())
+        .collect::<Vec<_>>();
+
+    world.apply(&commands);
+
+    assert_eq!(world.count::<Position>(), 4);
+    for e in entities {
+        assert!(world.entity_from_id(e).has::<Position>());
+    }
+}
+
+#[test]
+fn commands_apply_on_progress() {
+    let world = World::new();
+    let commands = world.commands();
+
+    let e = commands.spawn();
+    commands.insert(e, Position { x: 1, y: 2 });
+
+    world.progress();
+
+    assert!(world.entity_from_id(e).has::<Position>());
+}
+
+#[test]
+fn commands_apply_after_drop() {
+    let world = World::new();
+    let commands = world.commands();
+
+    let e = commands.spawn();
+    commands.add::<Tag>(e);
+    drop(commands);
+
+    world.progress();
+
+    assert!(world.entity_from_id(e).has::<Tag>());
+}
+
+#[test]
+fn commands_reserve_refill() {
+    let world = World::new();
+    let commands = world.commands_with_capacity(2);
+
+    let a = commands.spawn();
+    let b = commands.spawn();
+    assert_ne!(a, b);
+    assert!(commands.try_spawn().is_none());
+
+    world.apply(&commands);
+
+    assert_eq!(commands.reserved_count(), 2);
+    let c = commands.spawn();
+    assert_ne!(c, a);
+    assert_ne!(c, b);
+}
+
+#[test]
+fn commands_spawn_without_reserve() {
+    let world = World::new();
+    let commands = world.commands_with_capacity(0);
+
+    assert_eq!(commands.reserved_count(), 0);
+    let e = commands.spawn();
+    assert!(world.is_alive(e));
+}
+
+#[test]
+fn commands_spawn_deferred() {
+    let world = World::new();
+    let commands = world.commands_with_capacity(0);
+
+    let worker = commands.clone();
+    let e = std::thread::spawn(move || {
+        let e = worker.spawn();
+        worker.insert(e, Position { x: 1, y: 2 });
+        e
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(commands.resolve(e), e);
+    world.apply(&commands);
+
+    let created = commands.resolve(e);
+    assert_ne!(created, e);
+    world.entity_from_id(created).get::<&Position>(|pos| {
+        assert_eq!(pos.x, 1);
+        assert_eq!(pos.y, 2);
+    });
+
+    // the reserve grew to the number of ids spawned from other threads
+    assert_eq!(commands.reserved_count(), 1);
+    let worker = commands.clone();
+    let e = std::thread::spawn(move || worker.spawn()).join().unwrap();
+    assert!(world.is_alive(e));
+    assert_eq!(commands.resolve(e), e);
+}
+
+#[test]
+fn commands_spawn_reserved() {
+    let world = World::new();
+    let commands = world.commands();
+    assert!(commands.reserved_count() > 0);
+
+    let worker = commands.clone();
+    let e = std::thread::spawn(move || worker.spawn()).join().unwrap();
+    assert!(world.is_alive(e));
+    assert_eq!(commands.resolve(e), e);
+}
+
+#[test]
+fn commands_deferred_across_sync_points() {
+    let world = World::new();
+    let commands = world.commands_with_capacity(0);
+
+    let worker = commands.clone();
+    let e = std::thread::spawn(move || worker.spawn()).join().unwrap();
+    world.apply(&commands);
+
+    // commands recorded after the sync point that created the entity still reach it
+    commands.insert(e, Position { x: 1, y: 2 });
+    world.apply(&commands);
+    world.apply(&commands);
+
+    // the buffer forgets a deferred id once it is resolved
+    let created = commands.resolve(e);
+    assert_ne!(created, e);
+    assert!(world.entity_from_id(created).has::<Position>());
+    assert_eq!(commands.resolve(e), e);
+}
+
+#[test]
+fn commands_apply_on_app_run() {
+    let world = World::new();
+    let commands = world.commands();
+
+    let e = commands.spawn();
+    commands.add::<Tag>(e);
+
+    world.app().set_frames(1).run();
+
+    assert!(world.entity_from_id(e).has::<Tag>());
+}
+
+#[test]
+fn commands_custom() {
+    let world = World::new();
+    let commands = world.commands();
+
+    commands.push(|world| {
+        world.entity_named("from_command");
+    });
+
+    world.apply(&commands);
+
+    assert!(world.try_lookup("from_command").is_some());
+}
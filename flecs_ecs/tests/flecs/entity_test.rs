@@ -3047,6 +3047,305 @@ fn entity_slot() {
     assert!(inst.has_id((base_child, *flecs::Wildcard)));
 }
 
+#[test]
+fn entity_ordered_children() {
+    let world = World::new();
+
+    let parent = world.entity();
+    let a = world.entity().child_of_id(parent);
+    world.entity().child_of_id(parent);
+
+    parent.enable_ordered_children();
+    let c = world.entity().child_of_id(parent);
+    // table change doesn't affect the order
+    a.add::<TagA>();
+
+    assert_eq!(parent.children().len(), 3);
+    assert_eq!(parent.children()[2], c);
+    assert_eq!(c.index_in_parent(), Some(2));
+    assert_eq!(world.entity().index_in_parent(), None);
+}
+
+#[test]
+fn entity_ordered_children_insert_at() {
+    let world = World::new();
+
+    let parent = world.entity();
+    let a = world.entity();
+    let b = world.entity();
+    let c = world.entity();
+
+    parent
+        .insert_child_at(0, a)
+        .insert_child_at(0, b)
+        .insert_child_at(1, c);
+
+    assert!(a.has_id((*flecs::ChildOf, parent)));
+    assert_eq!(parent.children(), [b, c, a]);
+
+    parent.insert_child_at(100, b);
+    assert_eq!(parent.children(), [c, a, b]);
+    assert_eq!(b.index_in_parent(), Some(2));
+}
+
+#[test]
+fn entity_ordered_children_move() {
+    let world = World::new();
+
+    let parent = world.entity().enable_ordered_children();
+    let a = world.entity().child_of_id(parent);
+    let b = world.entity().child_of_id(parent);
+    let c = world.entity().child_of_id(parent);
+
+    parent.move_child_before(c, a);
+    assert_eq!(parent.children(), [c, a, b]);
+
+    parent.move_child_after(c, b);
+    assert_eq!(parent.children(), [a, b, c]);
+
+    parent.move_child_after(a, b);
+    assert_eq!(parent.children(), [b, a, c]);
+}
+
+#[test]
+fn entity_ordered_children_reparent_delete() {
+    let world = World::new();
+
+    let parent = world.entity().enable_ordered_children();
+    let other = world.entity().enable_ordered_children();
+    let a = world.entity().child_of_id(parent);
+    let b = world.entity().child_of_id(parent);
+    let c = world.entity().child_of_id(parent);
+
+    b.child_of_id(other);
+    assert_eq!(parent.children(), [a, c]);
+    assert_eq!(other.children(), [b]);
+
+    other.insert_child_at(0, c);
+    assert_eq!(parent.children(), [a]);
+    assert_eq!(other.children(), [c, b]);
+
+    c.destruct();
+    assert_eq!(other.children(), [b]);
+
+    b.remove_id((*flecs::ChildOf, other));
+    assert!(other.children().is_empty());
+    assert_eq!(b.index_in_parent(), None);
+
+    parent.destruct();
+    assert!(!a.is_alive());
+}
+
+#[test]
+fn entity_ordered_children_deferred() {
+    let world = World::new();
+
+    let parent = world.entity().enable_ordered_children();
+    let a = world.entity().child_of_id(parent);
+    let b = world.entity();
+
+    world.defer_begin();
+    parent.insert_child_at(0, b);
+    a.destruct();
+    world.defer_end();
+
+    assert_eq!(parent.children(), [b]);
+    assert!(b.has_id((*flecs::ChildOf, parent)));
+}
+
+#[test]
+fn entity_ordered_children_enable_deferred() {
+    let world = World::new();
+    let parent = world.entity();
+    let a = world.entity().child_of_id(parent);
+    world.defer_begin();
+    parent.enable_ordered_children();
+    let b = world.entity().child_of_id(parent);
+    world.defer_end();
+    let c = world.entity().child_of_id(parent);
+    assert_eq!(parent.children(), [a, b, c]);
+}
+
+#[test]
+fn entity_ordered_children_from_system() {
+    let world = World::new();
+
+    let parent = world.entity().add::<TagA>();
+    let a = world.entity().child_of_id(parent);
+    let b = world.entity().child_of_id(parent);
+    let c = world.entity();
+    let other = world.entity().add::<TagB>();
+    let d = world.entity().child_of_id(other);
+    let e = world.entity().child_of_id(other);
+    let f = world.entity();
+    let (c_id, d_id, e_id, f_id) = (c.id(), d.id(), e.id(), f.id());
+
+    world
+        .system::<()>()
+        .with::<TagA>()
+        .each_entity(move |parent, _| {
+            parent.insert_child_at(0, c_id);
+        });
+    world
+        .system::<()>()
+        .with::<TagB>()
+        .each_entity(move |other, _| {
+            other.move_child_before(e_id, d_id);
+        });
+    // adds a child before the observer that keeps the order up to date is created
+    let parent_id = parent.id();
+    world.system::<()>().run(move |it| {
+        it.world().entity_from_id(f_id).child_of_id(parent_id);
+    });
+    world.progress();
+
+    assert!(c.has_id((*flecs::ChildOf, parent)));
+    assert_eq!(parent.children(), [c, a, b, f]);
+    assert_eq!(other.children(), [e, d]);
+
+    let g = world.entity().child_of_id(other);
+    world.progress();
+    assert_eq!(other.children(), [e, d, g]);
+}
+
+#[test]
+fn entity_ordered_children_duplicate() {
+    let world = World::new();
+
+    let parent = world.entity().enable_ordered_children();
+    let a = world.entity().child_of_id(parent);
+    let b = world.entity().child_of_id(parent);
+    parent.move_child_before(b, a);
+
+    let copy = parent.duplicate(true);
+    assert!(copy.children().is_empty());
+
+    let c = world.entity().child_of_id(copy);
+    assert_eq!(copy.children(), [c]);
+    assert_eq!(parent.children(), [b, a]);
+}
+
+#[test]
+fn entity_ordered_children_prefab_instance() {
+    let world = World::new();
+
+    let prefab = world.prefab().enable_ordered_children();
+    world.prefab().child_of_id(prefab);
+    world.prefab().child_of_id(prefab);
+
+    let instance = world.entity().is_a_id(prefab);
+    let children = instance.children();
+
+    assert_eq!(children.len(), 2);
+    for child in children {
+        assert_eq!(child.parent().unwrap(), instance);
+    }
+}
+
+#[test]
+fn entity_instantiate() {
+    let world = World::new();
+
+    let base = world
+        .prefab()
+        .set(Position { x: 10, y: 20 })
+        .set(Velocity { x: 1, y: 2 });
+
+    let inst = base
+        .instantiate()
+        .override_with(Position { x: 30, y: 40 })
+        .override_with(Mass { value: 50 })
+        .build();
+
+    assert!(inst.has_id((*flecs::IsA, base)));
+    assert_eq!(inst.prefab(), base);
+    assert!(inst.owns::<Position>());
+    assert!(inst.owns::<Mass>());
+
+    inst.get::<(&Position, &Velocity, &Mass)>(|(p, v, m)| {
+        assert_eq!(p.x, 30);
+        assert_eq!(p.y, 40);
+        assert_eq!(v.x, 1);
+        assert_eq!(v.y, 2);
+        assert_eq!(m.value, 50);
+    });
+
+    base.get::<&Position>(|p| {
+        assert_eq!(p.x, 10);
+        assert_eq!(p.y, 20);
+    });
+}
+
+#[test]
+fn entity_instantiate_named_child_of() {
+    let world = World::new();
+
+    let base = world.prefab();
+    let parent = world.entity_named("parent");
+
+    let inst = base.instantiate().named("inst").child_of_id(parent).build();
+
+    assert_eq!(inst.name(), "inst");
+    assert_eq!(inst.path().unwrap(), "::parent::inst");
+    assert!(inst.has_id((*flecs::ChildOf, parent)));
+    assert_eq!(world.lookup("parent::inst"), inst.entity());
+
+    let inst = base.instantiate().child_of::<Parent>().build();
+    assert!(inst.has_second::<Parent>(*flecs::ChildOf));
+}
+
+#[test]
+fn entity_instantiate_slot() {
+    let world = World::new();
+
+    let base = world.prefab_named("Base");
+    let turret = world.prefab_named("Turret").child_of_id(base).slot();
+    let cannon = world
+        .prefab_named("Cannon")
+        .child_of_id(turret)
+        .slot_of_id(base);
+    world.prefab_named("Engine").child_of_id(base);
+
+    let inst = base.instantiate().build();
+
+    let inst_turret = inst.slot("Turret");
+    assert!(inst_turret.has_id((*flecs::ChildOf, inst.id())));
+    assert!(inst.has_id((turret, inst_turret)));
+
+    let inst_cannon = inst.slot("Cannon");
+    assert!(inst_cannon.has_id((*flecs::ChildOf, inst_turret)));
+    assert!(inst.has_id((cannon, inst_cannon)));
+
+    assert!(inst.try_slot("Engine").is_none());
+    assert!(inst.try_slot("Wing").is_none());
+}
+
+#[test]
+fn entity_instantiate_children() {
+    let world = World::new();
+
+    let base = world.prefab_named("Base");
+    let turret = world.prefab_named("Turret").child_of_id(base);
+    let cannon = world.prefab_named("Cannon").child_of_id(turret);
+    let engine = world.prefab_named("Engine").child_of_id(base);
+
+    let inst = base.instantiate().build();
+    let children = inst.children();
+    assert_eq!(children.len(), 3);
+
+    let inst_turret = inst.child(turret).unwrap();
+    let inst_cannon = inst.child(cannon).unwrap();
+    let inst_engine = inst.child(engine).unwrap();
+
+    assert_eq!(inst_turret, inst.lookup("Turret"));
+    assert_eq!(inst_cannon, inst.lookup("Turret::Cannon"));
+    assert_eq!(inst_engine, inst.lookup("Engine"));
+    assert!(inst_cannon.has_id((*flecs::ChildOf, inst_turret)));
+    assert!(!inst_turret.has::<flecs::Prefab>());
+
+    assert!(inst.child(base).is_none());
+}
+
 #[test]
 fn entity_id_get_entity() {
     let world = World::new();
@@ -3225,6 +3524,332 @@ fn entity_clone_to_existing_overlap() {
     src.duplicate_into(true, dst);
 }
 
+#[test]
+fn entity_clone_filtered() {
+    let world = World::new();
+
+    let v = Position { x: 10, y: 20 };
+
+    let src = world
+        .entity_named("src")
+        .add::<Tag>()
+        .set(v)
+        .set(Velocity { x: 1, y: 2 });
+    let velocity = world.component_id::<Velocity>();
+    let dst = src.duplicate_filtered(true, |id| id != velocity);
+
+    assert!(dst.has::<Tag>());
+    assert!(dst.has::<Position>());
+    assert!(!dst.has::<Velocity>());
+    assert!(dst.get_name().is_none());
+
+    dst.get::<&Position>(|pos| {
+        assert_eq!(pos.x, 10);
+        assert_eq!(pos.y, 20);
+    });
+}
+
+#[test]
+fn entity_clone_filtered_no_value() {
+    let world = World::new();
+
+    let src = world.entity().add::<Tag>().set(Position { x: 10, y: 20 });
+    let dst = src.duplicate_filtered(false, |_| true);
+
+    assert!(dst.has::<Tag>());
+    assert!(dst.has::<Position>());
+}
+
+#[test]
+fn entity_copy_to_world() {
+    let src_world = World::new();
+    let dst_world = World::new();
+
+    // register in a different order, so ids don't match between the worlds
+    dst_world.component::<Velocity>();
+    dst_world.component::<Value>();
+    dst_world.component::<Position>();
+
+    let src = src_world
+        .entity_named("Ship")
+        .add::<Tag>()
+        .set(Position { x: 10, y: 20 });
+
+    let dst = src.copy_to(&dst_world);
+
+    assert_ne!(
+        src_world.component_id::<Position>(),
+        dst_world.component_id::<Position>()
+    );
+    assert_eq!(dst.name(), "Ship");
+    assert!(dst.has::<Tag>());
+    assert!(dst.has::<Position>());
+
+    dst.get::<&Position>(|pos| {
+        assert_eq!(pos.x, 10);
+        assert_eq!(pos.y, 20);
+    });
+}
+
+#[test]
+fn entity_copy_to_world_w_children() {
+    let src_world = World::new();
+    let dst_world = World::new();
+
+    let parent = src_world
+        .entity_named("parent")
+        .set(Position { x: 1, y: 2 });
+    let child = src_world
+        .entity_named("child")
+        .child_of_id(parent)
+        .set(Position { x: 3, y: 4 });
+    src_world
+        .entity_named("grandchild")
+        .child_of_id(child)
+        .add::<Tag>();
+
+    let dst = parent.copy_to(&dst_world);
+
+    let dst_child = dst_world.lookup("parent::child");
+    assert_eq!(dst_child.parent().unwrap(), dst);
+    dst_child.get::<&Position>(|pos| {
+        assert_eq!(pos.x, 3);
+        assert_eq!(pos.y, 4);
+    });
+
+    let dst_grandchild = dst_world.lookup("parent::child::grandchild");
+    assert!(dst_grandchild.has::<Tag>());
+}
+
+#[test]
+fn entity_copy_to_world_w_pair() {
+    let src_world = World::new();
+    let dst_world = World::new();
+
+    let bob = src_world.entity_named("Bob");
+    let anonymous = src_world.entity();
+    let src = src_world
+        .entity()
+        .add_first::<Likes>(bob)
+        .add_first::<Eats>(anonymous);
+
+    let dst_bob = dst_world.entity_named("Bob");
+    let dst = src.copy_to(&dst_world);
+
+    assert!(dst.has_first::<Likes>(dst_bob));
+    assert!(!dst.has_first::<Eats>(flecs::Wildcard::ID));
+}
+
+#[test]
+fn entity_copy_to_world_w_non_clone() {
+    #[derive(Component)]
+    struct Label {
+        text: String,
+    }
+
+    #[derive(Component, Clone)]
+    struct Title {
+        text: String,
+    }
+
+    #[derive(Component)]
+    struct Handle {
+        value: u32,
+    }
+
+    let src_world = World::new();
+    let dst_world = World::new();
+
+    // register the copy hooks that panic, as for components that don't implement Clone
+    let handle = src_world.component::<Handle>().id();
+    unsafe {
+        let mut hooks = *flecs_ecs::sys::ecs_get_hooks_id(src_world.ptr_mut(), *handle);
+        register_copy_panic_lifecycle_action::<Handle>(&mut hooks);
+        flecs_ecs::sys::ecs_set_hooks_id(src_world.ptr_mut(), *handle, &hooks);
+    }
+
+    let src = src_world
+        .entity()
+        .set(Label {
+            text: "label".to_string(),
+        })
+        .set(Title {
+            text: "title".to_string(),
+        })
+        .set(Handle { value: 1 })
+        .set(Position { x: 10, y: 20 });
+
+    let dst = src.copy_to(&dst_world);
+
+    assert!(!dst.has::<Label>());
+    assert!(!dst.has::<Handle>());
+    dst.get::<(&Title, &Position)>(|(title, pos)| {
+        assert_eq!(title.text, "title");
+        assert_eq!(pos.x, 10);
+    });
+    src.get::<(&Label, &Handle)>(|(label, handle)| {
+        assert_eq!(label.text, "label");
+        assert_eq!(handle.value, 1);
+    });
+
+    let clone = src.duplicate_filtered(true, |_| true);
+
+    assert!(!clone.has::<Label>());
+    assert!(!clone.has::<Handle>());
+    clone.get::<(&Title, &Position)>(|(title, pos)| {
+        assert_eq!(title.text, "title");
+        assert_eq!(pos.x, 10);
+    });
+}
+
+#[test]
+fn entity_weak_upgrade() {
+    let world = World::new();
+
+    let e = world.entity();
+    let weak = e.downgrade();
+
+    assert_eq!(weak.id(), e.id());
+    assert_eq!(weak.upgrade(&world).unwrap(), e);
+    assert!(weak.is_alive(&world));
+}
+
+#[test]
+fn entity_weak_upgrade_recycled() {
+    let world = World::new();
+
+    let e = world.entity();
+    let weak = e.downgrade();
+    e.destruct();
+
+    let recycled = world.entity();
+    assert_eq!(strip_generation(recycled), strip_generation(weak.id()));
+    assert_ne!(get_generation(recycled), weak.generation());
+
+    assert!(weak.upgrade(&world).is_none());
+    assert!(recycled.downgrade().upgrade(&world).is_some());
+}
+
+#[test]
+fn entity_weak_from_id() {
+    let world = World::new();
+
+    let e = world.entity();
+    e.destruct();
+    let e = world.entity();
+
+    let weak = WeakEntity::new(e.id());
+    assert_eq!(Entity::from(weak), e.id());
+    assert_eq!(weak.generation(), get_generation(e));
+}
+
+#[test]
+fn entity_handle_keeps_alive() {
+    let world = World::new();
+
+    let e = world.entity();
+    let handle = e.handle();
+    let handle2 = handle.clone();
+    assert_eq!(handle.handle_count(), 2);
+    assert_eq!(handle.id(), e.id());
+
+    drop(handle);
+    world.progress();
+    assert!(e.is_alive());
+
+    drop(handle2);
+    assert!(e.is_alive());
+    world.progress();
+    assert!(!e.is_alive());
+}
+
+#[test]
+fn entity_handle_shared_count() {
+    let world = World::new();
+
+    let e = world.entity();
+    let handle = e.handle();
+    let handle2 = e.handle();
+
+    assert_eq!(handle, handle2);
+    assert_eq!(handle.handle_count(), 2);
+
+    drop(handle);
+    world.progress();
+    assert!(e.is_alive());
+    assert_eq!(handle2.entity_view(&world), e);
+    assert_eq!(handle2.downgrade().upgrade(&world).unwrap(), e);
+}
+
+#[test]
+fn entity_handle_in_component() {
+    #[derive(Component)]
+    struct Owner {
+        target: EntityHandle,
+    }
+
+    let world = World::new();
+
+    let target = world.entity();
+    let owner = world.entity().set(Owner {
+        target: target.handle(),
+    });
+
+    owner.destruct();
+    world.progress();
+
+    assert!(!target.is_alive());
+}
+
+#[test]
+fn entity_handle_duplicate() {
+    let world = World::new();
+
+    let e = world.entity();
+    let handle = e.handle();
+
+    let copy = e.duplicate(true);
+    let copy_handle = copy.handle();
+
+    assert_ne!(copy_handle, handle);
+    assert_eq!(handle.handle_count(), 1);
+    assert_eq!(copy_handle.handle_count(), 1);
+
+    drop(copy_handle);
+    world.progress();
+    assert!(!copy.is_alive());
+    assert!(e.is_alive());
+}
+
+#[test]
+fn entity_handle_prefab_instance() {
+    let world = World::new();
+
+    let prefab = world.prefab();
+    let handle = prefab.handle();
+    let instance = world.entity().is_a_id(prefab);
+
+    let instance_handle = instance.handle();
+    assert_ne!(instance_handle, handle);
+    assert_eq!(handle.handle_count(), 1);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn entity_weak_serde() {
+    let world = World::new();
+
+    let e = world.entity();
+    e.destruct();
+    let e = world.entity();
+
+    let json = serde_json::to_string(&e.downgrade()).unwrap();
+    let weak: WeakEntity = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(weak, e.downgrade());
+    assert_eq!(weak.upgrade(&world), Some(e));
+}
+
 // TODO set doc name test cases with doc addon
 
 #[test]
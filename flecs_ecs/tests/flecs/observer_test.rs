@@ -950,3 +950,81 @@ fn observer_name_from_root() {
     let ns = world.entity_named("::ns");
     assert!(ns == o.parent().unwrap());
 }
+
+#[test]
+fn observer_context_typed() {
+    let world = World::new();
+
+    type Log = std::sync::Mutex<Vec<i32>>;
+
+    let mut observer = world
+        .observer::<flecs::OnSet, &Position>()
+        .set_context_typed(Log::default())
+        .run(|mut it| {
+            while it.next() {
+                let p = it.field::<Position>(0).unwrap();
+                let x = p[0].x;
+                it.context_typed::<Log>().lock().unwrap().push(x);
+            }
+        });
+
+    world.entity().set(Position { x: 1, y: 0 });
+    world.entity().set(Position { x: 2, y: 0 });
+    assert_eq!(*observer.context_typed::<Log>().lock().unwrap(), [1, 2]);
+
+    observer.set_context_typed(Log::new(vec![0]));
+    world.entity().set(Position { x: 3, y: 0 });
+    assert_eq!(*observer.context_typed::<Log>().lock().unwrap(), [0, 3]);
+}
+
+#[test]
+fn observer_run_hooks() {
+    let world = World::new();
+
+    let log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let log_observer = log.clone();
+    let before = world
+        .observer_named::<flecs::OnSet, &Position>("Before")
+        .each(move |_| log_observer.borrow_mut().push("Before".to_string()));
+
+    let log_pre = log.clone();
+    let log_post = log.clone();
+    world.on_system_run(
+        move |run| {
+            assert!(run.is_observer);
+            assert_eq!(run.event, flecs::OnSet::ID);
+            assert_eq!(run.entity_count, 1);
+            log_pre
+                .borrow_mut()
+                .push(format!("pre {}", run.entity.name()));
+        },
+        move |run, _| {
+            log_post
+                .borrow_mut()
+                .push(format!("post {}", run.entity.name()))
+        },
+    );
+
+    let log_observer = log.clone();
+    world
+        .observer_named::<flecs::OnSet, &Position>("After")
+        .run(move |mut it| {
+            while it.next() {
+                log_observer.borrow_mut().push("After".to_string());
+            }
+        });
+
+    world.entity().set(Position { x: 1, y: 0 });
+    assert_eq!(
+        *log.borrow(),
+        [
+            "pre Before",
+            "Before",
+            "post Before",
+            "pre After",
+            "After",
+            "post After"
+        ]
+    );
+    assert!(before.is_alive());
+}
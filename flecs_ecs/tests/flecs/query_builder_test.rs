@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 use std::cell::Cell;
 use std::ffi::c_void;
+use std::rc::Rc;
 
 use crate::common_test::*;
 use flecs_ecs::sys;
@@ -509,6 +510,80 @@ fn query_builder_set_2_vars() {
     assert_eq!(count, 1);
 }
 
+#[test]
+fn query_builder_build_with_vars() {
+    let world = World::new();
+
+    let apples = world.entity();
+    let pears = world.entity();
+
+    let bob = world.entity().add_first::<Eats>(apples);
+
+    let alice = world
+        .entity()
+        .add_first::<Eats>(pears)
+        .add_first::<Likes>(bob);
+
+    bob.add_first::<Likes>(alice);
+
+    let (r, [food, person]) = world
+        .query::<()>()
+        .with::<&Eats>()
+        .set_second_name("$Food")
+        .with::<&Likes>()
+        .set_second_name("$Person")
+        .with_var("$Food")
+        .with_var("Person")
+        .build_with_vars()
+        .unwrap();
+
+    assert_eq!(r.query_var("Food"), Some(food));
+    assert_eq!(r.query_var("$Person"), Some(person));
+    assert_eq!(r.query_var("Drink"), None);
+
+    let mut count = 0;
+    r.iterable()
+        .set_var(food, pears)
+        .set_var(person, bob)
+        .each_iter(|it, index, ()| {
+            assert_eq!(it.entity(index), alice);
+            assert_eq!(it.var(food), pears);
+            assert_eq!(it.var(person), bob);
+            count += 1;
+        });
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn query_builder_build_with_vars_errors() {
+    let world = World::new();
+
+    let result = world
+        .query::<()>()
+        .with::<&Eats>()
+        .set_second_name("$Food")
+        .with_var("Drink")
+        .build_with_vars::<1>();
+    assert_eq!(
+        result.err().map(|err| err.to_string()),
+        Some("query has no variable named `$Drink`".to_string())
+    );
+
+    let result = world
+        .query::<()>()
+        .with::<&Eats>()
+        .set_second_name("$Food")
+        .with_var("Food")
+        .build_with_vars::<2>();
+    assert!(matches!(
+        result.err(),
+        Some(QueryVarError::CountMismatch {
+            declared: 1,
+            requested: 2
+        })
+    ));
+}
+
 #[test]
 fn query_builder_set_var_by_name() {
     let world = World::new();
@@ -2019,13 +2094,13 @@ fn query_builder_group_by_raw() {
     let q = world
         .query::<()>()
         .with::<&TagX>()
-        .group_by_id_fn(world.entity_from::<TagX>(), Some(group_by_first_id))
+        .group_by_id_action(world.entity_from::<TagX>(), Some(group_by_first_id))
         .build();
 
     let q_reverse = world
         .query::<()>()
         .with::<&TagX>()
-        .group_by_id_fn(world.entity_from::<TagX>(), Some(group_by_first_id_negated))
+        .group_by_id_action(world.entity_from::<TagX>(), Some(group_by_first_id_negated))
         .build();
 
     let e3 = world.entity().add::<TagX>().add::<TagC>();
@@ -2082,13 +2157,13 @@ fn query_builder_group_by_template() {
     let q = world
         .query::<()>()
         .with::<&TagX>()
-        .group_by_fn::<TagX>(Some(group_by_first_id))
+        .group_by_action::<TagX>(Some(group_by_first_id))
         .build();
 
     let q_reverse = world
         .query::<()>()
         .with::<&TagX>()
-        .group_by_fn::<TagX>(Some(group_by_first_id_negated))
+        .group_by_action::<TagX>(Some(group_by_first_id_negated))
         .build();
 
     let e3 = world.entity().add::<TagX>().add::<TagC>();
@@ -2168,7 +2243,7 @@ fn query_builder_group_by_iter_one() {
     let q = world
         .query::<()>()
         .with_id((rel, *flecs::Wildcard))
-        .group_by_id_fn(rel, Some(group_by_rel))
+        .group_by_id_action(rel, Some(group_by_rel))
         .build();
 
     let mut e2_found = false;
@@ -2208,7 +2283,7 @@ fn query_builder_group_by_iter_one_template() {
     let q = world
         .query::<()>()
         .with_first::<&Rel>(*flecs::Wildcard)
-        .group_by_fn::<Rel>(Some(group_by_rel))
+        .group_by_action::<Rel>(Some(group_by_rel))
         .build();
 
     let mut e2_found = false;
@@ -2254,7 +2329,7 @@ fn query_builder_group_by_iter_one_all_groups() {
     let q = world
         .query::<()>()
         .with_id((rel, *flecs::Wildcard))
-        .group_by_id_fn(rel, Some(group_by_rel))
+        .group_by_id_action(rel, Some(group_by_rel))
         .build();
 
     let group_id = Cell::new(0u64);
@@ -2469,8 +2544,8 @@ fn query_builder_group_by_callbacks() {
         .with_first::<&Rel>(*flecs::Wildcard)
         .group_by::<Rel>()
         .group_by_ctx(cell_count_group_ctx.as_ptr() as *mut c_void, None)
-        .on_group_create(Some(callback_group_create))
-        .on_group_delete(Some(callback_group_delete))
+        .on_group_create_action(Some(callback_group_create))
+        .on_group_delete_action(Some(callback_group_delete))
         .build();
 
     let mut e1_found = false;
@@ -2486,7 +2561,7 @@ fn query_builder_group_by_callbacks() {
             assert!(e2_found);
             assert!(e3_found);
             e1_found = true;
-            let ctx: *mut u64 = q.group_context(it.group_id()) as *mut u64;
+            let ctx: *mut u64 = q.group_context_ptr(it.group_id()) as *mut u64;
             assert_eq!(unsafe { *ctx }, it.group_id());
         }
         if e == e2 {
@@ -2495,7 +2570,7 @@ fn query_builder_group_by_callbacks() {
             assert!(!e2_found);
             assert!(e3_found);
             e2_found = true;
-            let ctx: *mut u64 = q.group_context(it.group_id()) as *mut u64;
+            let ctx: *mut u64 = q.group_context_ptr(it.group_id()) as *mut u64;
             assert_eq!(unsafe { *ctx }, it.group_id());
         }
         if e == e3 {
@@ -2504,7 +2579,7 @@ fn query_builder_group_by_callbacks() {
             assert!(!e2_found);
             assert!(!e3_found);
             e3_found = true;
-            let ctx: *mut u64 = q.group_context(it.group_id()) as *mut u64;
+            let ctx: *mut u64 = q.group_context_ptr(it.group_id()) as *mut u64;
             assert_eq!(unsafe { *ctx }, it.group_id());
         }
         count += 1;
@@ -2516,6 +2591,199 @@ fn query_builder_group_by_callbacks() {
     assert!(e3_found);
 }
 
+fn group_by_first_id_fn(_world: &World, table: Table, _id: Id) -> u64 {
+    *table.archetype().as_slice()[0]
+}
+
+fn group_by_first_id_negated_fn(world: &World, table: Table, id: Id) -> u64 {
+    !group_by_first_id_fn(world, table, id)
+}
+
+#[test]
+fn query_builder_group_by_closure() {
+    let world = World::new();
+
+    world.component::<TagA>();
+    world.component::<TagB>();
+    world.component::<TagC>();
+    world.component::<TagX>();
+
+    let q = world
+        .query::<()>()
+        .with::<&TagX>()
+        .group_by_fn::<TagX>(group_by_first_id_fn)
+        .build();
+
+    let q_reverse = world
+        .query::<()>()
+        .with::<&TagX>()
+        .group_by_fn::<TagX>(group_by_first_id_negated_fn)
+        .build();
+
+    let e3 = world.entity().add::<TagX>().add::<TagC>();
+    let e2 = world.entity().add::<TagX>().add::<TagB>();
+    let e1 = world.entity().add::<TagX>().add::<TagA>();
+
+    let mut count = 0;
+
+    q.run(|mut it| {
+        while it.next() {
+            assert_eq!(it.count(), 1);
+            if count == 0 {
+                assert!(it.entity(0) == e1);
+            } else if count == 1 {
+                assert!(it.entity(0) == e2);
+            } else if count == 2 {
+                assert!(it.entity(0) == e3);
+            } else {
+                panic!();
+            }
+            count += 1;
+        }
+    });
+    assert_eq!(count, 3);
+
+    count = 0;
+    q_reverse.run(|mut it| {
+        while it.next() {
+            assert_eq!(it.count(), 1);
+            if count == 0 {
+                assert!(it.entity(0) == e3);
+            } else if count == 1 {
+                assert!(it.entity(0) == e2);
+            } else if count == 2 {
+                assert!(it.entity(0) == e1);
+            } else {
+                panic!();
+            }
+            count += 1;
+        }
+    });
+    assert_eq!(count, 3);
+}
+
+#[test]
+fn query_builder_group_by_callbacks_closure() {
+    let cell_count_group_ctx = Rc::new(Cell::new(5u64));
+    let deleted = Rc::new(Cell::new(0));
+    let world = World::new();
+
+    let tgt_a = world.entity();
+    let tgt_b = world.entity();
+    let tgt_c = world.entity();
+
+    let e1 = world.entity().add_first::<Rel>(tgt_c);
+    let e2 = world.entity().add_first::<Rel>(tgt_b);
+    let e3 = world.entity().add_first::<Rel>(tgt_a);
+
+    let q = world
+        .query::<()>()
+        .with_first::<&Rel>(*flecs::Wildcard)
+        .group_by::<Rel>()
+        .on_group_create({
+            let cell_count = cell_count_group_ctx.clone();
+            move |_world, group_id| {
+                assert_ne!(group_id, 0);
+                assert_eq!(cell_count.get(), 5);
+                group_id
+            }
+        })
+        .on_group_delete({
+            let cell_count = cell_count_group_ctx.clone();
+            let deleted = deleted.clone();
+            move |_world, group_id, group_id_ctx: u64| {
+                assert_ne!(group_id, 0);
+                assert_eq!(cell_count.get(), 5);
+                assert_eq!(group_id_ctx, group_id);
+                deleted.set(deleted.get() + 1);
+            }
+        })
+        .build();
+
+    let mut e1_found = false;
+    let mut e2_found = false;
+    let mut e3_found = false;
+    let mut count = 0;
+
+    q.each_iter(|it: TableIter<false>, size: usize, ()| {
+        let e = it.entity(size);
+        if e == e1 {
+            assert_eq!(it.group_id(), tgt_c);
+            assert!(!e1_found);
+            assert!(e2_found);
+            assert!(e3_found);
+            e1_found = true;
+            assert_eq!(q.group_context::<u64>(it.group_id()), Some(&it.group_id()));
+        }
+        if e == e2 {
+            assert_eq!(it.group_id(), tgt_b);
+            assert!(!e1_found);
+            assert!(!e2_found);
+            assert!(e3_found);
+            e2_found = true;
+            assert_eq!(q.group_context::<u64>(it.group_id()), Some(&it.group_id()));
+        }
+        if e == e3 {
+            assert_eq!(it.group_id(), tgt_a);
+            assert!(!e1_found);
+            assert!(!e2_found);
+            assert!(!e3_found);
+            e3_found = true;
+            assert_eq!(q.group_context::<u64>(it.group_id()), Some(&it.group_id()));
+        }
+        count += 1;
+    });
+
+    assert_eq!(3, count);
+    assert!(e1_found);
+    assert!(e2_found);
+    assert!(e3_found);
+
+    assert_eq!(q.group_context::<i32>(tgt_a), None);
+
+    // groups are deleted together with the query, when the world is deleted
+    drop(q);
+    drop(world);
+    assert_eq!(deleted.get(), 3);
+}
+
+#[test]
+fn query_builder_group_by_dropped_unbuilt() {
+    let world = World::new();
+    let group_by_ctx = Rc::new(0);
+
+    let mut builder = world.query::<()>();
+    builder.with::<&TagX>().group_by_fn::<TagX>({
+        let group_by_ctx = group_by_ctx.clone();
+        move |_world, _table, _id| *group_by_ctx
+    });
+    assert_eq!(Rc::strong_count(&group_by_ctx), 2);
+
+    // the callbacks are freed together with the builder
+    drop(builder);
+    assert_eq!(Rc::strong_count(&group_by_ctx), 1);
+}
+
+#[test]
+fn query_builder_group_by_failed_build() {
+    let world = World::new();
+    let group_by_ctx = Rc::new(0);
+
+    let mut builder = world.query::<()>();
+    builder.expr("Unknown").group_by_fn::<TagX>({
+        let group_by_ctx = group_by_ctx.clone();
+        move |_world, _table, _id| *group_by_ctx
+    });
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| builder.build()));
+    assert!(result.is_err());
+
+    // the callbacks stay with the builder when the query can't be created
+    assert_eq!(Rc::strong_count(&group_by_ctx), 2);
+    drop(builder);
+    assert_eq!(Rc::strong_count(&group_by_ctx), 1);
+}
+
 #[test]
 fn query_builder_create_w_no_template_args() {
     let world = World::new();
@@ -4958,3 +5226,177 @@ fn query_builder_scope() {
 
     assert_eq!(count, 3);
 }
+
+#[test]
+fn query_builder_try_build() {
+    let world = World::new();
+
+    let q = world
+        .query::<&Position>()
+        .with::<&Velocity>()
+        .try_build()
+        .unwrap();
+
+    world
+        .entity()
+        .set(Position { x: 10, y: 20 })
+        .set(Velocity { x: 1, y: 2 });
+    assert_eq!(q.count(), 1);
+}
+
+#[test]
+fn query_builder_try_build_group_by() {
+    struct DropCount(Rc<Cell<usize>>);
+
+    impl Drop for DropCount {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    let world = World::new();
+    let drops = Rc::new(Cell::new(0));
+    let tracker = DropCount(drops.clone());
+
+    let q = world
+        .query::<&Position>()
+        .group_by_fn::<Position>(move |_world, _table, _id| {
+            let _ = &tracker;
+            10
+        })
+        .try_build()
+        .unwrap();
+
+    // the callbacks are owned by the query, not by the dropped builder
+    assert_eq!(drops.get(), 0);
+
+    world.entity().set(Position { x: 10, y: 20 });
+    let mut count = 0;
+    q.each_iter(|it, _, _| {
+        assert_eq!(it.group_id(), 10);
+        count += 1;
+    });
+    assert_eq!(count, 1);
+
+    // the cached query is destroyed together with the world
+    drop(q);
+    assert_eq!(drops.get(), 0);
+    drop(world);
+    assert_eq!(drops.get(), 1);
+}
+
+#[test]
+fn query_builder_try_build_unresolved() {
+    let world = World::new();
+
+    let err = world
+        .query::<&Position>()
+        .with_name("Foo")
+        .try_build()
+        .err()
+        .unwrap();
+
+    assert_eq!(err.message, "unresolved identifier 'Foo'");
+    assert_eq!(err.token, "Foo");
+    assert_eq!(err.term, Some(1));
+}
+
+#[test]
+fn query_builder_try_build_non_traversable() {
+    let world = World::new();
+
+    let err = world
+        .query::<()>()
+        .with::<&Position>()
+        .up_type::<Rel>()
+        .try_build()
+        .err()
+        .unwrap();
+
+    assert!(err
+        .message
+        .starts_with("cannot traverse non-traversable relationship"));
+    assert_eq!(err.term, Some(0));
+}
+
+#[test]
+fn query_builder_try_build_too_many_terms() {
+    let world = World::new();
+
+    let mut builder = world.query::<()>();
+    for _ in 0..sys::FLECS_TERM_COUNT_MAX + 1 {
+        builder.with::<&Position>();
+    }
+
+    let err = builder.try_build().err().unwrap();
+    assert_eq!(err.term, Some(sys::FLECS_TERM_COUNT_MAX as usize));
+}
+
+#[test]
+fn query_builder_try_query_from_expr() {
+    let world = World::new();
+    world.component::<Position>();
+    world.component::<Velocity>();
+
+    let q = world
+        .try_query_from_expr("flecs.common_test.Position, !flecs.common_test.Velocity")
+        .unwrap();
+
+    world.entity().set(Position { x: 10, y: 20 });
+    world
+        .entity()
+        .set(Position { x: 10, y: 20 })
+        .set(Velocity { x: 1, y: 2 });
+    assert_eq!(q.count(), 1);
+}
+
+#[test]
+fn query_builder_try_query_from_expr_errors() {
+    let world = World::new();
+    world.component::<Position>();
+
+    let err = world
+        .try_query_from_expr("flecs.common_test.Position, Foo")
+        .err()
+        .unwrap();
+    assert_eq!(err.message, "unresolved identifier 'Foo'");
+    assert_eq!(err.position, 28);
+    assert_eq!(err.token, "Foo");
+    assert_eq!(err.term, Some(1));
+
+    let err = world.try_query_from_expr("!!Foo").err().unwrap();
+    assert_eq!(err.message, "unexpected '!'");
+    assert_eq!(err.position, 1);
+    assert_eq!(err.token, "!");
+
+    let err = world
+        .try_query_from_expr("flecs.common_test.Position(up Bar)")
+        .err()
+        .unwrap();
+    assert_eq!(err.position, 30);
+    assert_eq!(err.token, "Bar");
+    assert_eq!(
+        err.to_string(),
+        "unresolved traversal relationship 'Bar' (at 30, near `Bar`)"
+    );
+}
+
+#[test]
+fn query_builder_try_query_from_expr_threads() {
+    let threads: Vec<_> = (0..4)
+        .map(|i| {
+            std::thread::spawn(move || {
+                let world = World::new();
+                for _ in 0..20 {
+                    let token = format!("Foo{i}");
+                    let err = world.try_query_from_expr(&token).err().unwrap();
+                    assert_eq!(err.token, token);
+                }
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
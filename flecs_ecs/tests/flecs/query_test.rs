@@ -1,4 +1,5 @@
 #![allow(dead_code)]
+use crate::common_test::*;
 use flecs_ecs::core::*;
 use flecs_ecs::macros::*;
 
@@ -56,3 +57,749 @@ fn query_iter_stage() {
 
     world.progress();
 }
+
+#[test]
+fn query_par_each() {
+    let world = World::new();
+
+    for i in 0..1000 {
+        world
+            .entity()
+            .set(Position { x: i, y: 0 })
+            .set(Velocity { x: 1, y: 2 });
+    }
+    // different table
+    for i in 0..10 {
+        world
+            .entity()
+            .set(Position { x: i, y: 0 })
+            .set(Velocity { x: 1, y: 2 })
+            .add::<TagA>();
+    }
+
+    let query = world.new_query::<(&mut Position, &Velocity)>();
+    let count = std::sync::atomic::AtomicUsize::new(0);
+    query.par_each(|(p, v)| {
+        p.x += v.x;
+        p.y += v.y;
+        count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    });
+
+    assert_eq!(count.into_inner(), 1010);
+    query.each(|(p, _)| {
+        assert_eq!(p.y, 2);
+    });
+}
+
+#[test]
+fn query_par_each_entity_deferred() {
+    let world = World::new();
+
+    // components can't be registered while iterating in parallel
+    world.component::<TagA>();
+    world.component::<Velocity>();
+
+    for i in 0..100 {
+        world.entity().set(Position { x: i, y: 0 });
+    }
+
+    world.new_query::<&Position>().par_each_entity(|e, p| {
+        if p.x % 2 == 0 {
+            e.add::<TagA>();
+        }
+        e.set(Velocity { x: p.x, y: 0 });
+    });
+
+    assert!(!world.is_readonly());
+    assert_eq!(world.count::<TagA>(), 50);
+    world.new_query::<(&Position, &Velocity)>().each(|(p, v)| {
+        assert_eq!(p.x, v.x);
+    });
+}
+
+#[test]
+fn query_par_each_entity_child_of() {
+    let world = World::new();
+
+    let parent = world.entity();
+    let parent_id = parent.id();
+    let children = (0..10)
+        .map(|i| world.entity().set(Position { x: i, y: 0 }))
+        .collect::<Vec<_>>();
+
+    world.new_query::<&Position>().par_each_entity(|e, _| {
+        e.child_of_id(parent_id);
+    });
+
+    assert_eq!(parent.children().len(), 10);
+    for child in children {
+        assert!(parent.children().contains(&child));
+    }
+}
+
+#[test]
+fn query_par_each_shared_in() {
+    let world = World::new();
+
+    world.set(Mass { value: 3 });
+    for _ in 0..100 {
+        world.entity().set(Position { x: 0, y: 0 });
+    }
+
+    let query = world
+        .query::<(&mut Position, &Mass)>()
+        .term_at(1)
+        .singleton()
+        .build();
+    query.par_each(|(p, m)| {
+        p.x += m.value;
+    });
+
+    query.each(|(p, _)| assert_eq!(p.x, 3));
+}
+
+#[test]
+#[should_panic]
+fn query_par_each_shared_mut() {
+    let world = World::new();
+
+    world.set(Mass { value: 3 });
+    world.entity().set(Position { x: 0, y: 0 });
+
+    let query = world
+        .query::<(&Position, &mut Mass)>()
+        .term_at(1)
+        .singleton()
+        .build();
+    query.par_each(|(_, m)| {
+        m.value += 1;
+    });
+}
+
+#[test]
+fn query_iter_for_loop() {
+    let world = World::new();
+
+    let e1 = world.entity().set(Position { x: 10, y: 20 });
+    let e2 = world
+        .entity()
+        .set(Position { x: 30, y: 40 })
+        .set(Velocity { x: 1, y: 2 });
+
+    let query = world.new_query::<(&mut Position, Option<&Velocity>)>();
+
+    let mut count = 0;
+    query.iter(|iter| {
+        for (e, (p, v)) in iter {
+            if let Some(v) = v {
+                assert_eq!(e, e2);
+                p.x += v.x;
+                p.y += v.y;
+            } else {
+                assert_eq!(e, e1);
+            }
+            count += 1;
+        }
+    });
+    assert_eq!(count, 2);
+
+    e2.get::<&Position>(|p| {
+        assert_eq!(p.x, 31);
+        assert_eq!(p.y, 42);
+    });
+}
+
+#[test]
+fn query_iter_adapters() {
+    let world = World::new();
+
+    for i in 0..10 {
+        let e = world.entity().set(Position { x: i, y: 0 });
+        if i % 3 == 0 {
+            e.add::<TagA>();
+        }
+    }
+
+    let query = world.new_query::<&Position>();
+
+    let mut xs: Vec<i32> = query.iter(|iter| iter.map(|(_, p)| p.x).collect());
+    xs.sort();
+    assert_eq!(xs, (0..10).collect::<Vec<_>>());
+
+    let sum: i32 = query.iter(|iter| {
+        iter.filter(|(e, _)| e.has::<TagA>())
+            .map(|(_, p)| p.x)
+            .sum()
+    });
+    assert_eq!(sum, 18);
+    assert_eq!(query.iter(|iter| iter.count()), 10);
+}
+
+#[test]
+fn query_iter_early_break() {
+    let world = World::new();
+
+    for i in 0..10 {
+        world
+            .entity()
+            .set(Position { x: i, y: 0 })
+            .add_id(world.entity());
+    }
+
+    let query = world.query::<&Position>().set_cached().build();
+
+    for _ in 0..3 {
+        assert!(query.iter(|mut iter| iter.next().is_some()));
+        assert!(query.iter(|mut iter| iter.any(|(_, p)| p.x == 5)));
+    }
+
+    // tables are unlocked again after the iteration ends
+    let entities: Vec<Entity> = query.iter(|iter| iter.take(3).map(|(e, _)| e.id()).collect());
+    for e in entities {
+        world.entity_from_id(e).add::<TagA>();
+    }
+    assert_eq!(world.count::<TagA>(), 3);
+}
+
+#[test]
+fn query_iter_w_wildcard() {
+    let world = World::new();
+
+    let apples = world.entity();
+    let pears = world.entity();
+    world
+        .entity()
+        .set(Position { x: 1, y: 0 })
+        .add_id((apples, apples));
+    world
+        .entity()
+        .set(Position { x: 2, y: 0 })
+        .add_id((pears, pears));
+
+    let query = world
+        .query::<&Position>()
+        .with_id((apples, *flecs::Wildcard))
+        .build();
+    let xs: Vec<i32> = query.iter(|iter| iter.map(|(_, p)| p.x).collect());
+    assert_eq!(xs, [1]);
+}
+
+#[test]
+fn query_chunks() {
+    let world = World::new();
+
+    for i in 0..10 {
+        let e = world
+            .entity()
+            .set(Position { x: i, y: 0 })
+            .set(Velocity { x: 1, y: 2 });
+        if i >= 6 {
+            e.add::<TagA>();
+        }
+    }
+
+    let query = world.new_query::<(&mut Position, &Velocity)>();
+
+    let mut sizes = Vec::new();
+    query.chunks(|chunks| {
+        for chunk in chunks {
+            let (positions, velocities) = chunk.components;
+            assert_eq!(positions.len(), chunk.entities.len());
+            assert_eq!(velocities.len(), chunk.entities.len());
+            assert_eq!(chunk.table.unwrap().count(), chunk.entities.len() as i32);
+
+            for (p, v) in positions.iter_mut().zip(velocities.iter()) {
+                p.x += v.x;
+                p.y += v.y;
+            }
+            sizes.push(chunk.entities.len());
+        }
+    });
+    sizes.sort();
+    assert_eq!(sizes, [4, 6]);
+
+    query.each(|(p, _)| assert_eq!(p.y, 2));
+}
+
+#[test]
+fn query_chunks_singleton() {
+    let world = World::new();
+
+    world.set(Mass { value: 5 });
+
+    let query = world.query::<&Mass>().term_at(0).singleton().build();
+
+    query.chunks(|chunks| {
+        let chunks: Vec<_> = chunks.collect();
+        assert_eq!(chunks.len(), 1);
+        assert!(chunks[0].entities.is_empty());
+        assert_eq!(chunks[0].components[0].value, 5);
+    });
+}
+
+#[test]
+#[should_panic]
+fn query_iter_mut_singleton() {
+    let world = World::new();
+
+    world.set(Mass { value: 5 });
+    world.entity().set(Position { x: 1, y: 2 });
+    world.entity().set(Position { x: 3, y: 4 });
+
+    let query = world
+        .query::<(&Position, &mut Mass)>()
+        .term_at(1)
+        .singleton()
+        .build();
+
+    // every result would hand out a mutable reference to the same singleton
+    query.iter(|iter| iter.count());
+}
+
+#[test]
+fn query_page() {
+    let world = World::new();
+
+    for i in 0..10 {
+        let e = world.entity().set(Position { x: 9 - i, y: 0 });
+        if i % 2 == 0 {
+            e.set(Velocity { x: 0, y: 0 });
+        }
+    }
+
+    let cached = world
+        .query::<&Position>()
+        .set_cache_kind(QueryCacheKind::Auto)
+        .build();
+    let uncached = world.new_query::<&Position>();
+    let sorted = world
+        .query::<&Position>()
+        .order_by::<Position>(|_e1, p1: &Position, _e2, p2: &Position| -> i32 {
+            (p1.x > p2.x) as i32 - (p1.x < p2.x) as i32
+        })
+        .build();
+
+    for query in [&cached, &uncached, &sorted] {
+        let mut all = Vec::new();
+        query.each(|p| all.push(p.x));
+        assert_eq!(query.count(), 10);
+
+        let mut page = Vec::new();
+        query.iterable().page(3, 4).each(|p| page.push(p.x));
+        assert_eq!(page, all[3..7]);
+
+        assert_eq!(query.iterable().page(3, 4).count(), 4);
+        assert_eq!(query.iterable().page(8, 0).count(), 2);
+        assert_eq!(query.iterable().page(20, 5).count(), 0);
+        assert!(query.iterable().page(8, 0).is_true());
+        assert!(!query.iterable().page(20, 5).is_true());
+        query
+            .iterable()
+            .page(3, 4)
+            .first_entity()
+            .unwrap()
+            .get::<&Position>(|p| assert_eq!(p.x, all[3]));
+    }
+
+    let mut page = Vec::new();
+    sorted.iterable().page(5, 3).each(|p| page.push(p.x));
+    assert_eq!(page, [5, 6, 7]);
+}
+
+#[test]
+fn query_page_w_var() {
+    let world = World::new();
+
+    let apples = world.entity();
+    let pears = world.entity();
+    for _ in 0..5 {
+        world.entity().add_first::<Likes>(apples);
+        world.entity().add_first::<Likes>(pears);
+    }
+
+    let mut query = world
+        .query::<()>()
+        .with::<&Likes>()
+        .set_second_name("$food")
+        .build();
+    let food = query.query_var("food").unwrap();
+
+    assert_eq!(query.count(), 10);
+    assert_eq!(query.page(2, 0).set_var(food, pears).count(), 3);
+    assert_eq!(query.set_var(food, pears).page(1, 2).count(), 2);
+}
+
+#[test]
+fn query_describe() {
+    let world = World::new();
+
+    let parent = world.entity().set(Mass { value: 10 });
+    world
+        .entity()
+        .set(Position { x: 1, y: 2 })
+        .child_of_id(parent);
+    world
+        .entity()
+        .set(Position { x: 3, y: 4 })
+        .child_of_id(parent);
+    world
+        .entity()
+        .set(Position { x: 5, y: 6 })
+        .set(Velocity { x: 1, y: 1 })
+        .child_of_id(parent);
+    world.entity().set(Position { x: 7, y: 8 });
+
+    let query = world
+        .query::<(&mut Position, &Mass)>()
+        .term_at(1)
+        .parent()
+        .with::<&Velocity>()
+        .optional()
+        .set_cached()
+        .build();
+
+    let description = query.describe();
+    // all terms can be cached, so the Auto cache kind resolves to All
+    assert_eq!(description.cache_kind, QueryCacheKind::All);
+    assert!(description.variables.is_empty());
+    assert_eq!(description.terms.len(), 3);
+
+    let position = description.terms[0];
+    assert_eq!(position.id(), world.component_id::<Position>());
+    assert_eq!(position.inout(), InOutKind::InOut);
+    assert_eq!(position.oper(), OperKind::And);
+    assert_eq!(position.src(), TermSource::This);
+    assert_eq!(position.field_index(), 0);
+
+    let mass = description.terms[1];
+    assert_eq!(mass.inout(), InOutKind::In);
+    assert_eq!(mass.src(), TermSource::This);
+    let traversal = mass.traversal();
+    assert!(traversal.up);
+    assert!(!traversal.self_);
+    assert_eq!(traversal.relationship, flecs::ChildOf::ID);
+
+    assert_eq!(description.terms[2].oper(), OperKind::Optional);
+
+    assert_eq!(description.tables.len(), 2);
+    assert_eq!(description.entity_count(), 3);
+    let counts: Vec<usize> = description.tables.iter().map(|t| t.count).collect();
+    assert!(counts.contains(&2) && counts.contains(&1));
+    for table in &description.tables {
+        assert!(table
+            .archetype()
+            .as_slice()
+            .contains(&world.component_id::<Position>().into()));
+        assert_eq!(table.archetype().count(), table.table.archetype().count());
+    }
+}
+
+#[test]
+fn query_describe_w_var_and_fixed_src() {
+    let world = World::new();
+    world.set(Mass { value: 1 });
+
+    let apples = world.entity();
+    let pears = world.entity();
+    world.entity().add_first::<Likes>(apples);
+    world.entity().add_first::<Likes>(pears);
+    world.entity().add_first::<Likes>(pears).add::<Position>();
+
+    let query = world
+        .query::<&Mass>()
+        .term_at(0)
+        .singleton()
+        .with::<&Likes>()
+        .set_second_name("$food")
+        .with::<Position>()
+        .not()
+        .set_cache_kind(QueryCacheKind::None)
+        .build();
+
+    let description = query.describe();
+    assert_eq!(description.cache_kind, QueryCacheKind::None);
+    assert_eq!(description.variables, ["food"]);
+    assert_eq!(
+        description.terms[0].src(),
+        TermSource::Entity(world.component_id::<Mass>().into())
+    );
+    assert_eq!(description.terms[1].src(), TermSource::This);
+    assert_eq!(description.terms[2].oper(), OperKind::Not);
+
+    // both (Likes, apples) and (Likes, pears) tables are matched
+    assert_eq!(description.tables.len(), 2);
+    assert_eq!(description.entity_count(), 2);
+}
+
+fn changed_entities(query: &Query<&Position>) -> Vec<Entity> {
+    let mut changed = Vec::new();
+    query.run(|mut it| {
+        while it.next() {
+            it.each_changed::<Position>(|row| changed.push(it.entity(row).id()));
+        }
+    });
+    changed.sort();
+    changed
+}
+
+#[test]
+fn query_each_changed() {
+    let world = World::new();
+    eprintln!("0");
+    world.track_row_changes::<Position>();
+    eprintln!("1");
+    assert!(world.is_row_change_tracked::<Position>());
+    eprintln!("2");
+    assert!(!world.is_row_change_tracked::<Velocity>());
+
+    let entities: Vec<_> = (0..10)
+        .map(|i| world.entity().set(Position { x: i, y: 0 }).id())
+        .collect();
+    eprintln!("3");
+    let query = world.query::<&Position>().set_cached().build();
+    eprintln!("A");
+    assert_eq!(changed_entities(&query), entities);
+    eprintln!("B");
+    assert!(changed_entities(&query).is_empty());
+
+    // set, modified and mutable get each record a change
+    world
+        .entity_from_id(entities[1])
+        .set(Position { x: 10, y: 0 });
+    world.entity_from_id(entities[4]).modified::<Position>();
+    world
+        .entity_from_id(entities[7])
+        .get::<&mut Position>(|p| p.x += 1);
+    world.entity_from_id(entities[8]).get::<&Position>(|_| {});
+    assert_eq!(
+        changed_entities(&query),
+        [entities[1], entities[4], entities[7]]
+    );
+
+    // changes are kept when the entity moves to another table
+    world
+        .entity_from_id(entities[2])
+        .set(Position { x: 20, y: 0 })
+        .add::<Velocity>();
+    world.entity_from_id(entities[3]).destruct();
+    assert_eq!(changed_entities(&query), [entities[2]]);
+    assert!(changed_entities(&query).is_empty());
+}
+
+#[test]
+fn query_each_changed_per_query() {
+    let world = World::new();
+    world.track_row_changes::<Position>();
+
+    let a = world.entity().set(Position { x: 0, y: 0 }).id();
+    let b = world.entity().set(Position { x: 0, y: 0 }).id();
+    let q1 = world.new_query::<&Position>();
+    let q2 = world.new_query::<&Position>();
+
+    assert_eq!(changed_entities(&q1), [a, b]);
+    world.entity_from_id(a).set(Position { x: 1, y: 0 });
+    assert_eq!(changed_entities(&q1), [a]);
+    assert_eq!(changed_entities(&q2), [a, b]);
+}
+
+#[test]
+fn query_each_changed_early_break() {
+    let world = World::new();
+    world.track_row_changes::<Position>();
+
+    let a = world.entity().set(Position { x: 0, y: 0 }).id();
+    let b = world
+        .entity()
+        .set(Position { x: 0, y: 0 })
+        .add::<Velocity>()
+        .id();
+    let query = world.query::<&Position>().set_cached().build();
+
+    // stop after the first table, the rows of the other table are reported by the next run
+    let mut first = Vec::new();
+    query.run(|mut it| {
+        it.next();
+        it.each_changed::<Position>(|row| first.push(it.entity(row).id()));
+        it.fini();
+    });
+    assert_eq!(first.len(), 1);
+
+    let rest = changed_entities(&query);
+    assert_eq!(rest.len(), 1);
+    first.extend(rest);
+    first.sort();
+    assert_eq!(first, [a, b]);
+    assert!(changed_entities(&query).is_empty());
+}
+
+#[test]
+fn query_each_changed_new_query() {
+    let world = World::new();
+    world.track_row_changes::<Position>();
+
+    let e = world.entity().set(Position { x: 0, y: 0 }).id();
+
+    // the cursor of a query is dropped with the query, so a query that is created later (and
+    // may reuse its memory) starts with all changes
+    for _ in 0..3 {
+        let query = world.new_query::<&Position>();
+        assert_eq!(changed_entities(&query), [e]);
+        assert!(changed_entities(&query).is_empty());
+    }
+}
+
+#[test]
+fn system_each_changed_ignores_own_writes() {
+    let world = World::new();
+    world.track_row_changes::<Position>();
+
+    world.entity().set(Position { x: 0, y: 0 });
+    let b = world.entity().set(Position { x: 0, y: 0 });
+    let visited = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+
+    let visited_sys = visited.clone();
+    world.system::<&Position>().run(move |mut it| {
+        while it.next() {
+            it.each_changed::<Position>(|row| {
+                let e = it.entity(row);
+                visited_sys.borrow_mut().push(e.id());
+                e.set(Position { x: 1, y: 1 });
+            });
+        }
+    });
+
+    world.progress();
+    assert_eq!(visited.borrow().len(), 2);
+
+    visited.borrow_mut().clear();
+    world.progress();
+    assert!(visited.borrow().is_empty());
+
+    b.set(Position { x: 5, y: 5 });
+    world.progress();
+    assert_eq!(*visited.borrow(), [b.id()]);
+}
+
+#[test]
+fn query_either() {
+    let world = World::new();
+
+    let a = world
+        .entity()
+        .set(Position { x: 1, y: 2 })
+        .set(Mass { value: 10 });
+    let b = world
+        .entity()
+        .set(Velocity { x: 3, y: 4 })
+        .set(Mass { value: 20 });
+    world.entity().set(Mass { value: 30 });
+
+    let query = world.new_query::<(Either<&Position, &Velocity>, &Mass)>();
+    assert_eq!(query.field_count(), 2);
+
+    let mut matched = Vec::new();
+    query.each_entity(|e, (either, mass)| {
+        match either {
+            Either::Left(p) => assert_eq!((p.x, p.y), (1, 2)),
+            Either::Right(v) => assert_eq!((v.x, v.y), (3, 4)),
+        }
+        matched.push((e.id(), either.is_left(), mass.value));
+    });
+    assert_eq!(matched, [(a.id(), true, 10), (b.id(), false, 20)]);
+
+    let mut count = 0;
+    query.chunks(|chunks| {
+        for chunk in chunks {
+            let (either, mass) = chunk.components;
+            match either {
+                Either::Left(p) => assert_eq!(p.len(), mass.len()),
+                Either::Right(v) => assert_eq!(v.len(), mass.len()),
+            }
+            count += chunk.entities.len();
+        }
+    });
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn query_either_mut() {
+    let world = World::new();
+
+    let a = world.entity().set(Position { x: 1, y: 2 });
+    let b = world.entity().set(Velocity { x: 3, y: 4 });
+
+    world
+        .new_query::<Either<&mut Position, &mut Velocity>>()
+        .each(|either| match either {
+            Either::Left(p) => p.x += 10,
+            Either::Right(v) => v.y += 10,
+        });
+
+    a.get::<&Position>(|p| assert_eq!((p.x, p.y), (11, 2)));
+    b.get::<&Velocity>(|v| assert_eq!((v.x, v.y), (3, 14)));
+}
+
+#[test]
+fn query_either_nested() {
+    let world = World::new();
+
+    world.entity().set(Position { x: 1, y: 0 });
+    world.entity().set(Velocity { x: 2, y: 0 });
+    world.entity().set(Mass { value: 3 });
+    world
+        .entity()
+        .set(Position { x: 4, y: 0 })
+        .set(Mass { value: 100 });
+
+    let mut sum = 0;
+    world
+        .new_query::<Either<&Position, Either<&Velocity, &Mass>>>()
+        .each(|either| match either {
+            Either::Left(p) => sum += p.x,
+            Either::Right(Either::Left(v)) => sum += v.x,
+            Either::Right(Either::Right(m)) => sum += m.value,
+        });
+    assert_eq!(sum, 10);
+}
+
+#[test]
+fn query_macro_or() {
+    let world = World::new();
+
+    world
+        .entity()
+        .set(Position { x: 1, y: 0 })
+        .set(Mass { value: 1 });
+    world
+        .entity()
+        .set(Velocity { x: 2, y: 0 })
+        .set(Mass { value: 1 });
+    world.entity().set(Velocity { x: 4, y: 0 });
+
+    let mut sum = 0;
+    query!(world, &Position || &Velocity, &Mass)
+        .build()
+        .each(|(either, mass)| match either {
+            Either::Left(p) => sum += p.x * mass.value,
+            Either::Right(v) => sum += v.x * mass.value,
+        });
+    assert_eq!(sum, 3);
+}
+
+#[test]
+fn query_context_typed() {
+    let world = World::new();
+
+    world.entity().set(Position { x: 1, y: 0 });
+    let query = world
+        .query::<&Position>()
+        .set_context_typed(String::from("positions"))
+        .build();
+
+    assert_eq!(query.context_typed::<String>(), "positions");
+    query.run(|mut it| {
+        while it.next() {
+            assert_eq!(it.context_typed::<String>(), "positions");
+        }
+    });
+}
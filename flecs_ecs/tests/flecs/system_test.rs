@@ -6958,3 +6958,1435 @@ fn system_randomize_timers() {
 //                     assert_eq!(c.0,2);
 //                 });
 // }
+
+#[derive(Component, Debug, PartialEq)]
+struct Momentum(i32);
+
+#[test]
+fn system_derive_on_set_and_remove() {
+    let world = World::new();
+
+    world.derive::<Momentum, (&Velocity, &Mass)>(|(v, m)| Momentum(v.x * m.value));
+
+    let e = world.entity().set(Velocity { x: 2, y: 0 });
+    assert!(!e.has::<Momentum>());
+
+    e.set(Mass { value: 3 });
+    e.get::<&Momentum>(|p| assert_eq!(*p, Momentum(6)));
+
+    e.set(Velocity { x: 5, y: 0 });
+    e.get::<&Momentum>(|p| assert_eq!(*p, Momentum(15)));
+
+    e.remove::<Mass>();
+    assert!(!e.has::<Momentum>());
+
+    e.set(Mass { value: 1 });
+    e.get::<&Momentum>(|p| assert_eq!(*p, Momentum(5)));
+
+    e.destruct();
+    assert!(!e.is_alive());
+}
+
+#[test]
+fn system_derive_recomputes_changed_tables() {
+    let world = World::new();
+
+    let computed = std::rc::Rc::new(std::cell::Cell::new(0));
+    let computed_derive = computed.clone();
+    world.derive::<Momentum, (&Velocity, &Mass)>(move |(v, m)| {
+        computed_derive.set(computed_derive.get() + 1);
+        Momentum(v.x * m.value)
+    });
+
+    let a = world
+        .entity()
+        .set(Velocity { x: 1, y: 0 })
+        .set(Mass { value: 2 });
+    let b = world
+        .entity()
+        .set(Velocity { x: 1, y: 0 })
+        .set(Mass { value: 2 })
+        .add::<Tag>();
+
+    world.progress();
+    computed.set(0);
+
+    // nothing changed
+    world.progress();
+    assert_eq!(computed.get(), 0);
+
+    // writes through a query are picked up by the system, for the changed table only
+    world
+        .query::<&mut Velocity>()
+        .with::<Tag>()
+        .build()
+        .each(|v| v.x = 10);
+    world.progress();
+    assert_eq!(computed.get(), 1);
+    a.get::<&Momentum>(|p| assert_eq!(*p, Momentum(2)));
+    b.get::<&Momentum>(|p| assert_eq!(*p, Momentum(20)));
+}
+
+#[test]
+fn system_derive_destruct() {
+    let world = World::new();
+
+    let derived = world.derive::<Momentum, (&Velocity, &Mass)>(|(v, m)| Momentum(v.x * m.value));
+
+    let e = world
+        .entity()
+        .set(Velocity { x: 1, y: 0 })
+        .set(Mass { value: 2 });
+    e.get::<&Momentum>(|p| assert_eq!(*p, Momentum(2)));
+
+    derived.destruct();
+    e.set(Velocity { x: 4, y: 0 });
+    world.progress();
+    e.get::<&Momentum>(|p| assert_eq!(*p, Momentum(2)));
+}
+
+#[derive(Component)]
+struct PhysicsSet;
+
+#[derive(Component)]
+struct RenderSet;
+
+fn push_run(
+    builder: &mut flecs_ecs::addons::system::SystemBuilder<()>,
+    name: &'static str,
+    log: &std::rc::Rc<std::cell::RefCell<Vec<&'static str>>>,
+) -> Entity {
+    let log = log.clone();
+    builder.run(move |_| log.borrow_mut().push(name)).id()
+}
+
+#[test]
+fn system_order_before_after() {
+    let world = World::new();
+    let log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+
+    let a = push_run(&mut world.system_named::<()>("A"), "A", &log);
+    let b = push_run(&mut world.system_named::<()>("B"), "B", &log);
+    push_run(world.system_named::<()>("C").before(a), "C", &log);
+    push_run(world.system_named::<()>("D").after(b).before(a), "D", &log);
+    push_run(&mut world.system_named::<()>("E"), "E", &log);
+    push_run(
+        world
+            .system_named::<()>("F")
+            .kind::<flecs::pipeline::PreUpdate>()
+            .before(b),
+        "F",
+        &log,
+    );
+
+    world.progress();
+    assert_eq!(*log.borrow(), ["F", "B", "C", "D", "A", "E"]);
+}
+
+#[test]
+fn system_order_sets() {
+    let world = World::new();
+    let log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+
+    // constraints on a set apply to systems that are added to the set later
+    push_run(
+        world
+            .system_named::<()>("Render")
+            .in_set::<RenderSet>()
+            .after_set::<PhysicsSet>(),
+        "Render",
+        &log,
+    );
+    push_run(
+        world.system_named::<()>("Input").before_set::<PhysicsSet>(),
+        "Input",
+        &log,
+    );
+    push_run(
+        world.system_named::<()>("Move").in_set::<PhysicsSet>(),
+        "Move",
+        &log,
+    );
+    push_run(
+        world.system_named::<()>("Collide").in_set::<PhysicsSet>(),
+        "Collide",
+        &log,
+    );
+
+    world.progress();
+    assert_eq!(*log.borrow(), ["Input", "Move", "Collide", "Render"]);
+
+    let order = world.resolve_system_order().unwrap();
+    let names: Vec<_> = order
+        .iter()
+        .map(|&s| world.entity_from_id(s).name())
+        .filter(|name| ["Input", "Move", "Collide", "Render"].contains(name))
+        .collect();
+    assert_eq!(names, ["Input", "Move", "Collide", "Render"]);
+}
+
+#[test]
+fn system_order_cycle() {
+    use flecs_ecs::addons::pipeline::SystemOrderError;
+
+    let world = World::new();
+    let log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+
+    let physics = push_run(
+        world
+            .system_named::<()>("Physics")
+            .in_set::<PhysicsSet>()
+            .after_set::<RenderSet>(),
+        "Physics",
+        &log,
+    );
+    let render = push_run(
+        world
+            .system_named::<()>("Render")
+            .in_set::<RenderSet>()
+            .after_set::<PhysicsSet>(),
+        "Render",
+        &log,
+    );
+
+    let err = world.resolve_system_order().unwrap_err();
+    assert_eq!(
+        err,
+        SystemOrderError::Cycle {
+            systems: vec![physics, render]
+        }
+    );
+    assert!(err.to_string().contains("cycle"));
+
+    // the systems keep running in their previous order
+    world.progress();
+    assert_eq!(*log.borrow(), ["Physics", "Render"]);
+}
+
+#[test]
+fn system_order_phase_conflict() {
+    use flecs_ecs::addons::pipeline::SystemOrderError;
+
+    let world = World::new();
+
+    let update = world.system::<()>().run(|_| {});
+    let post_update = world
+        .system::<()>()
+        .kind::<flecs::pipeline::PostUpdate>()
+        .before(update)
+        .run(|_| {});
+
+    let err = world.resolve_system_order().unwrap_err();
+    assert_eq!(
+        err,
+        SystemOrderError::PhaseConflict {
+            before: post_update.id(),
+            after: update.id()
+        }
+    );
+    assert!(err.to_string().contains("earlier phase"));
+}
+
+#[test]
+fn system_order_deleted_system() {
+    let world = World::new();
+    let log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+
+    let a = push_run(&mut world.system_named::<()>("A"), "A", &log);
+    let b = push_run(world.system_named::<()>("B").before(a), "B", &log);
+    push_run(world.system_named::<()>("C").after(a).before(b), "C", &log);
+    assert!(world.resolve_system_order().is_err());
+
+    // the constraints of a deleted system are removed with it
+    world.entity_from_id(b).destruct();
+    world.progress();
+    assert_eq!(*log.borrow(), ["A", "C"]);
+    assert!(world.resolve_system_order().is_ok());
+}
+
+#[test]
+fn system_fixed_timestep() {
+    use flecs_ecs::addons::pipeline::{FixedTime, FixedUpdate};
+
+    let world = World::new();
+
+    let steps = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let steps_fixed = steps.clone();
+    world
+        .system::<()>()
+        .kind::<FixedUpdate>()
+        .run(move |it| steps_fixed.borrow_mut().push(it.delta_time()));
+
+    // fixed systems don't run without a fixed timestep
+    world.progress_time(1.0);
+    assert!(steps.borrow().is_empty());
+
+    world.set_fixed_timestep(0.25);
+
+    world.progress_time(0.625);
+    assert_eq!(*steps.borrow(), [0.25, 0.25]);
+    world.get::<&FixedTime>(|time| {
+        assert_eq!(time.steps_this_frame, 2);
+        assert_eq!(time.alpha, 0.5);
+    });
+
+    steps.borrow_mut().clear();
+    world.progress_time(0.0625);
+    assert!(steps.borrow().is_empty());
+    world.get::<&FixedTime>(|time| {
+        assert_eq!(time.steps_this_frame, 0);
+        assert_eq!(time.alpha, 0.75);
+    });
+
+    world.progress_time(0.0625);
+    assert_eq!(steps.borrow().len(), 1);
+    world.get::<&FixedTime>(|time| assert_eq!(time.alpha, 0.0));
+}
+
+#[test]
+fn system_fixed_timestep_max_steps() {
+    use flecs_ecs::addons::pipeline::{FixedTime, FixedUpdate};
+
+    let world = World::new();
+    world.set_fixed_timestep(0.25);
+    world.set_max_fixed_steps(3);
+
+    let log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let log_fixed = log.clone();
+    world
+        .system::<()>()
+        .kind::<FixedUpdate>()
+        .run(move |_| log_fixed.borrow_mut().push("fixed"));
+    let log_update = log.clone();
+    world
+        .system::<&FixedTime>()
+        .term_at(0)
+        .singleton()
+        .each(move |time| {
+            assert_eq!(time.steps_this_frame, 3);
+            log_update.borrow_mut().push("update");
+        });
+
+    // the time that doesn't fit in the maximum number of steps is dropped
+    world.progress_time(10.125);
+    assert_eq!(*log.borrow(), ["fixed", "fixed", "fixed", "update"]);
+    world.get::<&FixedTime>(|time| {
+        assert_eq!(time.accumulator, 0.125);
+        assert_eq!(time.alpha, 0.5);
+    });
+}
+
+#[test]
+fn system_fixed_timestep_read_fixed_time() {
+    use flecs_ecs::addons::pipeline::{FixedTime, FixedUpdate};
+
+    let world = World::new();
+    world.set_fixed_timestep(0.25);
+
+    let steps = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let steps_fixed = steps.clone();
+    world
+        .system::<&FixedTime>()
+        .term_at(0)
+        .singleton()
+        .kind::<FixedUpdate>()
+        .each_iter(move |it, _, time| {
+            steps_fixed
+                .borrow_mut()
+                .push((time.steps_this_frame, it.delta_system_time()));
+        });
+
+    // fixed systems see the state of the step that runs
+    world.progress_time(0.75);
+    assert_eq!(*steps.borrow(), [(1, 0.25), (2, 0.25), (3, 0.25)]);
+    world.get::<&FixedTime>(|time| assert_eq!(time.steps_this_frame, 3));
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+enum GameState {
+    Menu,
+    Playing,
+    Paused,
+}
+
+#[derive(Component, PartialEq)]
+struct Level(u32);
+
+#[test]
+fn system_run_if() {
+    let world = World::new();
+
+    world.entity().set(Position { x: 0, y: 0 });
+    world
+        .system::<&mut Position>()
+        .run_if(|world| world.has::<Tag>())
+        .run_if(|world| world.has::<Velocity>())
+        .each(|p| p.x += 1);
+
+    world.progress();
+    world.add::<Tag>();
+    world.progress();
+    world.set(Velocity { x: 1, y: 1 });
+    world.progress();
+    world.progress();
+
+    world.each::<&Position>(|p| assert_eq!(p.x, 2));
+}
+
+#[test]
+fn system_run_if_skips_iteration() {
+    let world = World::new();
+
+    world.entity().set(Position { x: 0, y: 0 });
+    let iterations = std::rc::Rc::new(std::cell::Cell::new(0));
+    let count = iterations.clone();
+    let evaluations = std::rc::Rc::new(std::cell::Cell::new(0));
+    let evaluated = evaluations.clone();
+    world
+        .system::<&Position>()
+        .run_if(move |_| {
+            evaluated.set(evaluated.get() + 1);
+            false
+        })
+        .run(move |mut it| {
+            while it.next() {
+                count.set(count.get() + 1);
+            }
+        });
+
+    world.progress();
+    world.progress();
+    assert_eq!(evaluations.get(), 2);
+    assert_eq!(iterations.get(), 0);
+}
+
+#[test]
+fn system_run_if_no_terms() {
+    let world = World::new();
+
+    let count = std::rc::Rc::new(std::cell::Cell::new(0));
+    let counted = count.clone();
+    world
+        .system::<()>()
+        .run_if_singleton::<Tag>()
+        .run(move |_| counted.set(counted.get() + 1));
+
+    world.progress();
+    world.add::<Tag>();
+    world.progress();
+    world.remove::<Tag>();
+    world.progress();
+    assert_eq!(count.get(), 1);
+}
+
+#[test]
+fn system_run_if_singleton_changed() {
+    let world = World::new();
+
+    world.set(Mass { value: 1 });
+    let count = std::rc::Rc::new(std::cell::Cell::new(0));
+    let counted = count.clone();
+    world
+        .system::<()>()
+        .run_if_singleton_changed::<Mass>()
+        .run(move |_| counted.set(counted.get() + 1));
+
+    world.progress();
+    assert_eq!(count.get(), 0);
+
+    world.set(Mass { value: 1 });
+    world.progress();
+    assert_eq!(count.get(), 1);
+    world.progress();
+    assert_eq!(count.get(), 1);
+
+    world.set(Mass { value: 2 });
+    world.progress();
+    assert_eq!(count.get(), 2);
+    world.progress();
+    assert_eq!(count.get(), 2);
+}
+
+#[test]
+fn system_run_if_multi_threaded() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let world = World::new();
+    world.set_threads(4);
+    world.set(Mass { value: 1 });
+    for _ in 0..100 {
+        world.entity().set(Position { x: 0, y: 0 });
+    }
+
+    let evaluated = Arc::new(AtomicUsize::new(0));
+    let evaluated_condition = evaluated.clone();
+    world
+        .system::<&mut Position>()
+        .multi_threaded()
+        .run_if_singleton_changed::<Mass>()
+        .run_if(move |_| {
+            evaluated_condition.fetch_add(1, Ordering::SeqCst);
+            true
+        })
+        .each(|p| p.x += 1);
+
+    // a change is seen by all workers, and the conditions are evaluated once per run
+    world.set(Mass { value: 2 });
+    world.progress();
+    world.progress();
+    assert_eq!(evaluated.load(Ordering::SeqCst), 1);
+    world.each::<&Position>(|p| assert_eq!(p.x, 1));
+}
+
+#[test]
+fn system_run_if_multi_threaded_runs_per_frame() {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let world = World::new();
+    world.set_threads(4);
+    for _ in 0..100 {
+        world.entity().set(Position { x: 0, y: 0 });
+    }
+
+    let enabled = Arc::new(AtomicBool::new(true));
+    let evaluated = Arc::new(AtomicUsize::new(0));
+    let (enabled_condition, evaluated_condition) = (enabled.clone(), evaluated.clone());
+    world
+        .system::<&mut Position>()
+        .multi_threaded()
+        .run_if(move |_| {
+            evaluated_condition.fetch_add(1, Ordering::SeqCst);
+            enabled_condition.load(Ordering::SeqCst)
+        })
+        .each(|p| p.x += 1);
+
+    // the pipeline runs twice in the same frame, the workers don't reuse the first result
+    let pipeline = world.get_pipeline();
+    world.run_pipeline_id(pipeline);
+    enabled.store(false, Ordering::SeqCst);
+    world.run_pipeline_id(pipeline);
+
+    assert_eq!(evaluated.load(Ordering::SeqCst), 2);
+    world.each::<&Position>(|p| assert_eq!(p.x, 1));
+}
+
+#[test]
+fn system_run_if_state() {
+    let world = World::new();
+    world.component::<GameState>();
+
+    let log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let log_equals = log.clone();
+    world
+        .system::<()>()
+        .run_if_equals(Level(2))
+        .run(move |_| log_equals.borrow_mut().push("equals"));
+    let log_enum = log.clone();
+    world
+        .system::<()>()
+        .run_if_enum(GameState::Playing)
+        .run(move |_| log_enum.borrow_mut().push("enum"));
+
+    world.progress();
+    world.set(Level(1));
+    world.add_enum(GameState::Menu);
+    world.progress();
+    assert!(log.borrow().is_empty());
+
+    world.set(Level(2));
+    world.add_enum(GameState::Playing);
+    world.progress();
+    assert_eq!(*log.borrow(), ["equals", "enum"]);
+}
+
+#[test]
+fn system_run_if_ticked() {
+    let world = World::new();
+
+    let timer = world.timer().set_interval(2.0);
+    let count = std::rc::Rc::new(std::cell::Cell::new(0));
+    let counted = count.clone();
+    world.system::<()>().run_if_ticked(timer).run(move |it| {
+        assert_eq!(it.delta_time(), 1.0);
+        counted.set(counted.get() + 1);
+    });
+
+    for _ in 0..4 {
+        world.progress_time(1.0);
+    }
+    assert_eq!(count.get(), 2);
+}
+
+struct MoveCount {
+    count: std::sync::atomic::AtomicUsize,
+}
+
+impl MoveCount {
+    fn new(count: usize) -> Self {
+        Self {
+            count: std::sync::atomic::AtomicUsize::new(count),
+        }
+    }
+
+    fn get(&self) -> usize {
+        self.count.load(std::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+#[test]
+fn system_context_typed() {
+    let world = World::new();
+
+    world.entity().set(Position { x: 0, y: 0 });
+    world.entity().set(Position { x: 0, y: 0 });
+    let mut system = world
+        .system::<&Position>()
+        .set_context_typed(MoveCount::new(0))
+        .run(|mut it| {
+            while it.next() {
+                it.context_typed::<MoveCount>()
+                    .count
+                    .fetch_add(it.count(), std::sync::atomic::Ordering::SeqCst);
+            }
+        });
+
+    world.progress();
+    assert_eq!(system.context_typed::<MoveCount>().get(), 2);
+
+    // replacing the context keeps the callback of the system
+    system.set_context_typed(MoveCount::new(10));
+    world.progress();
+    assert_eq!(system.context_typed::<MoveCount>().get(), 12);
+}
+
+#[test]
+fn system_context_raw_after_typed() {
+    let world = World::new();
+
+    let mut value = 5_i32;
+    let mut system = world
+        .system::<()>()
+        .set_context_typed(MoveCount::new(0))
+        .run(|_| {});
+
+    // a raw context replaces the typed context, and isn't dropped with the system
+    let raw = &mut value as *mut i32 as *mut c_void;
+    system.set_context(raw);
+    assert_eq!(system.context(), raw);
+    world.progress();
+    system.destruct();
+    assert_eq!(value, 5);
+}
+
+#[test]
+#[should_panic(expected = "no context of type")]
+fn system_context_typed_raw() {
+    let world = World::new();
+
+    let mut value = 5_i32;
+    let mut system = world.system::<()>().run(|_| {});
+    system.set_context(&mut value as *mut i32 as *mut c_void);
+    system.context_typed::<i32>();
+}
+
+#[test]
+fn system_context_typed_drop() {
+    let dropped = std::rc::Rc::new(std::cell::Cell::new(false));
+
+    struct DropFlag(std::rc::Rc<std::cell::Cell<bool>>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.set(true);
+        }
+    }
+
+    let world = World::new();
+    let system = world
+        .system::<()>()
+        .set_context_typed(DropFlag(dropped.clone()))
+        .run(|_| {});
+    assert!(!dropped.get());
+
+    system.destruct();
+    assert!(dropped.get());
+}
+
+#[test]
+fn system_context_typed_query() {
+    let world = World::new();
+
+    world.entity().set(Position { x: 1, y: 2 });
+    let positions = world.new_query::<&Position>();
+    let system = world
+        .system::<()>()
+        .set_context_typed(positions)
+        .run(|_| {});
+    assert_eq!(system.context_typed::<Query<&Position>>().count(), 1);
+
+    // the query is dropped with the system before the world is destroyed
+    world.progress();
+}
+
+#[test]
+#[should_panic(expected = "context is not of type")]
+fn system_context_typed_mismatch() {
+    let world = World::new();
+
+    let system = world
+        .system::<()>()
+        .set_context_typed(MoveCount::new(0))
+        .run(|_| {});
+    system.context_typed::<Position>();
+}
+
+#[test]
+fn system_in_state() {
+    let world = World::new();
+    world.add_state(GameState::Menu);
+
+    let log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let log_playing = log.clone();
+    world
+        .system::<()>()
+        .in_state(GameState::Playing)
+        .run(move |_| log_playing.borrow_mut().push("playing"));
+    let log_active = log.clone();
+    world
+        .system::<()>()
+        .in_state(GameState::Playing)
+        .in_state(GameState::Paused)
+        .run(move |_| log_active.borrow_mut().push("active"));
+
+    world.progress();
+    assert!(log.borrow().is_empty());
+
+    // the state changes at the start of the next frame
+    world.set_state(GameState::Playing);
+    world.state::<GameState>(|state| assert_eq!(*state, GameState::Menu));
+    world.progress();
+    world.state::<GameState>(|state| assert_eq!(*state, GameState::Playing));
+    assert_eq!(*log.borrow(), ["playing", "active"]);
+
+    log.borrow_mut().clear();
+    world.set_state(GameState::Paused);
+    world.progress();
+    assert_eq!(*log.borrow(), ["active"]);
+}
+
+#[test]
+fn system_in_state_disabled() {
+    let world = World::new();
+    world.add_state(GameState::Menu);
+
+    let count = std::rc::Rc::new(std::cell::Cell::new(0));
+    let count_system = count.clone();
+    let system = world
+        .system::<()>()
+        .in_state(GameState::Playing)
+        .run(move |_| count_system.set(count_system.get() + 1));
+
+    // a system disabled by hand stays disabled when its state is entered
+    system.disable_self();
+    world.set_state(GameState::Playing);
+    world.progress();
+    assert_eq!(count.get(), 0);
+    assert!(!system.is_enabled_self());
+
+    system.enable_self();
+    world.progress();
+    assert_eq!(count.get(), 1);
+}
+
+#[test]
+fn system_in_state_not_added() {
+    let world = World::new();
+    // enum constants are registered with the same ids in every world
+    world.component::<GameState>();
+
+    let count = std::rc::Rc::new(std::cell::Cell::new(0));
+    let count_system = count.clone();
+    world
+        .system::<()>()
+        .in_state(GameState::Menu)
+        .run(move |_| count_system.set(count_system.get() + 1));
+
+    world.progress();
+    assert_eq!(count.get(), 0);
+
+    world.add_state(GameState::Menu);
+    world.progress();
+    assert_eq!(count.get(), 1);
+}
+
+#[test]
+fn system_on_enter_on_exit() {
+    let world = World::new();
+    world.add_state(GameState::Menu);
+
+    let log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let log_enter_menu = log.clone();
+    world
+        .system::<()>()
+        .on_enter(GameState::Menu)
+        .run(move |_| log_enter_menu.borrow_mut().push("enter menu"));
+    let log_exit_menu = log.clone();
+    world
+        .system::<()>()
+        .on_exit(GameState::Menu)
+        .run(move |_| log_exit_menu.borrow_mut().push("exit menu"));
+    let log_enter_playing = log.clone();
+    world
+        .system::<()>()
+        .on_enter(GameState::Playing)
+        .run(move |it| {
+            it.world()
+                .state::<GameState>(|state| assert_eq!(*state, GameState::Playing));
+            log_enter_playing.borrow_mut().push("enter playing");
+        });
+    let log_playing = log.clone();
+    world
+        .system::<()>()
+        .in_state(GameState::Playing)
+        .run(move |_| log_playing.borrow_mut().push("playing"));
+
+    world.progress();
+    world.progress();
+    assert_eq!(*log.borrow(), ["enter menu"]);
+
+    log.borrow_mut().clear();
+    // the last state set in a frame wins
+    world.set_state(GameState::Paused);
+    world.set_state(GameState::Playing);
+    world.progress();
+    assert_eq!(*log.borrow(), ["exit menu", "enter playing", "playing"]);
+
+    log.borrow_mut().clear();
+    world.set_state(GameState::Playing);
+    world.progress();
+    assert_eq!(*log.borrow(), ["playing"]);
+}
+
+#[test]
+fn system_state_transition_events() {
+    let world = World::new();
+    world.add_state(GameState::Menu);
+    world.progress();
+
+    let log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let log_add = log.clone();
+    world
+        .observer::<flecs::OnAdd, ()>()
+        .with_enum(GameState::Playing)
+        .run(move |mut it| {
+            while it.next() {
+                log_add.borrow_mut().push("add playing");
+            }
+        });
+    let log_remove = log.clone();
+    world
+        .observer::<flecs::OnRemove, ()>()
+        .with_enum(GameState::Menu)
+        .run(move |mut it| {
+            while it.next() {
+                log_remove.borrow_mut().push("remove menu");
+            }
+        });
+
+    world.set_state(GameState::Playing);
+    assert!(log.borrow().is_empty());
+    world.progress();
+    assert_eq!(*log.borrow(), ["remove menu", "add playing"]);
+}
+
+#[test]
+#[should_panic]
+fn system_set_state_not_added() {
+    let world = World::new();
+    world.set_state(GameState::Playing);
+}
+
+#[test]
+fn system_pipeline_schedule() {
+    let world = World::new();
+
+    let spawn = world
+        .system_named::<()>("Spawn")
+        .write::<Position>()
+        .run(|mut it| while it.next() {});
+    let integrate = world.system_named::<&Position>("Integrate").each(|_| {});
+    let render = world
+        .system_named::<&Position>("Render")
+        .kind::<flecs::pipeline::OnStore>()
+        .multi_threaded()
+        .each(|_| {});
+
+    world.entity().set(Position { x: 0, y: 0 });
+
+    let schedule = world.schedule();
+    assert_eq!(schedule.pipeline, world.get_pipeline().id());
+
+    let systems: Vec<_> = schedule.systems().map(|s| s.system).collect();
+    assert_eq!(systems, [spawn.id(), integrate.id(), render.id()]);
+
+    // reading Position after a deferred write inserts a sync point, as does changing threading
+    assert_eq!(schedule.ops.len(), 3);
+    assert_eq!(schedule.sync_points(), 2);
+    assert_eq!(schedule.ops[0].systems[0].name, "Spawn");
+    assert_eq!(schedule.ops[0].systems[0].phase_name, "OnUpdate");
+    assert!(!schedule.ops[1].multi_threaded);
+    assert!(schedule.ops[2].multi_threaded);
+    assert_eq!(schedule.ops[2].systems[0].phase_name, "OnStore");
+}
+
+#[test]
+fn system_pipeline_schedule_dot_mermaid() {
+    let world = World::new();
+
+    let pipeline = world.pipeline().with::<flecs::system::System>().build();
+    let a = world
+        .system_named::<()>("A")
+        .write::<Position>()
+        .run(|mut it| while it.next() {});
+    let b = world.system_named::<&Position>("B").each(|_| {});
+    world.entity().set(Position { x: 0, y: 0 });
+
+    let schedule = pipeline.schedule();
+    assert_eq!(
+        schedule.to_dot(),
+        format!(
+            "digraph pipeline {{
+    rankdir=TB;
+    node [shape=box];
+    subgraph cluster_op0 {{
+        label=\"op 0\";
+        s{a} [label=\"A\\nOnUpdate\"];
+    }}
+    sync0 [label=\"sync point\", shape=diamond];
+    subgraph cluster_op1 {{
+        label=\"op 1\";
+        s{b} [label=\"B\\nOnUpdate\"];
+    }}
+    s{a} -> sync0;
+    sync0 -> s{b};
+}}
+",
+            a = a.id(),
+            b = b.id()
+        )
+    );
+    assert_eq!(
+        schedule.to_mermaid(),
+        format!(
+            "flowchart TD
+    subgraph op0 [\"op 0\"]
+        s{a}[\"A<br/>OnUpdate\"]
+    end
+    sync0{{{{\"sync point\"}}}}
+    subgraph op1 [\"op 1\"]
+        s{b}[\"B<br/>OnUpdate\"]
+    end
+    s{a} --> sync0
+    sync0 --> s{b}
+",
+            a = a.id(),
+            b = b.id()
+        )
+    );
+}
+
+#[test]
+fn system_run_hooks() {
+    let world = World::new();
+
+    let log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let log_system = log.clone();
+    world
+        .system_named::<&Position>("Each")
+        .each(move |_| log_system.borrow_mut().push("Each".to_string()));
+
+    let log_pre = log.clone();
+    let log_post = log.clone();
+    let hook = world.on_system_run(
+        move |run| {
+            assert!(!run.is_observer);
+            assert_eq!(run.entity_count, 0);
+            log_pre.borrow_mut().push(format!(
+                "pre {} {}",
+                run.entity.name(),
+                world_name(run.entity, run.phase)
+            ));
+        },
+        move |run, _| {
+            log_post
+                .borrow_mut()
+                .push(format!("post {} {}", run.entity.name(), run.entity_count))
+        },
+    );
+
+    let log_system = log.clone();
+    world
+        .system_named::<()>("Run")
+        .kind::<flecs::pipeline::OnStore>()
+        .run(move |_| log_system.borrow_mut().push("Run".to_string()));
+
+    // entities are counted while a system iterates them in a run callback
+    let log_system = log.clone();
+    world
+        .system_named::<&Position>("RunIter")
+        .kind::<flecs::pipeline::OnStore>()
+        .run_if(|_| true)
+        .run(move |mut it| {
+            while it.next() {
+                log_system.borrow_mut().push("RunIter".to_string());
+            }
+        });
+
+    world.entity().set(Position { x: 0, y: 0 });
+    world.entity().set(Position { x: 1, y: 0 });
+    world.progress();
+    assert_eq!(
+        *log.borrow(),
+        [
+            "pre Each OnUpdate",
+            "Each",
+            "Each",
+            "post Each 2",
+            "pre Run OnStore",
+            "Run",
+            "post Run 0",
+            "pre RunIter OnStore",
+            "RunIter",
+            "post RunIter 2"
+        ]
+    );
+
+    log.borrow_mut().clear();
+    world.remove_system_run_hook(hook);
+    world.progress();
+    assert_eq!(*log.borrow(), ["Each", "Each", "Run", "RunIter"]);
+}
+
+fn world_name(entity: EntityView, id: Entity) -> String {
+    entity.world().entity_from_id(id).name().to_string()
+}
+
+#[test]
+fn system_run_hooks_multi_threaded() {
+    let world = World::new();
+    world.set_threads(4);
+    for _ in 0..100 {
+        world.entity().set(Position { x: 0, y: 0 });
+    }
+
+    world
+        .system_named::<&mut Position>("Move")
+        .multi_threaded()
+        .each(|p| p.x += 1);
+
+    let runs = std::rc::Rc::new(std::cell::Cell::new(0));
+    let runs_pre = runs.clone();
+    world.on_system_run(move |_| runs_pre.set(runs_pre.get() + 1), |_, _| {});
+
+    // the hooks are only called for the main thread
+    world.progress();
+    assert_eq!(runs.get(), 1);
+    world.each::<&Position>(|p| assert_eq!(p.x, 1));
+}
+
+#[test]
+fn system_run_hooks_nested() {
+    let world = World::new();
+    world.set_fixed_timestep(1.0);
+
+    world
+        .system_named::<()>("Fixed")
+        .kind::<flecs_ecs::addons::pipeline::FixedUpdate>()
+        .run(|_| {});
+
+    let log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let log_pre = log.clone();
+    let log_post = log.clone();
+    world.on_system_run(
+        move |run| {
+            log_pre
+                .borrow_mut()
+                .push(format!("pre {}", run.entity.id()))
+        },
+        move |run, _| {
+            log_post
+                .borrow_mut()
+                .push(format!("post {}", run.entity.id()))
+        },
+    );
+
+    world.progress_time(1.0);
+    let fixed = world.lookup("Fixed").id();
+    let log = log.borrow();
+    let fixed_pre = log.iter().position(|e| *e == format!("pre {}", fixed));
+    let fixed_post = log.iter().position(|e| *e == format!("post {}", fixed));
+    // the fixed system runs inside the system that runs the fixed steps
+    assert_eq!(log.len(), 4);
+    assert_eq!(fixed_pre, Some(1));
+    assert_eq!(fixed_post, Some(2));
+}
+
+#[test]
+fn system_record_chrome_trace() {
+    let world = World::new();
+
+    world
+        .system_named::<&Position>("Move \"fast\"")
+        .each(|_| {});
+    world
+        .observer_named::<flecs::OnAdd, &Velocity>("AddVelocity")
+        .each(|_| {});
+    world.entity().set(Position { x: 0, y: 0 });
+
+    let path = std::env::temp_dir().join(format!("flecs_chrome_trace_{}.json", std::process::id()));
+    let trace = world.record_chrome_trace(&path, 2);
+
+    world.progress();
+    assert!(!trace.is_finished());
+    world.entity().set(Velocity { x: 0, y: 0 });
+    world.progress();
+    assert!(trace.is_finished());
+    assert!(trace.take_error().is_none());
+    world.progress();
+
+    let json = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(json.starts_with("{\"traceEvents\":["));
+    assert_eq!(json.matches("\"name\":\"Move \\\"fast\\\"\"").count(), 2);
+    assert_eq!(json.matches("\"cat\":\"system\"").count(), 2);
+    assert_eq!(
+        json.matches("\"args\":{\"phase\":\"OnUpdate\",\"entities\":1}")
+            .count(),
+        2
+    );
+    assert_eq!(
+        json.matches("\"name\":\"AddVelocity\",\"cat\":\"observer\"")
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn system_deterministic_step_frames() {
+    let world = World::new();
+    world.set_deterministic(0.25);
+    assert_eq!(world.get_deterministic(), Some(0.25));
+
+    let delta_times = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let system_delta_times = delta_times.clone();
+    world.system::<()>().run(move |mut it| {
+        while it.next() {}
+        system_delta_times.borrow_mut().push(it.delta_time());
+    });
+
+    let interval_count = std::rc::Rc::new(std::cell::RefCell::new(0));
+    let interval_system_count = interval_count.clone();
+    world.system::<()>().interval(1.0).run(move |mut it| {
+        while it.next() {}
+        *interval_system_count.borrow_mut() += 1;
+    });
+
+    let rate_count = std::rc::Rc::new(std::cell::RefCell::new(0));
+    let rate_system_count = rate_count.clone();
+    let timer = world.timer().set_interval(0.5);
+    world
+        .system::<()>()
+        .rate_w_tick_source(timer, 2)
+        .run(move |mut it| {
+            while it.next() {}
+            *rate_system_count.borrow_mut() += 1;
+        });
+
+    // a target fps doesn't make deterministic frames sleep
+    world.set_target_fps(1.0);
+    assert!(world.step_frames(8));
+
+    assert_eq!(*delta_times.borrow(), [0.25; 8]);
+    assert_eq!(*interval_count.borrow(), 2);
+    assert_eq!(*rate_count.borrow(), 2);
+    assert_eq!(world.delta_time(), 0.25);
+    assert_eq!(world.time(), 2.0);
+    assert_eq!(world.tick(), 8);
+
+    world.set_time_scale(2.0);
+    world.step_frames(2);
+    assert_eq!(world.delta_time(), 0.5);
+    assert_eq!(world.time(), 3.0);
+
+    world.reset_clock();
+    assert_eq!(world.time(), 0.0);
+
+    assert_eq!(world.get_target_fps(), 0.0);
+    world.set_deterministic(0.0);
+    assert_eq!(world.get_deterministic(), None);
+    assert_eq!(world.get_target_fps(), 1.0);
+}
+
+#[test]
+fn system_deterministic_restore_frame_time() {
+    let world = World::new();
+    let measuring = || unsafe { flecs_ecs_sys::ecs_rust_is_measuring_frame_time(world.ptr_mut()) };
+
+    unsafe { flecs_ecs_sys::ecs_measure_frame_time(world.ptr_mut(), true) };
+    world.set_deterministic(0.25);
+    assert!(!measuring());
+    world.step_frames(2);
+
+    world.set_deterministic(0.0);
+    assert!(measuring());
+
+    unsafe { flecs_ecs_sys::ecs_measure_frame_time(world.ptr_mut(), false) };
+    world.set_deterministic(0.25);
+    world.set_deterministic(0.0);
+    assert!(!measuring());
+}
+
+#[test]
+#[should_panic(expected = "step_frames requires a deterministic world")]
+fn system_step_frames_not_deterministic() {
+    let world = World::new();
+    world.step_frames(1);
+}
+
+#[test]
+fn system_deterministic_sequential() {
+    fn processed_order(threads: i32, sequential: bool) -> Vec<u64> {
+        let world = World::new();
+        world.set_deterministic(1.0);
+        world.set_threads(threads);
+        world.set_sequential(sequential);
+
+        for x in 0..64 {
+            world.entity().set(Position { x, y: 0 });
+        }
+
+        let order = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let system_order = order.clone();
+        world
+            .system::<&Position>()
+            .multi_threaded()
+            .each_entity(move |e, _| system_order.lock().unwrap().push(*e.id()));
+
+        world.step_frames(2);
+        let order = order.lock().unwrap().clone();
+        order
+    }
+
+    let single_threaded = processed_order(0, false);
+    assert_eq!(single_threaded.len(), 128);
+    assert_eq!(processed_order(4, true), single_threaded);
+    assert_eq!(processed_order(8, true), single_threaded);
+
+    let world = World::new();
+    world.set_threads(4);
+    world.set_sequential(true);
+    assert!(world.is_sequential());
+    assert_eq!(world.get_threads(), 1);
+    world.set_threads(2);
+    assert_eq!(world.get_threads(), 1);
+    world.set_sequential(false);
+    assert_eq!(world.get_threads(), 2);
+}
+
+#[test]
+fn system_timer_after() {
+    let world = World::new();
+    world.set_deterministic(1.0);
+
+    let fired = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let after_fired = fired.clone();
+    let timer = world.after(2.5, move |world| {
+        after_fired.borrow_mut().push(world.tick());
+    });
+    assert!(world.is_timer_active(timer));
+
+    world.step_frames(2);
+    assert!(fired.borrow().is_empty());
+    world.step_frames(1);
+    assert_eq!(*fired.borrow(), [2]);
+    assert!(!world.is_timer_active(timer));
+    assert!(!world.entity_from_id(timer.entity()).is_alive());
+
+    world.step_frames(3);
+    assert_eq!(*fired.borrow(), [2]);
+    assert!(!world.cancel_timer(timer));
+}
+
+#[test]
+fn system_timer_every_entity() {
+    let world = World::new();
+    world.set_deterministic(0.5);
+
+    let player = world.entity().set(Position { x: 0, y: 0 });
+    let other = world.entity();
+    let count = std::rc::Rc::new(std::cell::RefCell::new(0));
+    let every_count = count.clone();
+    let player_id = player.id();
+    let timer = player.every(1.0, move |e| {
+        assert_eq!(e.id(), player_id);
+        e.get::<&mut Position>(|p| p.x += 1);
+        *every_count.borrow_mut() += 1;
+    });
+    let other_timer = other.every(1.0, |_| {});
+
+    world.step_frames(5);
+    player.get::<&Position>(|p| assert_eq!(p.x, 2));
+    assert!(world.is_timer_active(timer));
+
+    // timers are deleted with their entity, which drops the callback
+    player.destruct();
+    assert!(!world.is_timer_active(timer));
+    assert!(world.is_timer_active(other_timer));
+    assert_eq!(std::rc::Rc::strong_count(&count), 1);
+    world.step_frames(4);
+    assert_eq!(*count.borrow(), 2);
+}
+
+#[test]
+fn system_timer_every_multiple_intervals() {
+    let world = World::new();
+    world.set_deterministic(2.5);
+
+    let count = std::rc::Rc::new(std::cell::RefCell::new(0));
+    let every_count = count.clone();
+    world.every(1.0, move |_| *every_count.borrow_mut() += 1);
+
+    world.step_frames(2);
+    assert_eq!(*count.borrow(), 5);
+}
+
+#[test]
+fn system_timer_cancel() {
+    let world = World::new();
+    world.set_deterministic(1.0);
+
+    let count = std::rc::Rc::new(std::cell::RefCell::new(0));
+    let every_count = count.clone();
+    let timer = world.every(1.0, move |_| *every_count.borrow_mut() += 1);
+
+    world.step_frames(2);
+    assert!(world.cancel_timer(timer));
+    assert!(!world.is_timer_active(timer));
+    assert!(!world.cancel_timer(timer));
+    world.step_frames(2);
+    assert_eq!(*count.borrow(), 2);
+}
+
+#[test]
+fn system_timer_pause() {
+    let world = World::new();
+    world.set_deterministic(1.0);
+
+    let count = std::rc::Rc::new(std::cell::RefCell::new(0));
+    let every_count = count.clone();
+    let timer = world.every(2.0, move |_| *every_count.borrow_mut() += 1);
+
+    world.step_frames(1);
+    assert!(world.pause_timer(timer));
+    assert!(world.is_timer_paused(timer));
+    assert!(world.is_timer_active(timer));
+    world.step_frames(5);
+    assert_eq!(*count.borrow(), 0);
+
+    // the time elapsed before the pause is kept
+    assert!(world.resume_timer(timer));
+    assert!(!world.is_timer_paused(timer));
+    world.step_frames(1);
+    assert_eq!(*count.borrow(), 1);
+
+    world.cancel_timer(timer);
+    assert!(!world.pause_timer(timer));
+    assert!(!world.is_timer_paused(timer));
+}
+
+#[test]
+fn system_timer_pause_from_callback() {
+    let world = World::new();
+    world.set_deterministic(1.0);
+
+    let other_count = std::rc::Rc::new(std::cell::RefCell::new(0));
+    let every_other_count = other_count.clone();
+    let other = world.every(1.0, move |_| *every_other_count.borrow_mut() += 1);
+
+    let count = std::rc::Rc::new(std::cell::RefCell::new(0));
+    let handle = std::rc::Rc::new(std::cell::Cell::new(None));
+    let every_count = count.clone();
+    let every_handle = handle.clone();
+    let timer = world.every(1.0, move |world| {
+        *every_count.borrow_mut() += 1;
+        // the timer system holds the timers, so the changes are queued
+        assert!(world.pause_timer(every_handle.get().unwrap()));
+        assert!(world.pause_timer(other));
+        assert!(world.is_timer_paused(other));
+    });
+    handle.set(Some(timer));
+
+    world.step_frames(3);
+    assert_eq!(*count.borrow(), 1);
+    assert_eq!(*other_count.borrow(), 1);
+    assert!(world.is_timer_paused(timer));
+    assert!(world.is_timer_paused(other));
+
+    world.resume_timer(timer);
+    world.resume_timer(other);
+    world.step_frames(1);
+    assert_eq!(*count.borrow(), 2);
+    assert_eq!(*other_count.borrow(), 2);
+}
+
+#[test]
+fn system_timer_cancel_from_callback() {
+    let world = World::new();
+    world.set_deterministic(1.0);
+
+    let log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let handle = std::rc::Rc::new(std::cell::Cell::new(None));
+
+    let every_log = log.clone();
+    let every_handle = handle.clone();
+    let timer = world.every(1.0, move |world| {
+        every_log.borrow_mut().push("every");
+        if every_log.borrow().len() == 2 {
+            world.cancel_timer(every_handle.get().unwrap());
+
+            // timers can be started from callbacks
+            let after_log = every_log.clone();
+            world.after(1.0, move |_| after_log.borrow_mut().push("after"));
+        }
+    });
+    handle.set(Some(timer));
+
+    world.step_frames(5);
+    assert_eq!(*log.borrow(), ["every", "every", "after"]);
+}
+
+#[test]
+fn system_timer_time_scale() {
+    let world = World::new();
+    world.set_deterministic(1.0);
+
+    let count = std::rc::Rc::new(std::cell::RefCell::new(0));
+    let every_count = count.clone();
+    world.every(2.0, move |_| *every_count.borrow_mut() += 1);
+
+    world.step_frames(1);
+    world.set_time_scale(0.0);
+    world.step_frames(10);
+    assert_eq!(*count.borrow(), 0);
+
+    world.set_time_scale(1.0);
+    world.step_frames(1);
+    assert_eq!(*count.borrow(), 1);
+
+    world.set_time_scale(2.0);
+    world.step_frames(2);
+    assert_eq!(*count.borrow(), 3);
+}
@@ -1,5 +1,9 @@
 #![allow(dead_code)]
-use flecs_ecs::core::World;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use flecs_ecs::addons::app::AppFrames;
+use flecs_ecs::prelude::*;
 
 #[test]
 fn world_no_panic_clone_test() {
@@ -25,3 +29,108 @@ fn world_panic_lingering_query_handles() {
     let _query = world.new_query::<()>();
     std::mem::drop(world);
 }
+
+#[test]
+fn world_context_typed() {
+    let world = World::default();
+
+    world.set_context_typed(5_u32);
+    assert_eq!(*world.context_typed::<u32>(), 5);
+
+    world.set_context_typed(String::from("replaced"));
+    assert_eq!(world.context_typed::<String>(), "replaced");
+}
+
+#[test]
+fn world_context_raw_after_typed() {
+    let world = World::default();
+    let dropped = Rc::new(RefCell::new(false));
+
+    struct DropFlag(Rc<RefCell<bool>>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            *self.0.borrow_mut() = true;
+        }
+    }
+
+    // a raw context drops the typed context it replaces
+    world.set_context_typed(DropFlag(dropped.clone()));
+    let mut value = 5_u32;
+    world.set_context(&mut value as *mut u32 as *mut std::ffi::c_void, None);
+    assert!(*dropped.borrow());
+    assert_eq!(world.context() as *const u32, &value as *const u32);
+}
+
+#[test]
+#[should_panic(expected = "no context of type")]
+fn world_context_typed_unset() {
+    let world = World::default();
+    world.context_typed::<u32>();
+}
+
+#[test]
+fn world_app_hooks() {
+    let world = World::default();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let on_init = log.clone();
+    let on_frame_begin = log.clone();
+    let on_frame_end = log.clone();
+    let on_quit = log.clone();
+    let result = world
+        .app()
+        .set_frames(2)
+        .set_delta_time(0.5)
+        .on_init(move |_| on_init.borrow_mut().push(String::from("init")))
+        .on_frame_begin(move |_, _| on_frame_begin.borrow_mut().push(String::from("begin")))
+        .on_frame_end(move |_, dt| on_frame_end.borrow_mut().push(format!("end {}", dt)))
+        .on_quit(move |world| {
+            assert!(world.should_quit());
+            on_quit.borrow_mut().push(String::from("quit"));
+        })
+        .run();
+
+    assert_eq!(result, 0);
+    assert_eq!(
+        *log.borrow(),
+        ["init", "begin", "end 0.5", "begin", "end 0.5", "quit"]
+    );
+}
+
+#[test]
+fn world_app_frame_action() {
+    let world = World::default();
+    let frames_ran = Rc::new(RefCell::new(0));
+
+    let on_frame_end = frames_ran.clone();
+    world
+        .app()
+        .on_frame_end(move |_, _| *on_frame_end.borrow_mut() += 1)
+        .set_frame_action(|frames: &mut AppFrames| {
+            while frames.run_frame() {
+                if frames.frame_count() == 3 {
+                    frames.world().quit();
+                }
+            }
+            assert_eq!(frames.frame_count(), 3);
+        })
+        .run();
+
+    // the frame after the quit request doesn't end
+    assert_eq!(*frames_ran.borrow(), 3);
+    assert!(world.should_quit());
+}
+
+#[test]
+fn world_app_deterministic() {
+    let world = World::default();
+    world.set_deterministic(0.5);
+
+    world.app().set_frames(4).set_target_fps(1.0).run();
+
+    assert_eq!(world.time(), 2.0);
+    assert_eq!(world.get_target_fps(), 0.0);
+    world.set_deterministic(0.0);
+    assert_eq!(world.get_target_fps(), 1.0);
+}
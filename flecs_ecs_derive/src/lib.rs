@@ -1071,7 +1071,7 @@ impl Parse for Term {
         let reference = input.parse::<Reference>()?;
         if peek_id(&input) {
             let initial = input.parse::<TermId>()?;
-            if !input.peek(Token![,]) && !input.is_empty() {
+            if !input.peek(Token![,]) && !input.peek(Token![||]) && !input.is_empty() {
                 // Component or pair with explicit source
                 let inner;
                 parenthesized!(inner in input);
@@ -1263,18 +1263,56 @@ fn expand_term_type(term: &Term) -> Option<TokenStream> {
 
     match &term.oper {
         TermOper::Optional => Some(quote! { Option<#access_type> }),
-        TermOper::And => Some(quote! { #access_type }),
+        TermOper::And | TermOper::Or => Some(quote! { #access_type }),
         _ => None,
     }
 }
 
+/// Nests the types of static terms joined by `||` in `Either`s: `A || B || C` becomes
+/// `Either<A, Either<B, C>>`.
+fn expand_alternatives(alternatives: &[TokenStream]) -> TokenStream {
+    match alternatives {
+        [ty] => ty.clone(),
+        [first, rest @ ..] => {
+            let rest = expand_alternatives(rest);
+            quote! { flecs_ecs::core::Either<#first, #rest> }
+        }
+        [] => quote! { () },
+    }
+}
+
 fn expand_dsl(terms: &mut [Term]) -> (TokenStream, Vec<TokenStream>) {
     let mut iter_terms = Vec::new();
+    let mut static_terms = 0;
+    let mut alternatives = Vec::new();
+    let mut errors = Vec::new();
     for t in terms.iter() {
-        match expand_term_type(t) {
-            Some(ty) => iter_terms.push(ty),
-            None => break,
+        let Some(ty) = expand_term_type(t) else {
+            break;
         };
+        if !alternatives.is_empty() && t.oper == TermOper::Optional {
+            errors.push(quote_spanned! {
+                t.span => ; compile_error!("Optional terms can't be combined with '||'.")
+            });
+        }
+        static_terms += 1;
+        alternatives.push(ty);
+        if t.oper != TermOper::Or {
+            iter_terms.push(expand_alternatives(&alternatives));
+            alternatives.clear();
+        } else if !terms
+            .get(static_terms)
+            .is_some_and(|next| next.reference != Reference::None)
+        {
+            errors.push(quote_spanned! {
+                t.span => ; compile_error!("'||' must join two static terms, or two dynamic terms.")
+            });
+            iter_terms.push(expand_alternatives(&alternatives));
+            alternatives.clear();
+        }
+    }
+    if !alternatives.is_empty() {
+        iter_terms.push(expand_alternatives(&alternatives));
     }
     let iter_type = if iter_terms.len() == 1 {
         quote! {
@@ -1287,14 +1325,14 @@ fn expand_dsl(terms: &mut [Term]) -> (TokenStream, Vec<TokenStream>) {
             )*)
         }
     };
-    let builder_calls = terms
+    let mut builder_calls = terms
         .iter()
         .enumerate()
         .filter_map(|(i, t)| {
             let index = i as u32;
             let mut ops = Vec::new();
             let mut needs_accessor = false;
-            let iter_term = i < iter_terms.len();
+            let iter_term = i < static_terms;
             let mut term_accessor = if !iter_term {
                 quote! { .term() }
             } else {
@@ -1400,9 +1438,9 @@ fn expand_dsl(terms: &mut [Term]) -> (TokenStream, Vec<TokenStream>) {
             // Configure operator
 
             if iter_term {
-                if !matches!(t.oper, TermOper::And | TermOper::Optional) {
+                if !matches!(t.oper, TermOper::And | TermOper::Optional | TermOper::Or) {
                     ops.push(quote_spanned!{
-                        t.span => ; compile_error!("Only 'optional' and 'and' operators allowed for static terms.")
+                        t.span => ; compile_error!("Only 'optional', 'or' and 'and' operators allowed for static terms.")
                     });
                 }
             } else {
@@ -1457,6 +1495,7 @@ fn expand_dsl(terms: &mut [Term]) -> (TokenStream, Vec<TokenStream>) {
             }
         })
         .collect::<Vec<_>>();
+    builder_calls.extend(errors);
     (iter_type, builder_calls)
 }
 
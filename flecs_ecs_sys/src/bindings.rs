@@ -8032,6 +8032,31 @@ extern "C" {
         table: *mut ecs_table_t,
     ) -> i32;
 }
+#[doc = "Operation of a pipeline schedule.\n Every operation runs its systems and then merges the commands they enqueued."]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ecs_rust_pipeline_op_t {
+    pub offset: i32,
+    pub count: i32,
+    pub multi_threaded: bool,
+    pub immediate: bool,
+}
+extern "C" {
+    #[doc = "Builds the schedule of a pipeline if it's out of date, and copies at most\n `op_capacity` ops and `system_capacity` systems to `ops` and `systems`.\n Returns the number of ops, and stores the number of systems in\n `system_count`. Returns -1 if the entity is not a pipeline."]
+    pub fn ecs_rust_pipeline_schedule(
+        world: *mut ecs_world_t,
+        pipeline: ecs_entity_t,
+        ops: *mut ecs_rust_pipeline_op_t,
+        op_capacity: i32,
+        systems: *mut ecs_entity_t,
+        system_capacity: i32,
+        system_count: *mut i32,
+    ) -> i32;
+}
+extern "C" {
+    #[doc = "Returns whether the world measures the time between frames, which is\n enabled with `ecs_measure_frame_time` or `ecs_set_target_fps`."]
+    pub fn ecs_rust_is_measuring_frame_time(world: *const ecs_world_t) -> bool;
+}
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct ecs_event_id_record_t {
@@ -102,3 +102,63 @@ error:
     return -1;
 }
 
+
+#ifdef FLECS_PIPELINE
+
+int32_t ecs_rust_pipeline_schedule(
+    ecs_world_t *world,
+    ecs_entity_t pipeline,
+    ecs_rust_pipeline_op_t *ops,
+    int32_t op_capacity,
+    ecs_entity_t *systems,
+    int32_t system_capacity,
+    int32_t *system_count)
+{
+    ecs_check(world != NULL, ECS_INVALID_PARAMETER, NULL);
+    flecs_poly_assert(world, ecs_world_t);
+    ecs_check(!(world->flags & EcsWorldReadonly), ECS_INVALID_OPERATION,
+        "cannot build pipeline schedule while world is in readonly mode");
+
+    const EcsPipeline *p = ecs_get(world, pipeline, EcsPipeline);
+    if (!p || !p->state) {
+        return -1;
+    }
+
+    ecs_pipeline_state_t *pq = p->state;
+
+    /* Make sure the pipeline query matches the current set of systems */
+    ecs_run_aperiodic(world, 0);
+    flecs_pipeline_build(world, pq);
+
+    int32_t i, op_count = ecs_vec_count(&pq->ops);
+    ecs_pipeline_op_t *pq_ops = ecs_vec_first_t(&pq->ops, ecs_pipeline_op_t);
+    for (i = 0; i < op_count && i < op_capacity; i ++) {
+        ops[i].offset = pq_ops[i].offset;
+        ops[i].count = pq_ops[i].count;
+        ops[i].multi_threaded = pq_ops[i].multi_threaded;
+        ops[i].immediate = pq_ops[i].immediate;
+    }
+
+    int32_t count = ecs_vec_count(&pq->systems);
+    ecs_entity_t *pq_systems = ecs_vec_first_t(&pq->systems, ecs_entity_t);
+    for (i = 0; i < count && i < system_capacity; i ++) {
+        systems[i] = pq_systems[i];
+    }
+
+    if (system_count) {
+        *system_count = count;
+    }
+
+    return op_count;
+error:
+    return -1;
+}
+
+#endif
+
+bool ecs_rust_is_measuring_frame_time(
+    const ecs_world_t *world)
+{
+    flecs_poly_assert(world, ecs_world_t);
+    return ECS_BIT_IS_SET(world->flags, EcsWorldMeasureFrameTime);
+}
@@ -22,3 +22,32 @@ FLECS_API
     ecs_id_t id,
     ecs_table_t* table);
 
+
+/** Operation of a pipeline schedule.
+ * Every operation runs its systems and then merges the commands they enqueued. */
+typedef struct ecs_rust_pipeline_op_t {
+    int32_t offset;             /* Offset of the first system of the op */
+    int32_t count;              /* Number of systems in the op */
+    bool multi_threaded;        /* Whether the systems run multi threaded */
+    bool immediate;             /* Whether the systems run without staging */
+} ecs_rust_pipeline_op_t;
+
+/** Builds the schedule of a pipeline if it's out of date, and copies at most
+ * `op_capacity` ops and `system_capacity` systems to `ops` and `systems`.
+ * Returns the number of ops, and stores the number of systems in
+ * `system_count`. Returns -1 if the entity is not a pipeline. */
+FLECS_API
+int32_t ecs_rust_pipeline_schedule(
+    ecs_world_t *world,
+    ecs_entity_t pipeline,
+    ecs_rust_pipeline_op_t *ops,
+    int32_t op_capacity,
+    ecs_entity_t *systems,
+    int32_t system_capacity,
+    int32_t *system_count);
+
+/** Returns whether the world measures the time between frames, which is
+ * enabled with `ecs_measure_frame_time` or `ecs_set_target_fps`. */
+FLECS_API
+bool ecs_rust_is_measuring_frame_time(
+    const ecs_world_t *world);
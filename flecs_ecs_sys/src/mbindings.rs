@@ -120,12 +120,12 @@ pub struct WorldInfo {
     pub emit_time_total: f32,
     /// Total time spent in merges.
     pub merge_time_total: f32,
-    /// Time elapsed in simulation.
-    pub world_time_total: f32,
-    /// Time elapsed in simulation (no scaling).
-    pub world_time_total_raw: f32,
     /// Time spent on query rematching.
     pub rematch_time_total: f32,
+    /// Time elapsed in simulation.
+    pub world_time_total: f64,
+    /// Time elapsed in simulation (no scaling).
+    pub world_time_total_raw: f64,
     /// Total number of frames.
     pub frame_count_total: i64,
     /// Total number of merges.